@@ -1,21 +1,24 @@
-use std::fs::File;
-use std::io::Read;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process;
 use std::time::SystemTime;
 
-use chrono::{Datelike, TimeZone, Timelike, Utc, Local};
-use clap::{Arg, ArgAction, Command};
+use chrono::{TimeZone, Utc, Local, NaiveDate};
+use chrono_tz::Tz;
+use clap::{Arg, ArgAction, ArgGroup, Command};
 use timex_datalink::{
+    helpers::appointment_time::RoundingPolicy,
     helpers::crc_packets_wrapper::wrap_packets_with_crc,
-    char_encoders::CharString,
     devices::timex_datalink_150::TimexData,
-    protocol_3::{
-        eeprom::{Anniversary, Appointment, Eeprom, List, PhoneNumber},
-        time::DateFormat,
-        Alarm, End, SoundOptions, SoundTheme, Start, Sync, Time, WristApp,
-    },
-    NotebookAdapter, OnePlus6LedAdapter, PacketGenerator, Protocol3,
+    import::{ics::IcsImport, todo::{TodoFormat, TodoImport}, vcard},
+    models::{self, DateFormat, NotificationMinutes, PhoneType, Priority},
+    packet_annotator::{annotate_packets, format_listing},
+    protocol_3::WristApp,
+    spc::Spc,
+    sync_snapshot::{IncrementalSession, SnapshotStore},
+    transfer_estimate::{estimate_transfer, AdapterTiming},
+    zap::builder::ZapBuilder,
+    watch_config::{ProtocolVersion, WatchConfig},
+    NotebookAdapter, OnePlus6LedAdapter, PacketGenerator,
 };
 
 // Custom beep model equivalent to Ruby's Beep class
@@ -28,91 +31,182 @@ impl PacketGenerator for Beep {
     }
 }
 
-// Helper function to create a SystemTime from date components
-fn system_time_from_date(year: i32, month: u32, day: u32, hour: u32, min: u32) -> SystemTime {
-    let naive_dt = chrono::NaiveDate::from_ymd_opt(year, month, day)
-        .unwrap()
-        .and_hms_opt(hour, min, 0)
-        .unwrap();
+// Snapshot file in the home directory, or the current directory without one
+fn default_snapshot_file() -> PathBuf {
+    std::env::var_os("HOME")
+        .map(PathBuf::from)
+        .unwrap_or_default()
+        .join(".td150-snapshots.json")
+}
 
-    let dt = Utc.from_utc_datetime(&naive_dt);
+// Record a transmitted incremental session in the snapshot file
+fn save_snapshot(snapshots: Option<(SnapshotStore, &String, IncrementalSession)>, path: &Path) {
+    if let Some((mut store, watch_id, session)) = snapshots {
+        store.record(watch_id, &session);
 
-    if dt.timestamp() < 0 {
-        // For dates before 1970, just use UNIX_EPOCH as a fallback
-        SystemTime::UNIX_EPOCH
-    } else {
-        SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(dt.timestamp() as u64)
+        if let Err(e) = store.save(path) {
+            eprintln!("Error saving snapshots: {}", e);
+            process::exit(1);
+        }
     }
 }
 
-// Parse a time string to SystemTime
-fn parse_time_string(time_str: &str) -> SystemTime {
-    // Try to parse as RFC3339 first
-    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(time_str) {
-        return system_time_from_date(
-            dt.year(),
-            dt.month(),
-            dt.day(),
-            dt.hour(),
-            dt.minute(),
-        );
+// Build a ZAP file from assembled code and write it
+fn build_zap(matches: &clap::ArgMatches) {
+    let output = matches.get_one::<String>("output").unwrap();
+    let mut builder = ZapBuilder::new(matches.get_one::<String>("name").unwrap())
+        .version(matches.get_one::<String>("app-version").unwrap())
+        .description(matches.get_one::<String>("description").unwrap())
+        .help_file(matches.get_one::<String>("help-file").unwrap());
+    if let Some(&max_size) = matches.get_one::<usize>("max-size") {
+        builder = builder.max_size(max_size);
     }
-    
-    // Try parsing ISO8601 format
-    if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(time_str, "%Y-%m-%dT%H:%M:%S") {
-        let dt_utc = Utc.from_utc_datetime(&dt);
-        return system_time_from_date(
-            dt_utc.year(),
-            dt_utc.month(),
-            dt_utc.day(),
-            dt_utc.hour(),
-            dt_utc.minute(),
-        );
+
+    for (arg, protocol) in [("protocol-3", ProtocolVersion::Protocol3), ("protocol-4", ProtocolVersion::Protocol4)] {
+        if let Some(path) = matches.get_one::<String>(arg) {
+            let image = match std::fs::read(path) {
+                Ok(image) => image,
+                Err(e) => {
+                    eprintln!("Error reading {}: {}", path, e);
+                    process::exit(1);
+                }
+            };
+
+            builder = match builder.image(protocol, &image) {
+                Ok(builder) => builder,
+                Err(e) => {
+                    eprintln!("Error loading {}: {}", path, e);
+                    process::exit(1);
+                }
+            };
+        }
     }
-    
-    // Try parsing standard datetime format
-    if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(time_str, "%Y-%m-%d %H:%M:%S") {
-        let dt_utc = Utc.from_utc_datetime(&dt);
-        return system_time_from_date(
-            dt_utc.year(),
-            dt_utc.month(),
-            dt_utc.day(),
-            dt_utc.hour(),
-            dt_utc.minute(),
-        );
+
+    let zap = match builder.build() {
+        Ok(zap) => zap,
+        Err(e) => {
+            eprintln!("Error building ZAP file: {}", e);
+            process::exit(1);
+        }
+    };
+
+    if let Err(e) = zap.write(output) {
+        eprintln!("Error writing {}: {}", output, e);
+        process::exit(1);
     }
-    
-    // Try parsing just a date
-    if let Ok(date) = chrono::NaiveDate::parse_from_str(time_str, "%Y-%m-%d") {
-        let dt = date.and_hms_opt(0, 0, 0).unwrap();
-        let dt_utc = Utc.from_utc_datetime(&dt);
-        return system_time_from_date(
-            dt_utc.year(),
-            dt_utc.month(),
-            dt_utc.day(),
-            0,
-            0,
-        );
+
+    for variant in &zap.variants {
+        println!("{}: {} bytes of code", variant.watch, variant.code.len());
     }
-    
-    // If all parsing attempts fail, log and return current time
-    eprintln!("Failed to parse time: {}", time_str);
-    SystemTime::now()
 }
 
-fn parse_json_data(file_path: &str) -> Result<TimexData, String> {
-    let mut file = File::open(file_path).map_err(|e| format!("Failed to open file: {}", e))?;
-    let mut contents = String::new();
-    file.read_to_string(&mut contents)
-        .map_err(|e| format!("Failed to read file: {}", e))?;
+// Print the validation report of a watch data file
+fn validate_data(matches: &clap::ArgMatches) {
+    let path = matches.get_one::<String>("data_file").unwrap();
+    let data = match TimexData::load(Path::new(path)) {
+        Ok(data) => data,
+        Err(e) => {
+            eprintln!("Error loading watch data: {}", e);
+            process::exit(1);
+        }
+    };
+
+    let report = data.validate();
+    if report.problems.is_empty() {
+        println!("{}: no problems found", path);
+        return;
+    }
 
-    serde_json::from_str(&contents).map_err(|e| format!("Failed to parse JSON: {}", e))
+    print!("{}", report);
+    println!(
+        "{}: {} errors, {} warnings",
+        path,
+        report.errors().count(),
+        report.warnings().count()
+    );
+    if !report.is_valid() {
+        process::exit(1);
+    }
 }
 
 fn main() {
-    let matches = Command::new("td150")
+    let command = Command::new("td150")
         .about("Timex Datalink 150 protocol 3 data transfer tool")
-        .arg(Arg::new("json_file").help("JSON file with watch data"))
+        .arg(Arg::new("data_file").help("JSON, YAML or TOML file with watch data"))
+        .args_conflicts_with_subcommands(true)
+        .subcommand(
+            Command::new("validate")
+                .about("Report every problem in a watch data file without sending it")
+                .arg(
+                    Arg::new("data_file")
+                        .help("JSON, YAML or TOML file with watch data")
+                        .required(true),
+                ),
+        )
+        .subcommand(Command::new("schema").about("Print the JSON Schema of watch data files"))
+        .subcommand(
+            Command::new("build-zap")
+                .about("Build a ZAP wrist app file from assembled 6805 code")
+                .arg(
+                    Arg::new("output")
+                        .help("ZAP file to write")
+                        .value_name("ZAP_FILE")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("name")
+                        .long("name")
+                        .help("Friendly app name")
+                        .value_name("NAME")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("app-version")
+                        .long("app-version")
+                        .help("App version")
+                        .value_name("VERSION")
+                        .default_value(""),
+                )
+                .arg(
+                    Arg::new("description")
+                        .long("description")
+                        .help("App description")
+                        .value_name("TEXT")
+                        .default_value(""),
+                )
+                .arg(
+                    Arg::new("help-file")
+                        .long("help-file")
+                        .help("Help file name")
+                        .value_name("FILE")
+                        .default_value(""),
+                )
+                .arg(
+                    Arg::new("max-size")
+                        .long("max-size")
+                        .help("Largest code size in bytes (defaults to what one load section can carry)")
+                        .value_name("BYTES")
+                        .value_parser(clap::value_parser!(usize)),
+                )
+                .arg(
+                    Arg::new("protocol-3")
+                        .long("protocol-3")
+                        .help("Protocol 3 code as a binary, Intel HEX or S-record file")
+                        .value_name("IMAGE"),
+                )
+                .arg(
+                    Arg::new("protocol-4")
+                        .long("protocol-4")
+                        .help("Protocol 4 code as a binary, Intel HEX or S-record file")
+                        .value_name("IMAGE"),
+                )
+                .group(
+                    ArgGroup::new("code")
+                        .args(["protocol-3", "protocol-4"])
+                        .multiple(true)
+                        .required(true),
+                ),
+        )
         .arg(
             Arg::new("sound-theme")
                 .long("sound-theme")
@@ -189,6 +283,68 @@ fn main() {
                 .help("Skip creating time models")
                 .action(ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("ics")
+                .long("ics")
+                .help("Import appointments and yearly events from an iCalendar file (can be repeated)")
+                .value_name("FILE")
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("vcf")
+                .long("vcf")
+                .help("Import phone numbers from a vCard file (can be repeated)")
+                .value_name("FILE")
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("todo")
+                .long("todo")
+                .help("Import list entries from a todo.txt, CSV or iCalendar file (can be repeated)")
+                .value_name("FILE")
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("include-completed")
+                .long("include-completed")
+                .help("Also import completed to-do tasks")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("keep-todo-order")
+                .long("keep-todo-order")
+                .help("Keep imported to-do tasks in file order instead of sorting them by priority")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("from")
+                .long("from")
+                .help("Only import calendar appointments on or after this date (YYYY-MM-DD)")
+                .value_name("DATE")
+                .value_parser(clap::value_parser!(NaiveDate)),
+        )
+        .arg(
+            Arg::new("until")
+                .long("until")
+                .help("Only import calendar appointments on or before this date (YYYY-MM-DD)")
+                .value_name("DATE")
+                .value_parser(clap::value_parser!(NaiveDate)),
+        )
+        .arg(
+            Arg::new("timezone")
+                .long("timezone")
+                .help("IANA time zone appointments are shown in (defaults to the local time zone)")
+                .value_name("ZONE")
+                .value_parser(clap::value_parser!(Tz)),
+        )
+        .arg(
+            Arg::new("appointment-rounding")
+                .long("appointment-rounding")
+                .help("How appointment times are rounded into 15-minute slots")
+                .value_name("POLICY")
+                .value_parser(clap::value_parser!(RoundingPolicy))
+                .default_value("floor"),
+        )
         .arg(
             Arg::new("sync-length")
                 .long("sync-length")
@@ -203,10 +359,78 @@ fn main() {
                 .help("Send a start beep")
                 .action(ArgAction::SetTrue),
         )
-        .get_matches();
+        .arg(
+            Arg::new("annotate")
+                .long("annotate")
+                .help("Print an annotated listing of the generated packets")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("dry-run")
+                .long("dry-run")
+                .help("Generate packets without transmitting them")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("watch-id")
+                .long("watch-id")
+                .help("Only send sections that changed since the last sync to this watch")
+                .value_name("ID"),
+        )
+        .arg(
+            Arg::new("snapshot-file")
+                .long("snapshot-file")
+                .help("Specify the file snapshots of synced watches are kept in")
+                .value_name("PATH"),
+        )
+        .arg(
+            Arg::new("force-full")
+                .long("force-full")
+                .help("Send every section even if the watch already has it")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("save-data")
+                .long("save-data")
+                .help("Write the watch data to a .json, .yaml or .toml file and exit")
+                .value_name("PATH"),
+        );
+    #[cfg(feature = "dav")]
+    let command = command
+        .arg(
+            Arg::new("caldav")
+                .long("caldav")
+                .help("Sync appointments, anniversaries and to-dos from a CalDAV calendar URL (can be repeated)")
+                .value_name("URL")
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("carddav")
+                .long("carddav")
+                .help("Sync phone numbers from a CardDAV address book URL (can be repeated)")
+                .value_name("URL")
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("dav-user")
+                .long("dav-user")
+                .help("User name for the CalDAV/CardDAV server (password from TD150_DAV_PASSWORD)")
+                .value_name("USER"),
+        );
+    let matches = command.get_matches();
+
+    match matches.subcommand() {
+        Some(("build-zap", build_matches)) => return build_zap(build_matches),
+        Some(("validate", validate_matches)) => return validate_data(validate_matches),
+        Some(("schema", _)) => {
+            println!("{}", serde_json::to_string_pretty(&TimexData::json_schema()).unwrap());
+            return;
+        }
+        _ => {}
+    }
 
     // Get options from command line
-    let json_file = matches.get_one::<String>("json_file");
+    let data_file = matches.get_one::<String>("data_file");
     let sound_theme_file = matches.get_one::<String>("sound-theme");
     let wrist_app_file = matches.get_one::<String>("wrist-app");
     let adapter_type = matches.get_one::<String>("adapter").unwrap();
@@ -219,94 +443,266 @@ fn main() {
     let no_lists = matches.get_flag("no-lists");
     let no_alarms = matches.get_flag("no-alarms");
     let no_time = matches.get_flag("no-time");
+    let time_zone = matches.get_one::<Tz>("timezone").copied();
+    let appointment_rounding = *matches.get_one::<RoundingPolicy>("appointment-rounding").unwrap();
     let sync_length = *matches.get_one::<u8>("sync-length").unwrap();
     let start_beep = matches.get_flag("start-beep");
-
-    // Parse JSON data if a file was provided
-    let data = match json_file {
-        Some(path) => match parse_json_data(path) {
+    let annotate = matches.get_flag("annotate");
+    let dry_run = matches.get_flag("dry-run");
+    let watch_id = matches.get_one::<String>("watch-id");
+    let snapshot_file = matches.get_one::<String>("snapshot-file")
+        .map(PathBuf::from)
+        .unwrap_or_else(default_snapshot_file);
+    let force_full = matches.get_flag("force-full");
+
+    // Load watch data if a file was provided
+    let data = match data_file {
+        Some(path) => match TimexData::load(Path::new(path)) {
             Ok(data) => data,
             Err(e) => {
-                eprintln!("Error parsing JSON file: {}", e);
+                eprintln!("Error loading watch data: {}", e);
                 process::exit(1);
             }
         },
         None => TimexData::new(),
     };
 
-    // Create a new Protocol 3 instance
-    let mut protocol = Protocol3::new();
+    // Convert the watch data to another format instead of sending it
+    if let Some(path) = matches.get_one::<String>("save-data") {
+        if let Err(e) = data.save(Path::new(path)) {
+            eprintln!("Error saving watch data: {}", e);
+            process::exit(1);
+        }
+        println!("Saved watch data to {}", path);
+        return;
+    }
+
+    // Check the data before encoding anything
+    let report = data.validate();
+    eprint!("{}", report);
+    if !report.is_valid() {
+        eprintln!("Watch data has {} errors, nothing was sent", report.errors().count());
+        process::exit(1);
+    }
 
     // Create appointments
     let mut appointments = Vec::new();
     if !no_appointments && !data.appointments.is_empty() {
         for appointment in &data.appointments {
-            appointments.push(Appointment::new(
-                parse_time_string(&appointment.time),
-                appointment.message.clone(),
-            ));
+            appointments.push(models::Appointment {
+                time: appointment.wall_clock_time(time_zone),
+                message: appointment.message.clone(),
+                rounding: appointment_rounding,
+            });
         }
     }
 
-    // Create anniversaries
+    // Create anniversaries (the EEPROM model orders them by month and day)
     let mut anniversaries = Vec::new();
     if !no_anniversaries && !data.anniversaries.is_empty() {
-        let current_year = Utc::now().year();
         for anniversary in &data.anniversaries {
-            let time = parse_time_string(&anniversary.time);
-            
-            // Extract the month and day, then set to current year - 1
-            let dt = Utc.timestamp_opt(
-                time.duration_since(SystemTime::UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs() as i64,
-                0,
-            )
-            .unwrap();
-            
-            let modified_time = system_time_from_date(
-                current_year - 1,
-                dt.month(),
-                dt.day(),
-                0,
-                0,
-            );
+            match anniversary.date() {
+                Some(date) => anniversaries.push(models::Anniversary::from_date(
+                    date,
+                    anniversary.anniversary.clone(),
+                )),
+                None => eprintln!("Failed to parse anniversary date: {}", anniversary.time),
+            }
+        }
+    }
 
-            anniversaries.push(Anniversary::new(
-                modified_time,
-                anniversary.anniversary.clone(),
-            ));
+    // Import appointments and anniversaries from calendars
+    let mut ics_import = IcsImport::new()
+        .date_range(matches.get_one::<NaiveDate>("from").copied(), matches.get_one::<NaiveDate>("until").copied())
+        .rounding(appointment_rounding);
+    if let Some(zone) = time_zone {
+        ics_import = ics_import.time_zone(zone);
+    }
+    for path in matches.get_many::<String>("ics").unwrap_or_default() {
+        let import = match std::fs::read_to_string(path).map_err(|e| e.to_string())
+            .and_then(|ics| ics_import.import(&ics).map_err(|e| e.to_string()))
+        {
+            Ok(import) => import,
+            Err(e) => {
+                eprintln!("Error importing {}: {}", path, e);
+                process::exit(1);
+            }
+        };
+
+        for warning in &import.quantized {
+            eprintln!("Warning: {}: {}", path, warning);
+        }
+        for skipped in &import.skipped {
+            eprintln!("Skipped event in {}: {}", path, skipped);
+        }
+        if verbose {
+            println!(
+                "Imported {} appointments and {} anniversaries from {}",
+                import.appointments.len(),
+                import.anniversaries.len(),
+                path
+            );
         }
 
-        // Sort anniversaries by date
-        anniversaries.sort_by(|a, b| a.time.cmp(&b.time));
+        if !no_appointments {
+            appointments.extend(import.appointments);
+        }
+        if !no_anniversaries {
+            anniversaries.extend(import.anniversaries);
+        }
     }
 
     // Create phone numbers
     let mut phone_numbers = Vec::new();
     if !no_phone_numbers && !data.phone_numbers.is_empty() {
         for phone_number in &data.phone_numbers {
-            phone_numbers.push(PhoneNumber::new(
+            let phone_type = phone_number.r#type.map_or(PhoneType::Other, PhoneType::from);
+            phone_numbers.push(models::PhoneEntry::new(
                 phone_number.name.clone(),
                 phone_number.number.clone(),
-                phone_number.r#type.clone(),
+                phone_type,
             ));
         }
     }
 
+    // Import phone numbers from address books
+    for path in matches.get_many::<String>("vcf").unwrap_or_default() {
+        let import = match std::fs::read_to_string(path).map_err(|e| e.to_string())
+            .and_then(|vcf| vcard::import_vcards(&vcf).map_err(|e| e.to_string()))
+        {
+            Ok(import) => import,
+            Err(e) => {
+                eprintln!("Error importing {}: {}", path, e);
+                process::exit(1);
+            }
+        };
+
+        for skipped in &import.skipped {
+            eprintln!("Skipped contact in {}: {}", path, skipped);
+        }
+        if verbose {
+            println!("Imported {} phone numbers from {}", import.phone_numbers.len(), path);
+        }
+
+        if !no_phone_numbers {
+            phone_numbers.extend(import.phone_numbers);
+        }
+    }
+
     // Create lists
     let mut lists = Vec::new();
     if !no_lists && !data.lists.is_empty() {
         for list in &data.lists {
-            // Convert i32 to u8 for priority
-            let priority_u8 = list.priority.map(|p| p.clamp(1, 5) as u8);
-            lists.push(List::new(
+            // Validation has rejected priorities outside the watch's 1-5 range
+            let priority = list.priority.and_then(Priority::from_value);
+            lists.push(models::ListEntry::new(
                 list.list_entry.clone(),
-                priority_u8,
+                priority,
             ));
         }
     }
 
+    // Import list entries from to-do lists
+    let todo_import = TodoImport::new()
+        .include_completed(matches.get_flag("include-completed"))
+        .sort_by_priority(!matches.get_flag("keep-todo-order"));
+    for path in matches.get_many::<String>("todo").unwrap_or_default() {
+        let import = match TodoFormat::from_path(Path::new(path))
+            .ok_or_else(|| "unknown file type (expected .txt, .csv or .ics)".to_string())
+            .and_then(|format| {
+                std::fs::read_to_string(path).map_err(|e| e.to_string())
+                    .and_then(|text| todo_import.import(&text, format).map_err(|e| e.to_string()))
+            })
+        {
+            Ok(import) => import,
+            Err(e) => {
+                eprintln!("Error importing {}: {}", path, e);
+                process::exit(1);
+            }
+        };
+
+        for skipped in &import.skipped {
+            eprintln!("Skipped task in {}: {}", path, skipped);
+        }
+        if verbose {
+            println!(
+                "Imported {} list entries from {} ({} completed tasks left out)",
+                import.entries.len(),
+                path,
+                import.completed
+            );
+        }
+
+        if !no_lists {
+            lists.extend(import.entries);
+        }
+    }
+
+    // Sync calendars, to-dos and contacts from CalDAV/CardDAV servers
+    #[cfg(feature = "dav")]
+    {
+        use timex_datalink::import::dav::DavSync;
+
+        let calendars: Vec<&String> = matches.get_many::<String>("caldav").unwrap_or_default().collect();
+        let addressbooks: Vec<&String> = matches.get_many::<String>("carddav").unwrap_or_default().collect();
+
+        if !calendars.is_empty() || !addressbooks.is_empty() {
+            let mut sync = DavSync::new().events(ics_import.clone()).todos(todo_import.clone());
+            for url in &calendars {
+                sync = sync.calendar(url);
+            }
+            for url in &addressbooks {
+                sync = sync.addressbook(url);
+            }
+            if let Some(user) = matches.get_one::<String>("dav-user") {
+                sync = sync.credentials(user, &std::env::var("TD150_DAV_PASSWORD").unwrap_or_default());
+            }
+
+            let data = match sync.fetch() {
+                Ok(data) => data,
+                Err(e) => {
+                    eprintln!("Error syncing: {}", e);
+                    process::exit(1);
+                }
+            };
+
+            for warning in &data.calendar.quantized {
+                eprintln!("Warning: {}", warning);
+            }
+            for skipped in &data.calendar.skipped {
+                eprintln!("Skipped event: {}", skipped);
+            }
+            for skipped in &data.todos.skipped {
+                eprintln!("Skipped task: {}", skipped);
+            }
+            for skipped in &data.contacts.skipped {
+                eprintln!("Skipped contact: {}", skipped);
+            }
+            if verbose {
+                println!(
+                    "Synced {} appointments, {} anniversaries, {} list entries and {} phone numbers",
+                    data.calendar.appointments.len(),
+                    data.calendar.anniversaries.len(),
+                    data.todos.entries.len(),
+                    data.contacts.phone_numbers.len()
+                );
+            }
+
+            if !no_appointments {
+                appointments.extend(data.calendar.appointments);
+            }
+            if !no_anniversaries {
+                anniversaries.extend(data.calendar.anniversaries);
+            }
+            if !no_lists {
+                lists.extend(data.todos.entries);
+            }
+            if !no_phone_numbers {
+                phone_numbers.extend(data.contacts.phone_numbers);
+            }
+        }
+    }
+
     // Create time models
     let mut time_models = Vec::new();
     if !no_time {
@@ -339,7 +735,7 @@ fn main() {
         // Extract a meaningful name from the IANA timezone
         let tz_name = if iana_tz.contains('/') {
             // For IANA names like "Europe/Madrid", extract the city part
-            let city = iana_tz.split('/').last().unwrap_or("HOME");
+            let city = iana_tz.split('/').next_back().unwrap_or("HOME");
             
             // Clean up the city name (remove underscores, etc.)
             city.replace('_', " ").to_string()
@@ -373,20 +769,20 @@ fn main() {
             println!("  Zone 2 (UTC): {}", time2_utc.format("%Y-%m-%d %H:%M:%S UTC"));
         }
 
-        time_models.push(Time {
+        time_models.push(models::TimeZone {
             zone: 1,
             is_24h: true,
             date_format: DateFormat::DayDashMonthDashYear,
             time: time1,
-            name: CharString::new(&tz_name, true),
+            name: tz_name,
         });
 
-        time_models.push(Time {
+        time_models.push(models::TimeZone {
             zone: 2,
             is_24h: true,
             date_format: DateFormat::YearDotMonthDotDay,
             time: time2,
-            name: CharString::new("UTC", true),
+            name: "UTC".to_string(),
         });
     }
 
@@ -394,58 +790,58 @@ fn main() {
     let mut alarms = Vec::new();
     if !no_alarms && !data.alarms.is_empty() {
         for alarm in &data.alarms {
-            alarms.push(Alarm {
+            alarms.push(models::Alarm {
                 number: alarm.number,
                 audible: alarm.audible,
                 hour: alarm.hour,
                 minute: alarm.minute,
-                message: CharString::new(&alarm.message, true),
+                message: alarm.message.clone(),
             });
         }
     }
 
-    // Sound options
-    let sound_options = data.sound_options.as_ref().map(|opts| SoundOptions {
-        hourly_chime: opts.hourly_chime,
-        button_beep: opts.button_beep,
-    });
-
-    // Start building the protocol model sequence
-    protocol.add(Sync { length: sync_length as usize });
-    protocol.add(Start);
+    // Assemble the sync session in the order the watch expects
+    let mut config = WatchConfig::builder().sync_length(sync_length as usize);
 
     // Add start beep if requested
     if start_beep {
-        protocol.add(Beep);
+        config = config.after_start(Beep.packets());
     }
 
-    // Add time models
     for time_model in time_models {
-        protocol.add(time_model);
+        config = config.time_zone(time_model);
     }
 
-    // Add alarms
     for alarm in alarms {
-        protocol.add(alarm);
+        config = config.alarm(alarm);
     }
 
-    // Create and add EEPROM data if we have any
+    // Only send EEPROM data if we have any, as sending it clears the watch's EEPROM
     if !appointments.is_empty() || !anniversaries.is_empty() || !lists.is_empty() || !phone_numbers.is_empty() {
-        let mut eeprom = Eeprom::new();
-        eeprom.appointments = appointments;
-        eeprom.anniversaries = anniversaries;
-        eeprom.lists = lists;
-        eeprom.phone_numbers = phone_numbers;
-        eeprom.appointment_notification_minutes = data.appointment_notification_minutes;
-        
-        protocol.add(eeprom);
+        for appointment in appointments {
+            config = config.appointment(appointment);
+        }
+        for anniversary in anniversaries {
+            config = config.anniversary(anniversary);
+        }
+        for list in lists {
+            config = config.list_entry(list);
+        }
+        for phone_number in phone_numbers {
+            config = config.phone_entry(phone_number);
+        }
+        if let Some(minutes) = data.appointment_notification_minutes {
+            match NotificationMinutes::from_minutes(minutes) {
+                Some(notification) => config = config.appointment_notification(notification),
+                None => eprintln!("Ignoring appointment notification of {} minutes (expected 0-30 in steps of 5)", minutes),
+            }
+        }
     }
 
     // Add sound theme if provided
     if let Some(sound_theme_path) = sound_theme_file {
-        let path = PathBuf::from(sound_theme_path);
-        match SoundTheme::from_spc_file(&path) {
-            Ok(theme) => protocol.add(theme),
+        match Spc::read(sound_theme_path) {
+            Ok(spc) => config = config.sound_theme(spc.sound_data().to_vec()),
             Err(e) => {
                 eprintln!("Error loading sound theme: {}", e);
                 process::exit(1);
@@ -453,11 +849,18 @@ fn main() {
         }
     }
 
+    // Add sound options if provided
+    if let Some(opts) = &data.sound_options {
+        config = config.sound_options(models::SoundOptions {
+            hourly_chime: opts.hourly_chime,
+            button_beep: opts.button_beep,
+        });
+    }
+
     // Add wrist app if provided
     if let Some(wrist_app_path) = wrist_app_file {
-        let path = PathBuf::from(wrist_app_path);
-        match WristApp::from_zap_file(path) {
-            Ok(app) => protocol.add(app),
+        match WristApp::from_zap_file(wrist_app_path) {
+            Ok(app) => config = config.wrist_app(app.wrist_app_data),
             Err(e) => {
                 eprintln!("Error loading wrist app: {}", e);
                 process::exit(1);
@@ -465,21 +868,69 @@ fn main() {
         }
     }
 
-    // Add sound options if provided
-    if let Some(opts) = sound_options {
-        protocol.add(opts);
+    let config = match config.build() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Invalid watch data: {}", e);
+            process::exit(1);
+        }
+    };
+
+    // Warn about appointments the watch will show at a different time
+    for warning in config.appointment_quantization_report() {
+        eprintln!("Warning: {}", warning);
     }
 
-    // Add end marker
-    protocol.add(End);
+    // Generate all packets, or only the changed sections for a known watch
+    let mut snapshots = None;
+    let packets = match watch_id {
+        Some(watch_id) => {
+            let store = match SnapshotStore::load(&snapshot_file) {
+                Ok(store) => store,
+                Err(e) => {
+                    eprintln!("Error loading snapshots: {}", e);
+                    process::exit(1);
+                }
+            };
+            let session = store.plan(watch_id, &config, ProtocolVersion::Protocol3, force_full);
+
+            if verbose && !session.skipped.is_empty() {
+                let skipped: Vec<&str> = session.skipped.iter().map(|section| section.name()).collect();
+                println!("Skipping unchanged sections: {}", skipped.join(", "));
+            }
 
-    // Generate all packets
-    let packets = protocol.packets();
+            let packets = session.packets.clone();
+            snapshots = Some((store, watch_id, session));
+            packets
+        }
+        None => config.packets(ProtocolVersion::Protocol3),
+    };
 
     if verbose {
         println!("Generated {} packets for Protocol 3", packets.len());
     }
 
+    if annotate {
+        println!("{}", format_listing(&annotate_packets(&packets)));
+    }
+
+    // Show how long the transfer will take with the chosen adapter
+    let timing = match adapter_type.as_str() {
+        "led" => AdapterTiming::led(
+            OnePlus6LedAdapter::BYTE_SLEEP_DEFAULT,
+            OnePlus6LedAdapter::PACKET_SLEEP_DEFAULT,
+        ),
+        _ => AdapterTiming::serial(
+            NotebookAdapter::BYTE_SLEEP_DEFAULT,
+            NotebookAdapter::PACKET_SLEEP_DEFAULT,
+        ),
+    };
+    print!("{}", estimate_transfer(&packets, &timing));
+
+    if dry_run {
+        return;
+    }
+
     // Create the appropriate adapter and send the packets
     match adapter_type.as_str() {
         "serial" => {
@@ -499,6 +950,7 @@ fn main() {
                     if verbose {
                         println!("Successfully transmitted data to the watch!");
                     }
+                    save_snapshot(snapshots.take(), &snapshot_file);
                 },
                 Err(e) => {
                     eprintln!("Error transmitting data: {}", e);
@@ -523,6 +975,7 @@ fn main() {
                     if verbose {
                         println!("Successfully transmitted data to the watch!");
                     }
+                    save_snapshot(snapshots.take(), &snapshot_file);
                 },
                 Err(e) => {
                     eprintln!("Error transmitting data: {}", e);
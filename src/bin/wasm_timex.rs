@@ -4,12 +4,11 @@ use timex_datalink::{
     Protocol3, PacketGenerator,
     protocol_3::{
         Sync, Start, End, Time, Alarm, SoundOptions,
-        eeprom::{Eeprom, Appointment, Anniversary, PhoneNumber, List}
+        eeprom::{Eeprom, Appointment, Anniversary, PhoneNumber, List, NotificationMinutes}
     },
     char_encoders::CharString,
     protocol_3::time::DateFormat,
 };
-use chrono::{Utc, TimeZone};
 
 // This module is only meant to be compiled as a WebAssembly library, not as a binary.
 #[allow(dead_code)]
@@ -18,21 +17,12 @@ fn main() {
     // It only exists to satisfy the Rust compiler's requirement for binaries.
 }
 
-// Helper function to create a SystemTime from date components
-fn system_time_from_date(year: i32, month: u32, day: u32, hour: u32, min: u32) -> SystemTime {
-    let naive_dt = chrono::NaiveDate::from_ymd_opt(year, month, day)
+// Helper function to create a wall-clock time from date components
+fn datetime(year: i32, month: u32, day: u32, hour: u32, min: u32) -> chrono::NaiveDateTime {
+    chrono::NaiveDate::from_ymd_opt(year, month, day)
         .unwrap()
         .and_hms_opt(hour, min, 0)
-        .unwrap();
-    
-    let dt = Utc.from_utc_datetime(&naive_dt);
-    
-    if dt.timestamp() < 0 {
-        // For dates before 1970, just use UNIX_EPOCH as a fallback
-        SystemTime::UNIX_EPOCH
-    } else {
-        SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(dt.timestamp() as u64)
-    }
+        .unwrap()
 }
 
 #[wasm_bindgen]
@@ -43,15 +33,15 @@ pub fn generate_protocol3_packets() -> JsValue {
     // Define the appointments
     let appointments = vec![
         Appointment::new(
-            system_time_from_date(2023, 10, 31, 19, 0),
+            datetime(2023, 10, 31, 19, 0),
             "Scare the neighbors".to_string()
         ),
         Appointment::new(
-            system_time_from_date(2023, 11, 24, 17, 0),
+            datetime(2023, 11, 24, 17, 0),
             "Feed the neighbors".to_string()
         ),
         Appointment::new(
-            system_time_from_date(2023, 12, 25, 14, 0),
+            datetime(2023, 12, 25, 14, 0),
             "Spoil the neighbors".to_string()
         ),
     ];
@@ -59,13 +49,15 @@ pub fn generate_protocol3_packets() -> JsValue {
     // Define the anniversaries
     let anniversaries = vec![
         Anniversary::new(
-            system_time_from_date(1985, 7, 3, 0, 0),
+            7,
+            3,
             "Release of Back to the Future".to_string()
-        ),
+        ).expect("valid anniversary date"),
         Anniversary::new(
-            system_time_from_date(1968, 4, 6, 0, 0),
+            4,
+            6,
             "Release of 2001".to_string()
-        ),
+        ).expect("valid anniversary date"),
     ];
     
     // Define the phone numbers
@@ -142,7 +134,7 @@ pub fn generate_protocol3_packets() -> JsValue {
     eeprom.anniversaries = anniversaries;
     eeprom.phone_numbers = phone_numbers;
     eeprom.lists = lists;
-    eeprom.appointment_notification_minutes = Some(15);
+    eeprom.appointment_notification_minutes = Some(NotificationMinutes::FifteenMinutes);
     
     // Add the EEPROM to the protocol
     protocol.add(eeprom);
@@ -160,7 +152,5 @@ pub fn generate_protocol3_packets() -> JsValue {
     let packets = protocol.packets();
     
     // Convert the packets to a JavaScript array
-    let js_packets = serde_wasm_bindgen::to_value(&packets).unwrap();
-    
-    js_packets
+    serde_wasm_bindgen::to_value(&packets).unwrap()
 }
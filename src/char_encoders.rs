@@ -21,6 +21,18 @@ pub const INVALID_CHAR: char = ' ';
 /// EEPROM string terminator value
 pub const EEPROM_TERMINATOR: u8 = 0x3f;
 
+/// Maximum length of EEPROM text (appointments, lists, names, anniversaries)
+pub const EEPROM_TEXT_LENGTH: usize = 31;
+
+/// Number of characters of the phone field ("<number> <type>")
+pub const PHONE_FIELD_LENGTH: usize = 12;
+
+/// Maximum length of a phone number, leaving room for " <type>"
+pub const PHONE_NUMBER_LENGTH: usize = PHONE_FIELD_LENGTH - 2;
+
+/// Length of an alarm message
+pub const ALARM_MESSAGE_LENGTH: usize = 8;
+
 /// A string encoded with the watch character set with fixed maximum length
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct CharString<const MAX_LEN: usize> {
@@ -104,7 +116,7 @@ impl EepromString {
     /// Create a new EEPROM string (max 31 characters)
     pub fn new(input: &str) -> Self {
         // First encode the string using the EEPROM character set
-        let char_string = CharString::<31>::new_with_charset(input, EEPROM_CHARS, false);
+        let char_string = CharString::<EEPROM_TEXT_LENGTH>::new_with_charset(input, EEPROM_CHARS, false);
         let mut char_indices = char_string.as_bytes().to_vec();
         
         // Add the terminator byte
@@ -128,10 +140,47 @@ impl EepromString {
         EepromString { bytes }
     }
     
+    /// Wrap bytes read back from the watch (e.g. from a capture)
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        EepromString { bytes: bytes.to_vec() }
+    }
+    
     /// Get the encoded bytes
     pub fn as_bytes(&self) -> &[u8] {
         &self.bytes
     }
+    
+    /// Decode the packed characters back into text (up to the terminator)
+    pub fn decode(&self) -> String {
+        let chars: Vec<char> = EEPROM_CHARS.chars().collect();
+        let mut text = String::new();
+        let mut packed: u32 = 0;
+        let mut bits = 0;
+        
+        for &byte in &self.bytes {
+            packed |= (byte as u32) << bits;
+            bits += 8;
+            
+            while bits >= 6 {
+                let index = (packed & 0x3f) as u8;
+                packed >>= 6;
+                bits -= 6;
+                
+                if index == EEPROM_TERMINATOR {
+                    return text;
+                }
+                text.push(chars.get(index as usize).copied().unwrap_or(INVALID_CHAR));
+            }
+        }
+        
+        text
+    }
+}
+
+impl std::fmt::Display for EepromString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.decode())
+    }
 }
 
 /// Phone number string with compact encoding for Timex watches
@@ -156,7 +205,7 @@ impl PhoneString {
         // Limit to max 12 characters as in Ruby
         let chars: Vec<u8> = input.to_lowercase()
             .chars()
-            .take(12)
+            .take(PHONE_FIELD_LENGTH)
             .map(|c| {
                 let idx = PHONE_CHARS.find(c);
                 idx.map(|i| i as u8).unwrap_or(invalid_pos)
@@ -181,10 +230,33 @@ impl PhoneString {
         PhoneString { bytes }
     }
     
+    /// Wrap bytes read back from the watch (e.g. from a capture)
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        PhoneString { bytes: bytes.to_vec() }
+    }
+    
     /// Get the encoded bytes
     pub fn as_bytes(&self) -> &[u8] {
         &self.bytes
     }
+    
+    /// Decode the packed characters back into text
+    ///
+    /// An odd number of characters leaves the last nibble zero, which reads
+    /// back as a trailing "0". Numbers always end with their type code, so a
+    /// trailing "0" is dropped.
+    pub fn decode(&self) -> String {
+        let chars: Vec<char> = PHONE_CHARS.chars().collect();
+        let mut text: String = self.bytes.iter()
+            .flat_map(|&byte| [byte & 0x0f, byte >> 4])
+            .map(|index| chars[index as usize])
+            .collect();
+        
+        if text.ends_with('0') {
+            text.pop();
+        }
+        text
+    }
 }
 
 // Implement From trait for convenience
@@ -307,6 +379,16 @@ mod tests {
         assert_eq!(s.as_bytes(), &[0x1d, 0x46, 0x76, 0x91, 0x43, 0x36, 0x4e, 0x85, 0x6d, 0x8e, 0x72, 0x91, 0xa0, 0xd4, 0x45, 0xa4, 0x85, 0x6d, 0x0e, 0xd9, 0x45, 0xca, 0x45, 0xfe]);
     }
     
+    #[test]
+    fn test_eeprom_decode() {
+        assert_eq!(EepromString::new("Scare the neighbors").to_string(), "scare the neighbors");
+        // "]" is not in the EEPROM character set, so it was replaced with a space
+        assert_eq!(EepromString::new(";@_|<>[]").decode(), ";@_|<>[ ");
+        assert_eq!(EepromString::new("~with~invalid~characters").decode(), " with invalid characters");
+        assert_eq!(EepromString::new("To the Delorean with More Than 31 Characters").decode(), "to the delorean with more than ");
+        assert_eq!(EepromString::new("").decode(), "");
+    }
+    
     #[test]
     fn test_phone_string() {
         // Test with exact values from the Ruby spec
@@ -317,6 +399,13 @@ mod tests {
         assert_eq!(s.as_bytes()[0..5], [0x21, 0x43, 0x65, 0x87, 0x09]);
     }
     
+    #[test]
+    fn test_phone_decode() {
+        assert_eq!(PhoneString::new("1112223333 h").decode(), "1112223333 h");
+        assert_eq!(PhoneString::new("5551955  ").decode(), "5551955  ");
+        assert_eq!(PhoneString::from_bytes(&[0x21, 0x43]).decode(), "1234");
+    }
+    
     #[test]
     fn test_eeprom_from_trait() {
         let s: EepromString = "Hello".into();
@@ -1,83 +1,713 @@
-use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
 
-/// Data structures for Timex Datalink 150 watch communication
-/// These match the JSON format used by the Ruby implementation
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, NaiveDateTime};
+use chrono_tz::Tz;
+use schemars::JsonSchema;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AppointmentData {
+use crate::helpers::appointment_time;
+use crate::models::PhoneType;
+
+pub mod validation;
+
+/// Version of the watch data schema written by this crate
+///
+/// Files without a version are version 1, the JSON format used by the Ruby
+/// implementation. Version 2 only accepts the known phone types; version 1
+/// files are still read, with any other phone type read as other.
+pub const SCHEMA_VERSION: u32 = 2;
+
+// Data structures for Timex Datalink 150 watch communication
+// These extend the JSON format used by the Ruby implementation, and can also
+// be written as YAML or TOML for hand-edited configs kept in version control
+
+/// Date and time of an appointment
+///
+/// Written as RFC 3339 with an offset (`2025-03-14T09:30:00+01:00`) or as a
+/// wall-clock time in the watch's time zone (`YYYY-MM-DDTHH:MM[:SS]`,
+/// `YYYY-MM-DD HH:MM[:SS]` or `YYYY-MM-DD` for midnight). Wall-clock times are
+/// always written back as `YYYY-MM-DDTHH:MM:SS`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppointmentTime {
+    /// A time with a UTC offset, converted into the watch's time zone
+    Offset(DateTime<FixedOffset>),
+    /// A wall-clock time in the watch's time zone
+    WallClock(NaiveDateTime),
+}
+
+/// Error raised for appointment times in none of the accepted formats
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidAppointmentTime {
     pub time: String,
+}
+
+impl fmt::Display for InvalidAppointmentTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"{}\" is not a date and time", self.time)
+    }
+}
+
+impl std::error::Error for InvalidAppointmentTime {}
+
+impl AppointmentTime {
+    /// Wall-clock time in the watch's time zone
+    ///
+    /// Times with an offset are converted into `zone`, or the local time zone
+    /// without one. Wall-clock times are returned as-is.
+    pub fn wall_clock(&self, zone: Option<Tz>) -> NaiveDateTime {
+        match (self, zone) {
+            (AppointmentTime::Offset(dt), Some(zone)) => appointment_time::wall_clock(&dt.with_timezone(&zone)),
+            (AppointmentTime::Offset(dt), None) => appointment_time::wall_clock(&dt.with_timezone(&Local)),
+            (AppointmentTime::WallClock(dt), _) => *dt,
+        }
+    }
+}
+
+impl FromStr for AppointmentTime {
+    type Err = InvalidAppointmentTime;
+
+    fn from_str(time: &str) -> Result<Self, Self::Err> {
+        if let Ok(dt) = DateTime::parse_from_rfc3339(time) {
+            return Ok(AppointmentTime::Offset(dt));
+        }
+
+        for format in ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"] {
+            if let Ok(dt) = NaiveDateTime::parse_from_str(time, format) {
+                return Ok(AppointmentTime::WallClock(dt));
+            }
+        }
+
+        NaiveDate::parse_from_str(time, "%Y-%m-%d")
+            .ok()
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+            .map(AppointmentTime::WallClock)
+            .ok_or_else(|| InvalidAppointmentTime { time: time.to_string() })
+    }
+}
+
+impl fmt::Display for AppointmentTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppointmentTime::Offset(dt) => write!(f, "{}", dt.to_rfc3339()),
+            AppointmentTime::WallClock(dt) => write!(f, "{}", dt.format("%Y-%m-%dT%H:%M:%S")),
+        }
+    }
+}
+
+impl Serialize for AppointmentTime {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for AppointmentTime {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let time = String::deserialize(deserializer)?;
+        time.parse().map_err(de::Error::custom)
+    }
+}
+
+/// An appointment
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AppointmentData {
+    /// Date and time, either RFC 3339 with an offset or a wall-clock time (YYYY-MM-DDTHH:MM[:SS], YYYY-MM-DD HH:MM[:SS] or YYYY-MM-DD)
+    #[schemars(with = "String", example = &"2025-03-14T09:30:00")]
+    pub time: AppointmentTime,
+    /// Appointment message (max 31 characters)
     pub message: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// An anniversary, recurring every year on the month and day of its date
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct AnniversaryData {
+    /// Date (YYYY-MM-DD, a time part is ignored)
+    #[schemars(example = &"1985-10-26")]
     pub time: String,
+    /// Anniversary text (max 31 characters)
     pub anniversary: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Phone number type shown after the number
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PhoneTypeData {
+    /// Cell phone
+    #[serde(alias = "c", alias = "C", alias = "Cell", alias = "CELL")]
+    Cell,
+    /// Home phone
+    #[serde(alias = "h", alias = "H", alias = "Home", alias = "HOME")]
+    Home,
+    /// Work phone
+    #[serde(alias = "w", alias = "W", alias = "Work", alias = "WORK")]
+    Work,
+    /// Any other phone, shown as a blank
+    #[serde(alias = " ", alias = "", alias = "Other", alias = "OTHER")]
+    Other,
+}
+
+impl From<PhoneTypeData> for PhoneType {
+    fn from(phone_type: PhoneTypeData) -> Self {
+        match phone_type {
+            PhoneTypeData::Cell => PhoneType::Cell,
+            PhoneTypeData::Home => PhoneType::Home,
+            PhoneTypeData::Work => PhoneType::Work,
+            PhoneTypeData::Other => PhoneType::Other,
+        }
+    }
+}
+
+/// A phone book entry
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PhoneNumberData {
+    /// Contact name (max 31 characters)
     pub name: String,
+    /// Phone number (max 10 characters: digits, spaces and the letters c, f, h, p and w)
     pub number: String,
-    #[serde(default)]
-    pub r#type: Option<String>,
+    /// Phone number type (other if not set)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub r#type: Option<PhoneTypeData>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A list (to-do) entry
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ListData {
+    /// Entry text (max 31 characters)
     pub list_entry: String,
-    #[serde(default)]
-    pub priority: Option<i32>,
+    /// Priority (1-5, no priority if not set)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(range(min = 1, max = 5))]
+    pub priority: Option<u8>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// An alarm
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct AlarmData {
+    /// Alarm number (1-5, each number may be used once)
+    #[schemars(range(min = 1, max = 5))]
     pub number: u8,
+    /// Whether the alarm makes a sound
     pub audible: bool,
+    /// Hour (0-23)
+    #[schemars(range(max = 23))]
     pub hour: u8,
+    /// Minute (0-59)
+    #[schemars(range(max = 59))]
     pub minute: u8,
+    /// Alarm message (max 8 characters)
     pub message: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Sound options
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SoundOptionsData {
+    /// Beep every hour
     #[serde(default)]
     pub hourly_chime: bool,
+    /// Beep on every button press
     #[serde(default)]
     pub button_beep: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+/// Timex Datalink 150 watch data
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TimexData {
-    #[serde(default)]
+    /// Schema version of the file
+    #[serde(default = "legacy_schema_version")]
+    #[schemars(range(min = 1, max = 2))]
+    pub version: u32,
+    /// Minutes before an appointment the watch notifies (0-30 in steps of 5)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(range(max = 30))]
+    pub appointment_notification_minutes: Option<u8>,
+    /// Appointments
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub appointments: Vec<AppointmentData>,
-    #[serde(default)]
+    /// Anniversaries
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub anniversaries: Vec<AnniversaryData>,
-    #[serde(default)]
+    /// Phone book entries
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub phone_numbers: Vec<PhoneNumberData>,
-    #[serde(default)]
+    /// List (to-do) entries
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub lists: Vec<ListData>,
-    #[serde(default)]
+    /// Alarms (at most 5)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(length(max = 5))]
     pub alarms: Vec<AlarmData>,
-    #[serde(default)]
+    /// Sound options (left unchanged if not set)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub sound_options: Option<SoundOptionsData>,
+}
+
+// Files written before the schema was versioned
+fn legacy_schema_version() -> u32 {
+    1
+}
+
+// Read first to pick the schema the rest of the file is parsed with
+#[derive(Deserialize)]
+struct SchemaVersion {
+    #[serde(default = "legacy_schema_version")]
+    version: u32,
+}
+
+// Version 1 phone type, any string the Ruby implementation accepted
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum LegacyPhoneType {
+    Known(PhoneTypeData),
+    Unknown(de::IgnoredAny),
+}
+
+// Version 1 phone book entry
+#[derive(Deserialize)]
+struct LegacyPhoneNumberData {
+    name: String,
+    number: String,
     #[serde(default)]
-    pub appointment_notification_minutes: Option<u8>,
+    r#type: Option<LegacyPhoneType>,
+}
+
+// Version 1 watch data, only phone types differ from the current version
+#[derive(Deserialize)]
+struct LegacyTimexData {
+    #[serde(default)]
+    appointment_notification_minutes: Option<u8>,
+    #[serde(default)]
+    appointments: Vec<AppointmentData>,
+    #[serde(default)]
+    anniversaries: Vec<AnniversaryData>,
+    #[serde(default)]
+    phone_numbers: Vec<LegacyPhoneNumberData>,
+    #[serde(default)]
+    lists: Vec<ListData>,
+    #[serde(default)]
+    alarms: Vec<AlarmData>,
+    #[serde(default)]
+    sound_options: Option<SoundOptionsData>,
+}
+
+impl From<LegacyTimexData> for TimexData {
+    fn from(legacy: LegacyTimexData) -> Self {
+        let phone_numbers = legacy
+            .phone_numbers
+            .into_iter()
+            .map(|phone_number| PhoneNumberData {
+                name: phone_number.name,
+                number: phone_number.number,
+                r#type: phone_number.r#type.map(|phone_type| match phone_type {
+                    LegacyPhoneType::Known(phone_type) => phone_type,
+                    LegacyPhoneType::Unknown(_) => PhoneTypeData::Other,
+                }),
+            })
+            .collect();
+
+        TimexData {
+            version: SCHEMA_VERSION,
+            appointment_notification_minutes: legacy.appointment_notification_minutes,
+            appointments: legacy.appointments,
+            anniversaries: legacy.anniversaries,
+            phone_numbers,
+            lists: legacy.lists,
+            alarms: legacy.alarms,
+            sound_options: legacy.sound_options,
+        }
+    }
+}
+
+impl Default for TimexData {
+    fn default() -> Self {
+        TimexData {
+            version: SCHEMA_VERSION,
+            appointment_notification_minutes: None,
+            appointments: Vec::new(),
+            anniversaries: Vec::new(),
+            phone_numbers: Vec::new(),
+            lists: Vec::new(),
+            alarms: Vec::new(),
+            sound_options: None,
+        }
+    }
+}
+
+impl AppointmentData {
+    /// Wall-clock time of the appointment in the watch's time zone
+    ///
+    /// See `AppointmentTime::wall_clock`.
+    pub fn wall_clock_time(&self, zone: Option<Tz>) -> NaiveDateTime {
+        self.time.wall_clock(zone)
+    }
+}
+
+impl AnniversaryData {
+    /// Date of the anniversary (dates before 1970 are kept as-is)
+    ///
+    /// # Returns
+    ///
+    /// The date, or None if it cannot be parsed
+    pub fn date(&self) -> Option<NaiveDate> {
+        if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(&self.time) {
+            return Some(dt.date_naive());
+        }
+
+        for format in ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M:%S"] {
+            if let Ok(dt) = NaiveDateTime::parse_from_str(&self.time, format) {
+                return Some(dt.date());
+            }
+        }
+
+        NaiveDate::parse_from_str(&self.time, "%Y-%m-%d").ok()
+    }
 }
 
+/// File formats watch data can be stored in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataFormat {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl DataFormat {
+    /// Pick the format from a file extension (`.json`, `.yaml`/`.yml` or `.toml`)
+    ///
+    /// # Returns
+    ///
+    /// The format, or None if the extension is missing or unknown
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+
+        match extension.as_str() {
+            "json" => Some(DataFormat::Json),
+            "yaml" | "yml" => Some(DataFormat::Yaml),
+            "toml" => Some(DataFormat::Toml),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for DataFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataFormat::Json => write!(f, "JSON"),
+            DataFormat::Yaml => write!(f, "YAML"),
+            DataFormat::Toml => write!(f, "TOML"),
+        }
+    }
+}
+
+/// Errors raised while loading or saving watch data
+#[derive(Debug)]
+pub enum DataError {
+    /// The file could not be read or written
+    Io { path: String, error: std::io::Error },
+
+    /// The file extension does not name a known format
+    UnknownFormat { path: String },
+
+    /// The data could not be parsed or serialized
+    Format { format: DataFormat, message: String },
+}
+
+impl fmt::Display for DataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataError::Io { path, error } => write!(f, "{}: {}", path, error),
+            DataError::UnknownFormat { path } => {
+                write!(f, "{}: unknown file type (expected .json, .yaml, .yml or .toml)", path)
+            }
+            DataError::Format { format, message } => write!(f, "Invalid {}: {}", format, message),
+        }
+    }
+}
+
+impl std::error::Error for DataError {}
+
 impl TimexData {
     /// Create a new empty TimexData instance
     pub fn new() -> Self {
         Default::default()
     }
 
+    /// JSON Schema of the watch data, for editors and external validators
+    pub fn json_schema() -> serde_json::Value {
+        let mut schema = schemars::schema_for!(TimexData);
+        schema.insert("title".to_string(), format!("Timex Datalink 150 watch data, version {}", SCHEMA_VERSION).into());
+        schema.to_value()
+    }
+
+    /// Check the data for problems before it is encoded
+    ///
+    /// See `validation::validate`.
+    pub fn validate(&self) -> validation::ValidationReport {
+        validation::validate(self)
+    }
+
     /// Parse a JSON string into TimexData
+    ///
+    /// Version 1 files are upgraded to the current version.
     pub fn from_json(json_str: &str) -> Result<Self, serde_json::Error> {
-        serde_json::from_str(json_str)
+        match serde_json::from_str::<SchemaVersion>(json_str)?.version {
+            1 => serde_json::from_str::<LegacyTimexData>(json_str).map(Self::from),
+            _ => serde_json::from_str(json_str),
+        }
     }
 
     /// Serialize TimexData to a JSON string
     pub fn to_json(&self) -> Result<String, serde_json::Error> {
         serde_json::to_string_pretty(self)
     }
-}
\ No newline at end of file
+
+    /// Parse a YAML string into TimexData
+    ///
+    /// Version 1 files are upgraded to the current version.
+    pub fn from_yaml(yaml_str: &str) -> Result<Self, serde_yaml::Error> {
+        match serde_yaml::from_str::<SchemaVersion>(yaml_str)?.version {
+            1 => serde_yaml::from_str::<LegacyTimexData>(yaml_str).map(Self::from),
+            _ => serde_yaml::from_str(yaml_str),
+        }
+    }
+
+    /// Serialize TimexData to a YAML string
+    pub fn to_yaml(&self) -> Result<String, serde_yaml::Error> {
+        serde_yaml::to_string(self)
+    }
+
+    /// Parse a TOML string into TimexData
+    ///
+    /// Version 1 files are upgraded to the current version.
+    pub fn from_toml(toml_str: &str) -> Result<Self, toml::de::Error> {
+        match toml::from_str::<SchemaVersion>(toml_str)?.version {
+            1 => toml::from_str::<LegacyTimexData>(toml_str).map(Self::from),
+            _ => toml::from_str(toml_str),
+        }
+    }
+
+    /// Serialize TimexData to a TOML string
+    pub fn to_toml(&self) -> Result<String, toml::ser::Error> {
+        toml::to_string_pretty(self)
+    }
+
+    /// Parse a string in the given format
+    pub fn parse(contents: &str, format: DataFormat) -> Result<Self, DataError> {
+        let result = match format {
+            DataFormat::Json => Self::from_json(contents).map_err(|e| e.to_string()),
+            DataFormat::Yaml => Self::from_yaml(contents).map_err(|e| e.to_string()),
+            DataFormat::Toml => Self::from_toml(contents).map_err(|e| e.to_string()),
+        };
+
+        result.map_err(|message| DataError::Format { format, message })
+    }
+
+    /// Serialize to a string in the given format
+    pub fn serialize(&self, format: DataFormat) -> Result<String, DataError> {
+        let result = match format {
+            DataFormat::Json => self.to_json().map(|json| json + "\n").map_err(|e| e.to_string()),
+            DataFormat::Yaml => self.to_yaml().map_err(|e| e.to_string()),
+            DataFormat::Toml => self.to_toml().map_err(|e| e.to_string()),
+        };
+
+        result.map_err(|message| DataError::Format { format, message })
+    }
+
+    /// Load a data file, picking the format from its extension
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - A `.yaml`, `.yml` or `.toml` file, any other file is read as JSON
+    pub fn load(path: &Path) -> Result<Self, DataError> {
+        let format = DataFormat::from_path(path).unwrap_or(DataFormat::Json);
+        let contents = fs::read_to_string(path)
+            .map_err(|error| DataError::Io { path: path.display().to_string(), error })?;
+
+        Self::parse(&contents, format)
+    }
+
+    /// Save to a data file, picking the format from its extension
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - A `.json`, `.yaml`, `.yml` or `.toml` file
+    pub fn save(&self, path: &Path) -> Result<(), DataError> {
+        let format = DataFormat::from_path(path)
+            .ok_or_else(|| DataError::UnknownFormat { path: path.display().to_string() })?;
+        let contents = self.serialize(format)?;
+
+        fs::write(path, contents)
+            .map_err(|error| DataError::Io { path: path.display().to_string(), error })
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> TimexData {
+        TimexData {
+            appointment_notification_minutes: Some(15),
+            appointments: vec![AppointmentData {
+                time: "2025-03-14T09:30:00".parse().unwrap(),
+                message: "Dentist".to_string(),
+            }],
+            phone_numbers: vec![PhoneNumberData {
+                name: "Doc Brown".to_string(),
+                number: "5551234".to_string(),
+                r#type: Some(PhoneTypeData::Home),
+            }],
+            lists: vec![ListData { list_entry: "Milk".to_string(), priority: None }],
+            alarms: vec![AlarmData {
+                number: 1,
+                audible: true,
+                hour: 7,
+                minute: 0,
+                message: "Wake up".to_string(),
+            }],
+            sound_options: Some(SoundOptionsData { hourly_chime: true, button_beep: false }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_data_format_from_path() {
+        assert_eq!(DataFormat::from_path(Path::new("watch.json")), Some(DataFormat::Json));
+        assert_eq!(DataFormat::from_path(Path::new("watch.YML")), Some(DataFormat::Yaml));
+        assert_eq!(DataFormat::from_path(Path::new("configs/watch.toml")), Some(DataFormat::Toml));
+        assert_eq!(DataFormat::from_path(Path::new("watch")), None);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let data = sample();
+
+        for format in [DataFormat::Json, DataFormat::Yaml, DataFormat::Toml] {
+            let text = data.serialize(format).unwrap();
+            let parsed = TimexData::parse(&text, format).unwrap();
+
+            assert_eq!(parsed.serialize(format).unwrap(), text, "{} round trip", format);
+            assert!(!text.contains("anniversaries"), "{} writes empty sections", format);
+            assert!(!text.contains("priority"), "{} writes unset fields", format);
+        }
+    }
+
+    #[test]
+    fn test_commented_yaml() {
+        let data = TimexData::from_yaml(
+            r#"
+            # Synced every Monday
+            appointments:
+              - time: 2025-03-14T09:30:00   # local time
+                message: Dentist
+            lists:
+              - list_entry: Milk
+                priority: 2
+            "#,
+        )
+        .unwrap();
+
+        let time = NaiveDate::from_ymd_opt(2025, 3, 14).unwrap().and_hms_opt(9, 30, 0).unwrap();
+        assert_eq!(data.appointments[0].time, AppointmentTime::WallClock(time));
+        assert_eq!(data.lists[0].priority, Some(2));
+        assert!(data.alarms.is_empty());
+    }
+
+    #[test]
+    fn test_commented_toml() {
+        let data = TimexData::from_toml(
+            r#"
+            # Reminder lead time
+            appointment_notification_minutes = 15
+
+            [[alarms]]
+            number = 1
+            audible = true
+            hour = 7
+            minute = 0
+            message = "Wake up"   # shown on the watch
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(data.appointment_notification_minutes, Some(15));
+        assert_eq!(data.alarms[0].message, "Wake up");
+    }
+
+    #[test]
+    fn test_json_schema() {
+        let schema = TimexData::json_schema();
+
+        assert_eq!(schema["title"], "Timex Datalink 150 watch data, version 2");
+        assert!(schema["properties"]["version"].is_object());
+        assert_eq!(schema["$defs"]["ListData"]["properties"]["priority"]["maximum"], 5);
+        assert_eq!(schema["$defs"]["AppointmentData"]["properties"]["time"]["type"], "string");
+
+        let phone_types = schema["$defs"]["PhoneTypeData"].to_string();
+        assert!(["cell", "home", "work", "other"].iter().all(|code| phone_types.contains(code)));
+    }
+
+    #[test]
+    fn test_legacy_files() {
+        let data = TimexData::from_json(r#"{ "phone_numbers": [{ "name": "Doc", "number": "555", "type": "H" }] }"#).unwrap();
+
+        assert_eq!(data.version, SCHEMA_VERSION);
+        assert_eq!(data.phone_numbers[0].r#type, Some(PhoneTypeData::Home));
+
+        // Version 1 accepted any phone type, version 2 only the known ones
+        for legacy in [
+            r#"{ "phone_numbers": [{ "name": "Doc", "number": "555", "type": "fax" }] }"#,
+            r#"{ "version": 1, "phone_numbers": [{ "name": "Doc", "number": "555", "type": "fax" }] }"#,
+        ] {
+            let data = TimexData::from_json(legacy).unwrap();
+            assert_eq!(data.phone_numbers[0].r#type, Some(PhoneTypeData::Other));
+        }
+        assert!(TimexData::from_json(r#"{ "version": 2, "phone_numbers": [{ "name": "Doc", "number": "555", "type": "fax" }] }"#).is_err());
+
+        let data = TimexData::from_toml("[[phone_numbers]]\nname = \"Doc\"\nnumber = \"555\"\ntype = \"pager\"\n").unwrap();
+        assert_eq!(data.phone_numbers[0].r#type, Some(PhoneTypeData::Other));
+    }
+
+    #[test]
+    fn test_appointment_times() {
+        let wall_clock = NaiveDate::from_ymd_opt(2025, 3, 14).unwrap().and_hms_opt(9, 30, 0).unwrap();
+        for time in ["2025-03-14T09:30:00", "2025-03-14T09:30", "2025-03-14 09:30:00", "2025-03-14 09:30"] {
+            assert_eq!(time.parse(), Ok(AppointmentTime::WallClock(wall_clock)), "{}", time);
+        }
+        let midnight: AppointmentTime = "2025-03-14".parse().unwrap();
+        assert_eq!(midnight.to_string(), "2025-03-14T00:00:00");
+
+        let offset: AppointmentTime = "2025-03-14T09:30:00+01:00".parse().unwrap();
+        assert_eq!(offset.to_string(), "2025-03-14T09:30:00+01:00");
+        assert_eq!(offset.wall_clock(Some(chrono_tz::Europe::London)), wall_clock - chrono::Duration::hours(1));
+
+        assert_eq!(
+            "next tuesday".parse::<AppointmentTime>(),
+            Err(InvalidAppointmentTime { time: "next tuesday".to_string() })
+        );
+        let error = TimexData::from_json(r#"{ "appointments": [{ "time": "next tuesday", "message": "Dentist" }] }"#).unwrap_err();
+        assert!(error.to_string().contains("\"next tuesday\" is not a date and time"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = TimexData::parse("alarms: 3", DataFormat::Yaml).unwrap_err();
+        assert!(error.to_string().starts_with("Invalid YAML: "));
+
+        let error = TimexData::default().save(Path::new("watch.txt")).unwrap_err();
+        assert!(matches!(error, DataError::UnknownFormat { .. }));
+    }
+
+    #[test]
+    fn test_load_without_extension() {
+        let json = sample().serialize(DataFormat::Json).unwrap();
+        let path = std::env::temp_dir().join(format!("timex_datalink_{}_watch_data", std::process::id()));
+        fs::write(&path, &json).unwrap();
+        let loaded = TimexData::load(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.unwrap().serialize(DataFormat::Json).unwrap(), json);
+    }
+}
@@ -0,0 +1,238 @@
+//! Watch data validation
+//!
+//! This module checks watch data before anything is encoded and reports every
+//! problem at once. Errors are data the watch cannot represent (an alarm
+//! number used twice, a priority of 7); warnings are data the encoders would
+//! silently change (characters the watch cannot show, text that is cut off).
+
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::Serialize;
+
+use super::{TimexData, SCHEMA_VERSION};
+use crate::char_encoders::{
+    ALARM_MESSAGE_LENGTH, CHARS, EEPROM_CHARS, EEPROM_TEXT_LENGTH, PHONE_CHARS, PHONE_NUMBER_LENGTH,
+};
+use crate::models::NotificationMinutes;
+
+/// How serious a problem is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The data cannot be sent
+    Error,
+    /// The data can be sent, but will not appear exactly as written
+    Warning,
+}
+
+/// A problem found in the watch data
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Problem {
+    /// Path of the offending value (e.g. "alarms[1].number")
+    pub path: String,
+
+    /// How serious the problem is
+    pub severity: Severity,
+
+    /// Description of the problem
+    pub message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "{}: {}: {}", severity, self.path, self.message)
+    }
+}
+
+/// Every problem found in the watch data
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct ValidationReport {
+    /// Problems in the order of the data
+    pub problems: Vec<Problem>,
+}
+
+impl ValidationReport {
+    /// Whether the data can be sent (warnings are allowed)
+    pub fn is_valid(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    /// Problems that prevent the data from being sent
+    pub fn errors(&self) -> impl Iterator<Item = &Problem> {
+        self.problems.iter().filter(|problem| problem.severity == Severity::Error)
+    }
+
+    /// Problems that change how the data appears on the watch
+    pub fn warnings(&self) -> impl Iterator<Item = &Problem> {
+        self.problems.iter().filter(|problem| problem.severity == Severity::Warning)
+    }
+
+    fn error(&mut self, path: String, message: String) {
+        self.problems.push(Problem { path, severity: Severity::Error, message });
+    }
+
+    fn warning(&mut self, path: String, message: String) {
+        self.problems.push(Problem { path, severity: Severity::Warning, message });
+    }
+
+    /// Report characters missing from the watch's character set and text that is cut off
+    fn check_text(&mut self, path: String, text: &str, charset: &str, max_length: usize) {
+        let mut unsupported: Vec<char> = Vec::new();
+        for c in text.to_lowercase().chars() {
+            if !charset.contains(c) && !unsupported.contains(&c) {
+                unsupported.push(c);
+            }
+        }
+        if !unsupported.is_empty() {
+            let list: Vec<String> = unsupported.iter().map(|c| format!("{:?}", c)).collect();
+            self.warning(path.clone(), format!("unsupported characters {} are shown as spaces", list.join(", ")));
+        }
+
+        let length = text.chars().count();
+        if length > max_length {
+            let kept: String = text.chars().take(max_length).collect();
+            self.warning(path, format!("{} characters long, truncated to \"{}\"", length, kept));
+        }
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for problem in &self.problems {
+            writeln!(f, "{}", problem)?;
+        }
+        Ok(())
+    }
+}
+
+/// Check watch data for problems before it is encoded
+///
+/// # Arguments
+///
+/// * `data` - The watch data to check
+///
+/// # Returns
+///
+/// A report of every problem found, empty if there are none
+pub fn validate(data: &TimexData) -> ValidationReport {
+    let mut report = ValidationReport::default();
+
+    if data.version == 0 || data.version > SCHEMA_VERSION {
+        report.error("version".to_string(), format!("unsupported schema version {} (expected 1-{})", data.version, SCHEMA_VERSION));
+    }
+
+    if let Some(minutes) = data.appointment_notification_minutes {
+        if NotificationMinutes::from_minutes(minutes).is_none() {
+            report.error(
+                "appointment_notification_minutes".to_string(),
+                format!("{} is not 0-30 in steps of 5", minutes),
+            );
+        }
+    }
+
+    for (i, appointment) in data.appointments.iter().enumerate() {
+        report.check_text(format!("appointments[{}].message", i), &appointment.message, EEPROM_CHARS, EEPROM_TEXT_LENGTH);
+    }
+
+    for (i, anniversary) in data.anniversaries.iter().enumerate() {
+        if anniversary.date().is_none() {
+            report.error(format!("anniversaries[{}].time", i), format!("\"{}\" is not a date", anniversary.time));
+        }
+        report.check_text(format!("anniversaries[{}].anniversary", i), &anniversary.anniversary, EEPROM_CHARS, EEPROM_TEXT_LENGTH);
+    }
+
+    for (i, phone_number) in data.phone_numbers.iter().enumerate() {
+        report.check_text(format!("phone_numbers[{}].name", i), &phone_number.name, EEPROM_CHARS, EEPROM_TEXT_LENGTH);
+        report.check_text(format!("phone_numbers[{}].number", i), &phone_number.number, PHONE_CHARS, PHONE_NUMBER_LENGTH);
+    }
+
+    for (i, list) in data.lists.iter().enumerate() {
+        report.check_text(format!("lists[{}].list_entry", i), &list.list_entry, EEPROM_CHARS, EEPROM_TEXT_LENGTH);
+        if let Some(priority) = list.priority {
+            if !(1..=5).contains(&priority) {
+                report.error(format!("lists[{}].priority", i), format!("{} is out of range (1-5)", priority));
+            }
+        }
+    }
+
+    let mut alarm_numbers: HashMap<u8, usize> = HashMap::new();
+    for (i, alarm) in data.alarms.iter().enumerate() {
+        if !(1..=5).contains(&alarm.number) {
+            report.error(format!("alarms[{}].number", i), format!("{} is out of range (1-5)", alarm.number));
+        } else if let Some(first) = alarm_numbers.get(&alarm.number) {
+            report.error(format!("alarms[{}].number", i), format!("alarm {} is already set by alarms[{}]", alarm.number, first));
+        } else {
+            alarm_numbers.insert(alarm.number, i);
+        }
+        if alarm.hour > 23 {
+            report.error(format!("alarms[{}].hour", i), format!("{} is out of range (0-23)", alarm.hour));
+        }
+        if alarm.minute > 59 {
+            report.error(format!("alarms[{}].minute", i), format!("{} is out of range (0-59)", alarm.minute));
+        }
+        report.check_text(format!("alarms[{}].message", i), &alarm.message, CHARS, ALARM_MESSAGE_LENGTH);
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_data() {
+        let data = TimexData::from_yaml(
+            r#"
+            appointments:
+              - time: 2025-03-14T09:30:00
+                message: Dentist
+            phone_numbers:
+              - name: Doc Brown
+                number: "5551234"
+                type: H
+            alarms:
+              - { number: 1, audible: true, hour: 7, minute: 0, message: Wake up }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(validate(&data), ValidationReport::default());
+    }
+
+    #[test]
+    fn test_report_every_problem() {
+        let data = TimexData::from_json(r#"{
+            "version": 3,
+            "appointments": [{ "time": "2025-03-14T09:30:00", "message": "Café" }],
+            "phone_numbers": [{ "name": "Emmett Lathrop Brown, Hill Valley resident", "number": "(555) 123-4567" }],
+            "lists": [{ "list_entry": "Milk", "priority": 7 }],
+            "alarms": [
+                { "number": 2, "audible": true, "hour": 7, "minute": 0, "message": "Wake" },
+                { "number": 2, "audible": true, "hour": 24, "minute": 0, "message": "Alarm~2" }
+            ]
+        }"#).unwrap();
+
+        let report = validate(&data);
+        let problems: Vec<String> = report.problems.iter().map(|problem| problem.to_string()).collect();
+        assert_eq!(problems, vec![
+            "error: version: unsupported schema version 3 (expected 1-2)",
+            "warning: appointments[0].message: unsupported characters 'é' are shown as spaces",
+            "warning: phone_numbers[0].name: 42 characters long, truncated to \"Emmett Lathrop Brown, Hill Vall\"",
+            "warning: phone_numbers[0].number: unsupported characters '(', ')', '-' are shown as spaces",
+            "warning: phone_numbers[0].number: 14 characters long, truncated to \"(555) 123-\"",
+            "error: lists[0].priority: 7 is out of range (1-5)",
+            "error: alarms[1].number: alarm 2 is already set by alarms[0]",
+            "error: alarms[1].hour: 24 is out of range (0-23)",
+            "warning: alarms[1].message: unsupported characters '~' are shown as spaces",
+        ]);
+        assert!(!report.is_valid());
+        assert_eq!(report.errors().count(), 4);
+        assert_eq!(report.warnings().count(), 5);
+    }
+}
@@ -0,0 +1,193 @@
+//! Appointment Time module
+//!
+//! Appointments are stored on the watch as a month, a day and a 15-minute
+//! slot of that day. This module converts timezone-aware times into the
+//! watch's wall-clock time, rounds them into slots according to an explicit
+//! policy and reports appointments whose displayed time differs from the
+//! requested one.
+
+use std::fmt;
+use std::time::SystemTime;
+
+use chrono::{DateTime, Duration, NaiveDateTime, NaiveTime, TimeZone, Timelike, Utc};
+
+/// Length of an appointment time slot in minutes
+pub const SLOT_MINUTES: u32 = 15;
+
+/// How appointment times are rounded into 15-minute slots
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RoundingPolicy {
+    /// Round down to the start of the slot (the watch's historical behavior)
+    #[default]
+    Floor,
+    /// Round to the nearest slot start (halfway rounds up)
+    Nearest,
+    /// Round up to the next slot start
+    Ceil,
+}
+
+impl std::str::FromStr for RoundingPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "floor" => Ok(RoundingPolicy::Floor),
+            "nearest" => Ok(RoundingPolicy::Nearest),
+            "ceil" => Ok(RoundingPolicy::Ceil),
+            _ => Err(format!("unknown rounding policy: {} (expected floor, nearest or ceil)", s)),
+        }
+    }
+}
+
+/// Convert a timezone-aware datetime into the watch's wall-clock time
+pub fn wall_clock<Tz: TimeZone>(time: &DateTime<Tz>) -> NaiveDateTime {
+    time.naive_local()
+}
+
+/// Convert a `SystemTime` into wall-clock time in a time zone
+///
+/// # Arguments
+///
+/// * `time` - The instant to convert
+/// * `zone` - The time zone the watch is set to (`chrono::Local`, `chrono::Utc` or a `chrono_tz::Tz`)
+pub fn wall_clock_in<Tz: TimeZone>(time: SystemTime, zone: &Tz) -> NaiveDateTime {
+    DateTime::<Utc>::from(time).with_timezone(zone).naive_local()
+}
+
+/// Round a wall-clock time into a 15-minute slot
+///
+/// Seconds are ignored. Rounding up past midnight moves the appointment to
+/// the next day.
+///
+/// # Returns
+///
+/// The start of the slot the appointment is displayed at
+pub fn quantize(time: NaiveDateTime, policy: RoundingPolicy) -> NaiveDateTime {
+    let time = time.with_second(0).and_then(|t| t.with_nanosecond(0)).unwrap_or(time);
+    let offset = time.minute() % SLOT_MINUTES;
+    let floor = time - Duration::minutes(offset as i64);
+
+    let round_up = match policy {
+        RoundingPolicy::Floor => false,
+        RoundingPolicy::Nearest => offset * 2 >= SLOT_MINUTES,
+        RoundingPolicy::Ceil => offset > 0,
+    };
+
+    if round_up {
+        floor + Duration::minutes(SLOT_MINUTES as i64)
+    } else {
+        floor
+    }
+}
+
+/// Slot number (0-95) of a quantized wall-clock time
+pub fn slot(time: NaiveDateTime) -> u8 {
+    (time.hour() * (60 / SLOT_MINUTES) + time.minute() / SLOT_MINUTES) as u8
+}
+
+/// Start time of a slot number, or None if it is not a slot of the day
+pub fn slot_time(slot: u8) -> Option<NaiveTime> {
+    let minutes = slot as u32 * SLOT_MINUTES;
+    NaiveTime::from_hms_opt(minutes / 60, minutes % 60, 0)
+}
+
+/// An appointment whose displayed time differs from the requested time
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuantizationWarning {
+    /// Position of the appointment in the EEPROM model
+    pub index: usize,
+
+    /// Appointment message
+    pub message: String,
+
+    /// Requested wall-clock time
+    pub requested: NaiveDateTime,
+
+    /// Wall-clock time shown by the watch
+    pub displayed: NaiveDateTime,
+}
+
+impl fmt::Display for QuantizationWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "appointment {} \"{}\" requested at {} will be shown at {}",
+            self.index + 1,
+            self.message,
+            self.requested.format("%Y-%m-%d %H:%M"),
+            self.displayed.format("%Y-%m-%d %H:%M")
+        )
+    }
+}
+
+/// Build a warning if rounding changes the displayed time
+///
+/// # Arguments
+///
+/// * `index` - Position of the appointment
+/// * `message` - Appointment message
+/// * `requested` - Requested wall-clock time
+/// * `policy` - Rounding policy of the appointment
+pub fn quantization_warning(
+    index: usize,
+    message: &str,
+    requested: NaiveDateTime,
+    policy: RoundingPolicy,
+) -> Option<QuantizationWarning> {
+    let displayed = quantize(requested, policy);
+    let requested_minute = requested.with_second(0).and_then(|t| t.with_nanosecond(0)).unwrap_or(requested);
+
+    (displayed != requested_minute).then(|| QuantizationWarning {
+        index,
+        message: message.to_string(),
+        requested,
+        displayed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2022, 10, 31).unwrap().and_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_quantize() {
+        assert_eq!(quantize(at(19, 7), RoundingPolicy::Floor), at(19, 0));
+        assert_eq!(quantize(at(19, 7), RoundingPolicy::Nearest), at(19, 0));
+        assert_eq!(quantize(at(19, 8), RoundingPolicy::Nearest), at(19, 15));
+        assert_eq!(quantize(at(19, 1), RoundingPolicy::Ceil), at(19, 15));
+        assert_eq!(quantize(at(19, 30), RoundingPolicy::Ceil), at(19, 30));
+
+        // Rounding up at the end of the day moves to the next day
+        let next_day = NaiveDate::from_ymd_opt(2022, 11, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        assert_eq!(quantize(at(23, 55), RoundingPolicy::Ceil), next_day);
+        assert_eq!(slot(at(19, 45)), 79);
+        assert_eq!(slot_time(79), NaiveTime::from_hms_opt(19, 45, 0));
+        assert_eq!(slot_time(96), None);
+    }
+
+    #[test]
+    fn test_quantization_warning() {
+        assert_eq!(quantization_warning(0, "On time", at(19, 0), RoundingPolicy::Floor), None);
+
+        let warning = quantization_warning(1, "Late", at(19, 10), RoundingPolicy::Floor).unwrap();
+        assert_eq!(warning.displayed, at(19, 0));
+        assert_eq!(
+            warning.to_string(),
+            "appointment 2 \"Late\" requested at 2022-10-31 19:10 will be shown at 2022-10-31 19:00"
+        );
+    }
+
+    #[test]
+    fn test_wall_clock_in_zone() {
+        let instant = Utc.with_ymd_and_hms(2022, 11, 1, 2, 0, 0).unwrap();
+
+        let los_angeles = wall_clock_in(instant.into(), &chrono_tz::America::Los_Angeles);
+        assert_eq!(los_angeles, at(19, 0));
+        assert_eq!(wall_clock(&instant.with_timezone(&chrono_tz::America::Los_Angeles)), at(19, 0));
+    }
+}
@@ -0,0 +1,453 @@
+//! Command Packet module
+//!
+//! This module provides declarative command packet layouts, a builder that
+//! serializes field values according to a layout and a decoder that does the
+//! reverse. Protocol modules define their command registries on top of it.
+
+use std::fmt;
+
+/// Command bytes shared by Protocol 3 and Protocol 4 watches
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Opcode {
+    /// Start of a data transfer (CPACKET_START)
+    Start = 0x20,
+    /// End of a data transfer (CPACKET_SKIP)
+    End = 0x21,
+    /// Time and time zone settings (CPACKET_TIME)
+    Time = 0x32,
+    /// Alarm settings (CPACKET_ALARM)
+    Alarm = 0x50,
+    /// Hourly chime and button beep settings (CPACKET_BEEPS)
+    Beeps = 0x71,
+    /// Start of a load section (CPACKET_SECT)
+    Sect = 0x90,
+    /// Load section payload (CPACKET_DATA)
+    Data = 0x91,
+    /// End of a load section (CPACKET_END)
+    EndSect = 0x92,
+    /// Clear a memory area before loading (CPACKET_CLEAR)
+    Clear = 0x93,
+}
+
+impl Opcode {
+    /// All known opcodes
+    pub const ALL: [Opcode; 9] = [
+        Opcode::Start,
+        Opcode::End,
+        Opcode::Time,
+        Opcode::Alarm,
+        Opcode::Beeps,
+        Opcode::Sect,
+        Opcode::Data,
+        Opcode::EndSect,
+        Opcode::Clear,
+    ];
+
+    /// Command byte for this opcode
+    pub fn byte(self) -> u8 {
+        self as u8
+    }
+
+    /// Name of the opcode, as used in command layout names
+    pub fn name(self) -> &'static str {
+        match self {
+            Opcode::Start => "START",
+            Opcode::End => "END",
+            Opcode::Time => "TIME",
+            Opcode::Alarm => "ALARM",
+            Opcode::Beeps => "BEEPS",
+            Opcode::Sect => "SECT",
+            Opcode::Data => "DATA",
+            Opcode::EndSect => "END_SECT",
+            Opcode::Clear => "CLEAR",
+        }
+    }
+
+    /// Look up an opcode from its command byte
+    pub fn from_byte(byte: u8) -> Option<Opcode> {
+        Self::ALL.iter().copied().find(|opcode| opcode.byte() == byte)
+    }
+}
+
+/// Kind of value stored in a command field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    /// A single byte
+    U8,
+    /// A boolean stored as 0 or 1
+    Bool,
+    /// A big-endian 16-bit value
+    U16,
+    /// A fixed number of encoded watch characters
+    Chars(usize),
+    /// A fixed number of bytes that are always zero
+    Reserved(usize),
+    /// All remaining bytes (only valid as the last field)
+    Tail,
+}
+
+/// A named field in a command layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldLayout {
+    /// Field name
+    pub name: &'static str,
+    /// Field kind
+    pub kind: FieldKind,
+}
+
+impl FieldLayout {
+    /// Create a new field layout
+    pub const fn new(name: &'static str, kind: FieldKind) -> Self {
+        FieldLayout { name, kind }
+    }
+}
+
+/// Declarative layout of a command packet
+///
+/// A command is identified by its prefix (the command byte, optionally
+/// followed by a subtype or fixed bytes) and followed by its fields.
+#[derive(Debug, PartialEq, Eq)]
+pub struct CommandLayout {
+    /// Command name
+    pub name: &'static str,
+    /// Fixed bytes at the start of the command
+    pub prefix: &'static [u8],
+    /// Fields following the prefix
+    pub fields: &'static [FieldLayout],
+}
+
+impl CommandLayout {
+    /// Opcode of this command
+    pub fn opcode(&self) -> Option<Opcode> {
+        self.prefix.first().and_then(|&byte| Opcode::from_byte(byte))
+    }
+
+    /// Start building a command with this layout
+    pub fn builder(&'static self) -> CommandBuilder {
+        CommandBuilder::new(self)
+    }
+
+    /// Check whether a payload starts with this command's prefix
+    pub fn matches(&self, payload: &[u8]) -> bool {
+        payload.starts_with(self.prefix)
+    }
+
+    /// Decode a payload (without CRC framing) into its fields
+    ///
+    /// # Arguments
+    ///
+    /// * `payload` - The command bytes, starting with the prefix
+    ///
+    /// # Returns
+    ///
+    /// The decoded fields, or an error if the payload does not fit the layout
+    pub fn decode(&'static self, payload: &[u8]) -> Result<DecodedCommand, CommandError> {
+        if !self.matches(payload) {
+            return Err(CommandError::PrefixMismatch { command: self.name });
+        }
+
+        let mut rest = &payload[self.prefix.len()..];
+        let mut fields = Vec::with_capacity(self.fields.len());
+
+        for field in self.fields {
+            let length = match field.kind {
+                FieldKind::U8 | FieldKind::Bool => 1,
+                FieldKind::U16 => 2,
+                FieldKind::Chars(length) | FieldKind::Reserved(length) => length,
+                FieldKind::Tail => rest.len(),
+            };
+
+            if rest.len() < length {
+                return Err(CommandError::Truncated { command: self.name, field: field.name });
+            }
+
+            let (raw, tail) = rest.split_at(length);
+            let value = match field.kind {
+                FieldKind::U8 => FieldValue::U8(raw[0]),
+                FieldKind::Bool => FieldValue::Bool(raw[0] != 0),
+                FieldKind::U16 => FieldValue::U16(u16::from_be_bytes([raw[0], raw[1]])),
+                FieldKind::Chars(_) | FieldKind::Reserved(_) | FieldKind::Tail => FieldValue::Bytes(raw.to_vec()),
+            };
+
+            fields.push(DecodedField { name: field.name, value, raw: raw.to_vec() });
+            rest = tail;
+        }
+
+        if !rest.is_empty() {
+            return Err(CommandError::TrailingBytes { command: self.name, count: rest.len() });
+        }
+
+        Ok(DecodedCommand { layout: self, fields })
+    }
+}
+
+/// A field value for building or decoding commands
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldValue {
+    /// Value of a `FieldKind::U8` field
+    U8(u8),
+    /// Value of a `FieldKind::Bool` field
+    Bool(bool),
+    /// Value of a `FieldKind::U16` field
+    U16(u16),
+    /// Value of a `Chars`, `Reserved` or `Tail` field
+    Bytes(Vec<u8>),
+}
+
+/// Errors raised while building or decoding a command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandError {
+    /// A field in the layout was not given a value
+    MissingField { command: &'static str, field: &'static str },
+    /// A value was given for a field that is not in the layout
+    UnknownField { command: &'static str, field: String },
+    /// A value does not match the kind of its field
+    WrongKind { command: &'static str, field: &'static str },
+    /// A fixed-length value has the wrong number of bytes
+    WrongLength { command: &'static str, field: &'static str, expected: usize, actual: usize },
+    /// The payload does not start with the command prefix
+    PrefixMismatch { command: &'static str },
+    /// The payload ended in the middle of a field
+    Truncated { command: &'static str, field: &'static str },
+    /// The payload has bytes left over after the last field
+    TrailingBytes { command: &'static str, count: usize },
+    /// No command in the registry matches the payload
+    UnknownCommand { prefix: Vec<u8> },
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandError::MissingField { command, field } => write!(f, "{}: missing field {}", command, field),
+            CommandError::UnknownField { command, field } => write!(f, "{}: unknown field {}", command, field),
+            CommandError::WrongKind { command, field } => write!(f, "{}: wrong value kind for field {}", command, field),
+            CommandError::WrongLength { command, field, expected, actual } => {
+                write!(f, "{}: field {} needs {} bytes, got {}", command, field, expected, actual)
+            }
+            CommandError::PrefixMismatch { command } => write!(f, "payload is not a {} command", command),
+            CommandError::Truncated { command, field } => write!(f, "{}: payload ends inside field {}", command, field),
+            CommandError::TrailingBytes { command, count } => write!(f, "{}: {} unexpected trailing bytes", command, count),
+            CommandError::UnknownCommand { prefix } => write!(f, "unknown command {:02x?}", prefix),
+        }
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+/// A decoded field
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedField {
+    /// Field name from the layout
+    pub name: &'static str,
+    /// Decoded value
+    pub value: FieldValue,
+    /// Raw bytes of the field
+    pub raw: Vec<u8>,
+}
+
+/// A decoded command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedCommand {
+    /// Layout the command was decoded with
+    pub layout: &'static CommandLayout,
+    /// Decoded fields, in layout order
+    pub fields: Vec<DecodedField>,
+}
+
+impl DecodedCommand {
+    /// Look up a decoded field by name
+    pub fn get(&self, name: &str) -> Option<&FieldValue> {
+        self.fields.iter().find(|field| field.name == name).map(|field| &field.value)
+    }
+}
+
+/// Builder that serializes field values according to a command layout
+#[derive(Debug, Clone)]
+pub struct CommandBuilder {
+    layout: &'static CommandLayout,
+    values: Vec<(String, FieldValue)>,
+}
+
+impl CommandBuilder {
+    /// Create a new builder for a layout
+    pub fn new(layout: &'static CommandLayout) -> Self {
+        CommandBuilder { layout, values: Vec::new() }
+    }
+
+    /// Set a field value
+    pub fn set(mut self, name: &str, value: FieldValue) -> Self {
+        self.values.push((name.to_string(), value));
+        self
+    }
+
+    /// Set a `U8` field
+    pub fn u8(self, name: &str, value: u8) -> Self {
+        self.set(name, FieldValue::U8(value))
+    }
+
+    /// Set a `Bool` field
+    pub fn bool(self, name: &str, value: bool) -> Self {
+        self.set(name, FieldValue::Bool(value))
+    }
+
+    /// Set a `U16` field
+    pub fn u16(self, name: &str, value: u16) -> Self {
+        self.set(name, FieldValue::U16(value))
+    }
+
+    /// Set a `Chars` or `Tail` field
+    pub fn bytes(self, name: &str, value: &[u8]) -> Self {
+        self.set(name, FieldValue::Bytes(value.to_vec()))
+    }
+
+    /// Serialize the command
+    ///
+    /// # Returns
+    ///
+    /// The raw command bytes (without CRC framing), or an error if the
+    /// values do not fit the layout
+    pub fn build(self) -> Result<Vec<u8>, CommandError> {
+        let command = self.layout.name;
+
+        if let Some((name, _)) = self.values.iter()
+            .find(|(name, _)| !self.layout.fields.iter().any(|field| field.name == name.as_str()))
+        {
+            return Err(CommandError::UnknownField { command, field: name.clone() });
+        }
+
+        let mut packet = self.layout.prefix.to_vec();
+
+        for field in self.layout.fields {
+            if let FieldKind::Reserved(length) = field.kind {
+                packet.extend(std::iter::repeat_n(0, length));
+                continue;
+            }
+
+            let value = self.values.iter()
+                .rev()
+                .find(|(name, _)| name == field.name)
+                .map(|(_, value)| value)
+                .ok_or(CommandError::MissingField { command, field: field.name })?;
+
+            match (field.kind, value) {
+                (FieldKind::U8, FieldValue::U8(byte)) => packet.push(*byte),
+                (FieldKind::Bool, FieldValue::Bool(flag)) => packet.push(u8::from(*flag)),
+                (FieldKind::U16, FieldValue::U16(word)) => packet.extend(word.to_be_bytes()),
+                (FieldKind::Chars(length), FieldValue::Bytes(bytes)) => {
+                    if bytes.len() != length {
+                        return Err(CommandError::WrongLength {
+                            command,
+                            field: field.name,
+                            expected: length,
+                            actual: bytes.len(),
+                        });
+                    }
+                    packet.extend(bytes);
+                }
+                (FieldKind::Tail, FieldValue::Bytes(bytes)) => packet.extend(bytes),
+                _ => return Err(CommandError::WrongKind { command, field: field.name }),
+            }
+        }
+
+        Ok(packet)
+    }
+}
+
+/// A set of command layouts understood by a protocol
+#[derive(Debug)]
+pub struct CommandRegistry {
+    /// Layouts in the registry
+    pub commands: &'static [&'static CommandLayout],
+}
+
+impl CommandRegistry {
+    /// Find the layout matching a payload (longest prefix wins)
+    pub fn identify(&self, payload: &[u8]) -> Option<&'static CommandLayout> {
+        self.commands.iter()
+            .copied()
+            .filter(|layout| layout.matches(payload))
+            .max_by_key(|layout| layout.prefix.len())
+    }
+
+    /// Look up a layout by name
+    pub fn get(&self, name: &str) -> Option<&'static CommandLayout> {
+        self.commands.iter().copied().find(|layout| layout.name == name)
+    }
+
+    /// Identify and decode a payload
+    pub fn decode(&self, payload: &[u8]) -> Result<DecodedCommand, CommandError> {
+        match self.identify(payload) {
+            Some(layout) => layout.decode(payload),
+            None => Err(CommandError::UnknownCommand {
+                prefix: payload.iter().take(2).copied().collect(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static EXAMPLE: CommandLayout = CommandLayout {
+        name: "EXAMPLE",
+        prefix: &[0x50],
+        fields: &[
+            FieldLayout::new("number", FieldKind::U8),
+            FieldLayout::new("unused", FieldKind::Reserved(2)),
+            FieldLayout::new("message", FieldKind::Chars(3)),
+            FieldLayout::new("audible", FieldKind::Bool),
+        ],
+    };
+
+    #[test]
+    fn test_build_and_decode() {
+        let packet = EXAMPLE.builder()
+            .u8("number", 2)
+            .bytes("message", &[1, 2, 3])
+            .bool("audible", true)
+            .build()
+            .unwrap();
+
+        assert_eq!(packet, vec![0x50, 2, 0, 0, 1, 2, 3, 1]);
+
+        let decoded = EXAMPLE.decode(&packet).unwrap();
+        assert_eq!(decoded.get("number"), Some(&FieldValue::U8(2)));
+        assert_eq!(decoded.get("message"), Some(&FieldValue::Bytes(vec![1, 2, 3])));
+        assert_eq!(decoded.get("audible"), Some(&FieldValue::Bool(true)));
+    }
+
+    #[test]
+    fn test_build_errors() {
+        assert_eq!(
+            EXAMPLE.builder().u8("number", 1).bool("audible", true).build(),
+            Err(CommandError::MissingField { command: "EXAMPLE", field: "message" })
+        );
+        assert!(matches!(
+            EXAMPLE.builder().u8("number", 1).bytes("message", &[1]).bool("audible", true).build(),
+            Err(CommandError::WrongLength { expected: 3, actual: 1, .. })
+        ));
+        assert!(matches!(
+            EXAMPLE.builder().u8("bogus", 1).build(),
+            Err(CommandError::UnknownField { .. })
+        ));
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert!(matches!(EXAMPLE.decode(&[0x50, 1, 0]), Err(CommandError::Truncated { .. })));
+        assert!(matches!(
+            EXAMPLE.decode(&[0x50, 1, 0, 0, 1, 2, 3, 1, 9]),
+            Err(CommandError::TrailingBytes { count: 1, .. })
+        ));
+        assert!(matches!(EXAMPLE.decode(&[0x51]), Err(CommandError::PrefixMismatch { .. })));
+    }
+
+    #[test]
+    fn test_opcode_round_trip() {
+        for opcode in Opcode::ALL {
+            assert_eq!(Opcode::from_byte(opcode.byte()), Some(opcode));
+        }
+        assert_eq!(Opcode::from_byte(0x00), None);
+    }
+}
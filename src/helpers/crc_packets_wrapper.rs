@@ -2,9 +2,68 @@
 //! 
 //! This module provides functionality to wrap packets with CRC checksums
 //! Ported from Ruby's CrcPacketsWrapper
+//!
+//! It also provides the inverse operations: validating and unwrapping framed
+//! packets, and splitting a raw byte stream back into packets.
+
+use std::fmt;
 
 use crc16::*;
 
+/// Ping byte that starts a sync packet
+const PING_BYTE: u8 = 0x78;
+
+/// Sync bytes that follow the ping byte in a sync packet
+const SYNC_1_BYTE: u8 = 0x55;
+const SYNC_2_BYTE: u8 = 0xaa;
+
+/// Length of the CRC header and footer combined
+const FRAMING_LENGTH: usize = 3;
+
+/// Errors returned when unwrapping a CRC framed packet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CrcError {
+    /// The packet is too short to contain a header and footer
+    TooShort {
+        /// Actual length of the packet
+        length: usize,
+    },
+
+    /// The length header does not match the packet length
+    LengthMismatch {
+        /// Length declared in the header byte
+        declared: u8,
+        /// Actual length of the packet
+        actual: usize,
+    },
+
+    /// The CRC footer does not match the packet contents
+    CrcMismatch {
+        /// CRC calculated from the header and payload
+        expected: u16,
+        /// CRC found in the packet footer
+        actual: u16,
+    },
+}
+
+impl fmt::Display for CrcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CrcError::TooShort { length } => {
+                write!(f, "packet of {} bytes is too short to be CRC framed", length)
+            }
+            CrcError::LengthMismatch { declared, actual } => {
+                write!(f, "length header says {} bytes but packet has {}", declared, actual)
+            }
+            CrcError::CrcMismatch { expected, actual } => {
+                write!(f, "CRC mismatch: expected 0x{:04x}, got 0x{:04x}", expected, actual)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CrcError {}
+
 /// Wrapper function that adds CRC headers and footers to packets
 /// 
 /// # Arguments
@@ -38,7 +97,7 @@ pub fn wrap_packets_with_crc(packets: Vec<Vec<u8>>) -> Vec<Vec<u8>> {
 /// 
 /// A vector containing the header byte(s)
 fn crc_header(packet: &[u8]) -> Vec<u8> {
-    vec![(packet.len() + 3) as u8]
+    vec![(packet.len() + FRAMING_LENGTH) as u8]
 }
 
 /// Calculates the CRC footer for a packet
@@ -51,17 +110,236 @@ fn crc_header(packet: &[u8]) -> Vec<u8> {
 /// 
 /// A vector containing the footer bytes
 fn crc_footer(packet: &[u8]) -> Vec<u8> {
+    let crc = packet_crc(packet);
+    
+    // Split into two bytes (equivalent to Ruby's divmod(256))
+    vec![(crc >> 8) as u8, (crc & 0xFF) as u8]
+}
+
+/// Calculates the CRC16-ARC of a packet's header and payload
+fn packet_crc(packet: &[u8]) -> u16 {
     // Create the CRC check bytes (header + packet)
     let mut crc_check = Vec::with_capacity(1 + packet.len());
-    crc_check.push((packet.len() + 3) as u8); // Header
+    crc_check.push((packet.len() + FRAMING_LENGTH) as u8); // Header
     crc_check.extend_from_slice(packet);
     
     // Calculate CRC16-ARC
     let mut state = State::<ARC>::new();
     state.update(&crc_check);
-    let crc = state.get();
+    state.get()
+}
+
+/// Validates a CRC framed packet and returns its payload
+///
+/// This is the inverse of `wrap_packets_with_crc` for a single packet.
+///
+/// # Arguments
+///
+/// * `packet` - The framed packet (length header + payload + CRC footer)
+///
+/// # Returns
+///
+/// The payload without header and footer, or the reason the packet is invalid
+pub fn unwrap_packet_with_crc(packet: &[u8]) -> Result<Vec<u8>, CrcError> {
+    if packet.len() < FRAMING_LENGTH {
+        return Err(CrcError::TooShort { length: packet.len() });
+    }
     
-    // Split into two bytes (equivalent to Ruby's divmod(256))
-    vec![(crc >> 8) as u8, (crc & 0xFF) as u8]
+    if packet[0] as usize != packet.len() {
+        return Err(CrcError::LengthMismatch {
+            declared: packet[0],
+            actual: packet.len(),
+        });
+    }
+    
+    let payload = &packet[1..packet.len() - 2];
+    let expected = packet_crc(payload);
+    let actual = u16::from_be_bytes([packet[packet.len() - 2], packet[packet.len() - 1]]);
+    
+    if expected != actual {
+        return Err(CrcError::CrcMismatch { expected, actual });
+    }
+    
+    Ok(payload.to_vec())
 }
 
+/// Validates a list of CRC framed packets and returns their payloads
+///
+/// # Arguments
+///
+/// * `packets` - The framed packets
+///
+/// # Returns
+///
+/// The payloads, or the first error encountered together with the packet index
+pub fn unwrap_packets_with_crc(packets: &[Vec<u8>]) -> Result<Vec<Vec<u8>>, (usize, CrcError)> {
+    packets.iter()
+        .enumerate()
+        .map(|(index, packet)| unwrap_packet_with_crc(packet).map_err(|e| (index, e)))
+        .collect()
+}
+
+/// Checks whether a packet is a sync packet (ping byte followed by sync bytes)
+///
+/// Sync packets are not CRC framed and should be skipped before unwrapping.
+pub fn is_sync_packet(packet: &[u8]) -> bool {
+    packet.first() == Some(&PING_BYTE)
+        && packet.len() > 1
+        && packet[1..].iter().all(|&byte| byte == SYNC_1_BYTE || byte == SYNC_2_BYTE)
+}
+
+/// Splits a byte stream into packets
+///
+/// This is a convenience wrapper around `PacketFramer` for complete captures.
+///
+/// # Arguments
+///
+/// * `stream` - The raw bytes as sent to the watch
+///
+/// # Returns
+///
+/// The framed packets (sync packets included), followed by any trailing
+/// bytes that did not form a complete packet
+pub fn split_packets(stream: &[u8]) -> (Vec<Vec<u8>>, Vec<u8>) {
+    let mut framer = PacketFramer::new();
+    let mut packets = framer.push(stream);
+    let (rest, remainder) = framer.finish();
+    packets.extend(rest);
+    
+    (packets, remainder)
+}
+
+/// Incremental framer that splits a byte stream into packets
+///
+/// Bytes can be fed in arbitrary chunks (e.g. as they arrive from a serial
+/// port). Sync packets are recognised by their ping byte and returned whole,
+/// every other packet is delimited by its length header.
+#[derive(Debug, Default)]
+pub struct PacketFramer {
+    /// Bytes of the packet currently being assembled
+    buffer: Vec<u8>,
+    
+    /// Whether the buffer holds a sync packet
+    in_sync: bool,
+}
+
+impl PacketFramer {
+    /// Create a new framer
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Feed bytes into the framer
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The next chunk of the stream
+    ///
+    /// # Returns
+    ///
+    /// All packets completed by this chunk
+    pub fn push(&mut self, bytes: &[u8]) -> Vec<Vec<u8>> {
+        let mut packets = Vec::new();
+        
+        for &byte in bytes {
+            if self.in_sync {
+                if byte == SYNC_1_BYTE || byte == SYNC_2_BYTE {
+                    self.buffer.push(byte);
+                    continue;
+                }
+                
+                // First non-sync byte ends the sync packet
+                packets.push(std::mem::take(&mut self.buffer));
+                self.in_sync = false;
+            }
+            
+            if self.buffer.is_empty() && byte == PING_BYTE {
+                self.in_sync = true;
+                self.buffer.push(byte);
+                continue;
+            }
+            
+            self.buffer.push(byte);
+            
+            // Lengths too short to hold a framed packet are emitted as-is so
+            // that validation can report them
+            let length = (self.buffer[0] as usize).max(1);
+            if self.buffer.len() >= length {
+                packets.push(std::mem::take(&mut self.buffer));
+            }
+        }
+        
+        packets
+    }
+    
+    /// Finish framing
+    ///
+    /// # Returns
+    ///
+    /// A pending sync packet if the stream ended with one, and any bytes of
+    /// an incomplete packet
+    pub fn finish(self) -> (Vec<Vec<u8>>, Vec<u8>) {
+        if self.in_sync {
+            (vec![self.buffer], Vec::new())
+        } else {
+            (Vec::new(), self.buffer)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unwrap_round_trip() {
+        let payload = vec![0x20, 0x00, 0x00, 0x03];
+        let wrapped = wrap_packets_with_crc(vec![payload.clone()]);
+
+        // From golden fixture: start.jsonl
+        assert_eq!(wrapped, vec![vec![7, 32, 0, 0, 3, 1, 254]]);
+        assert_eq!(unwrap_packet_with_crc(&wrapped[0]), Ok(payload));
+    }
+
+    #[test]
+    fn test_unwrap_errors() {
+        assert_eq!(unwrap_packet_with_crc(&[4, 33]), Err(CrcError::TooShort { length: 2 }));
+        assert_eq!(
+            unwrap_packet_with_crc(&[5, 33, 216, 194]),
+            Err(CrcError::LengthMismatch { declared: 5, actual: 4 })
+        );
+        assert_eq!(
+            unwrap_packet_with_crc(&[4, 33, 216, 195]),
+            Err(CrcError::CrcMismatch { expected: 0xd8c2, actual: 0xd8c3 })
+        );
+        assert_eq!(
+            unwrap_packets_with_crc(&[vec![4, 33, 216, 194], vec![4, 33, 0, 0]]).unwrap_err().0,
+            1
+        );
+    }
+
+    #[test]
+    fn test_split_packets() {
+        let packets = vec![
+            vec![0x78, 0x55, 0x55, 0xaa, 0xaa],
+            vec![7, 32, 0, 0, 3, 1, 254],
+            vec![4, 33, 216, 194],
+        ];
+        let stream: Vec<u8> = packets.concat();
+
+        assert_eq!(split_packets(&stream), (packets, Vec::new()));
+    }
+
+    #[test]
+    fn test_framer_chunks() {
+        let stream = [0x78, 0x55, 0xaa, 7, 32, 0, 0, 3, 1, 254, 4, 33];
+        let mut framer = PacketFramer::new();
+
+        assert!(framer.push(&stream[..3]).is_empty());
+        assert_eq!(
+            framer.push(&stream[3..]),
+            vec![vec![0x78, 0x55, 0xaa], vec![7, 32, 0, 0, 3, 1, 254]]
+        );
+        assert_eq!(framer.finish(), (Vec::new(), vec![4, 33]));
+    }
+}
@@ -0,0 +1,380 @@
+//! Load Section module
+//!
+//! This module provides the packet sequence shared by every upload to the
+//! watch's memory (EEPROM data, wrist apps and sound themes):
+//!
+//! ```text
+//! [CPACKET_CLEAR]  (optional)
+//! CPACKET_SECT     packet count + header fields
+//! CPACKET_DATA     index + up to 32 payload bytes (repeated)
+//! CPACKET_END
+//! ```
+//!
+//! It also provides the matching parser that reassembles the payload from a
+//! packet sequence.
+
+use std::fmt;
+
+use crate::PacketGenerator;
+use crate::helpers::command_packet::Opcode;
+use crate::helpers::cpacket_paginator;
+use crate::helpers::crc_packets_wrapper::{self, CrcError};
+
+/// Maximum number of payload bytes in a single data packet
+pub const CPACKET_DATA_LENGTH: usize = 32;
+
+/// Maximum number of data packets in a section (the count is a single byte)
+pub const MAX_PACKET_COUNT: usize = 0xff;
+
+/// Maximum number of payload bytes in a section
+pub const MAX_PAYLOAD_LENGTH: usize = MAX_PACKET_COUNT * CPACKET_DATA_LENGTH;
+
+/// A load section upload
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadSection {
+    /// Section subtype (1 = EEPROM, 2 = wrist app, 3 = sound theme)
+    pub subtype: u8,
+
+    /// Whether a clear packet is sent before the section
+    pub clear: bool,
+
+    /// Header fields sent in the section packet after the packet count
+    pub header: Vec<u8>,
+
+    /// Payload bytes sent in the data packets
+    pub payload: Vec<u8>,
+}
+
+impl LoadSection {
+    /// Create a new load section
+    ///
+    /// # Arguments
+    ///
+    /// * `subtype` - The section subtype
+    /// * `clear` - Whether to send a clear packet before the section
+    /// * `header` - Header fields following the packet count
+    /// * `payload` - Payload bytes to paginate into data packets
+    pub fn new(subtype: u8, clear: bool, header: Vec<u8>, payload: Vec<u8>) -> Self {
+        LoadSection { subtype, clear, header, payload }
+    }
+
+    /// Number of data packets needed for the payload
+    pub fn packet_count(&self) -> usize {
+        self.payload.len().div_ceil(CPACKET_DATA_LENGTH)
+    }
+
+    /// Generate the packet sequence without CRC framing
+    ///
+    /// # Returns
+    ///
+    /// The packets, or an error if the payload needs more data packets than
+    /// the section packet can count
+    pub fn raw_packets(&self) -> Result<Vec<Vec<u8>>, LoadSectionError> {
+        let count = self.packet_count();
+        if count > MAX_PACKET_COUNT {
+            return Err(LoadSectionError::TooManyPackets { count });
+        }
+
+        let payloads = cpacket_paginator::paginate_cpackets(
+            &[Opcode::Data.byte(), self.subtype],
+            CPACKET_DATA_LENGTH,
+            &self.payload
+        );
+
+        let mut sect_packet = Vec::with_capacity(3 + self.header.len());
+        sect_packet.extend_from_slice(&[Opcode::Sect.byte(), self.subtype]);
+        sect_packet.push(payloads.len() as u8);
+        sect_packet.extend_from_slice(&self.header);
+
+        let mut all_packets = Vec::with_capacity(payloads.len() + 3);
+        if self.clear {
+            all_packets.push(vec![Opcode::Clear.byte(), self.subtype]);
+        }
+        all_packets.push(sect_packet);
+        all_packets.extend(payloads);
+        all_packets.push(vec![Opcode::EndSect.byte(), self.subtype]);
+
+        Ok(all_packets)
+    }
+
+    /// Reassemble a load section from CRC-wrapped packets
+    ///
+    /// # Arguments
+    ///
+    /// * `packets` - The packets of exactly one load section, as produced by `packets()`
+    ///
+    /// # Returns
+    ///
+    /// The load section, or an error describing the first malformed packet
+    pub fn parse(packets: &[Vec<u8>]) -> Result<Self, LoadSectionError> {
+        let raw_packets = crc_packets_wrapper::unwrap_packets_with_crc(packets)
+            .map_err(|(index, error)| LoadSectionError::Crc { index, error })?;
+
+        Self::parse_raw(&raw_packets)
+    }
+
+    /// Reassemble a load section from packets without CRC framing
+    ///
+    /// # Arguments
+    ///
+    /// * `packets` - The packets of exactly one load section, as produced by `raw_packets()`
+    ///
+    /// # Returns
+    ///
+    /// The load section, or an error describing the first malformed packet
+    pub fn parse_raw(packets: &[Vec<u8>]) -> Result<Self, LoadSectionError> {
+        let (section, consumed) = parse_section_at(packets, 0)?;
+
+        if consumed != packets.len() {
+            return Err(LoadSectionError::UnexpectedPacket { index: consumed });
+        }
+
+        Ok(section)
+    }
+}
+
+impl PacketGenerator for LoadSection {
+    /// # Panics
+    ///
+    /// Panics if the payload is longer than `MAX_PAYLOAD_LENGTH`, use
+    /// `raw_packets` to check it first
+    fn packets(&self) -> Vec<Vec<u8>> {
+        match self.raw_packets() {
+            Ok(raw_packets) => crc_packets_wrapper::wrap_packets_with_crc(raw_packets),
+            Err(e) => panic!("invalid load section: {}", e),
+        }
+    }
+}
+
+/// Errors raised while reassembling a load section
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadSectionError {
+    /// A packet has a bad length header or CRC footer
+    Crc { index: usize, error: CrcError },
+    /// A section packet was expected but something else was found
+    MissingSect { index: usize },
+    /// A packet belongs to a different subtype than its section
+    SubtypeMismatch { index: usize, expected: u8, actual: u8 },
+    /// A data packet is out of sequence
+    OutOfOrder { index: usize, expected: u8, actual: u8 },
+    /// The number of data packets does not match the section packet
+    PacketCountMismatch { declared: u8, actual: usize },
+    /// The packets ended before the end-of-section packet
+    MissingEnd,
+    /// A packet does not belong to the load section
+    UnexpectedPacket { index: usize },
+    /// The payload needs more data packets than a section can count
+    TooManyPackets { count: usize },
+}
+
+impl fmt::Display for LoadSectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadSectionError::Crc { index, error } => write!(f, "packet {}: {}", index, error),
+            LoadSectionError::MissingSect { index } => write!(f, "packet {}: expected a section packet", index),
+            LoadSectionError::SubtypeMismatch { index, expected, actual } => {
+                write!(f, "packet {}: subtype {} does not match section subtype {}", index, actual, expected)
+            }
+            LoadSectionError::OutOfOrder { index, expected, actual } => {
+                write!(f, "packet {}: expected data packet {}, got {}", index, expected, actual)
+            }
+            LoadSectionError::PacketCountMismatch { declared, actual } => {
+                write!(f, "section declares {} data packets but contains {}", declared, actual)
+            }
+            LoadSectionError::MissingEnd => write!(f, "packets end before the end of the section"),
+            LoadSectionError::UnexpectedPacket { index } => write!(f, "packet {}: not part of the load section", index),
+            LoadSectionError::TooManyPackets { count } => {
+                write!(f, "payload needs {} data packets, a section holds at most {}", count, MAX_PACKET_COUNT)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LoadSectionError {}
+
+/// Extract every load section from a full packet transmission
+///
+/// Sync packets and commands outside of load sections are skipped.
+///
+/// # Arguments
+///
+/// * `packets` - CRC-wrapped packets, as produced by a protocol's `packets()`
+///
+/// # Returns
+///
+/// The load sections in transmission order, or an error describing the first
+/// malformed packet
+pub fn extract_load_sections(packets: &[Vec<u8>]) -> Result<Vec<LoadSection>, LoadSectionError> {
+    let mut raw_packets = Vec::with_capacity(packets.len());
+    for (index, packet) in packets.iter().enumerate() {
+        if crc_packets_wrapper::is_sync_packet(packet) {
+            raw_packets.push(Vec::new());
+            continue;
+        }
+        let raw_packet = crc_packets_wrapper::unwrap_packet_with_crc(packet)
+            .map_err(|error| LoadSectionError::Crc { index, error })?;
+        raw_packets.push(raw_packet);
+    }
+
+    let mut sections = Vec::new();
+    let mut index = 0;
+    while index < raw_packets.len() {
+        match raw_packets[index].first().copied().and_then(Opcode::from_byte) {
+            Some(Opcode::Clear) | Some(Opcode::Sect) => {
+                let (section, consumed) = parse_section_at(&raw_packets, index)?;
+                sections.push(section);
+                index += consumed;
+            }
+            Some(Opcode::Data) | Some(Opcode::EndSect) => {
+                return Err(LoadSectionError::MissingSect { index });
+            }
+            _ => index += 1,
+        }
+    }
+
+    Ok(sections)
+}
+
+/// Parse one load section starting at `start`, returning it with the number
+/// of packets consumed
+fn parse_section_at(packets: &[Vec<u8>], start: usize) -> Result<(LoadSection, usize), LoadSectionError> {
+    let mut index = start;
+
+    let clear = packets.get(index)
+        .is_some_and(|packet| packet.len() == 2 && packet[0] == Opcode::Clear.byte());
+    let clear_subtype = if clear {
+        index += 1;
+        Some(packets[start][1])
+    } else {
+        None
+    };
+
+    let sect_packet = packets.get(index).ok_or(LoadSectionError::MissingEnd)?;
+    if sect_packet.len() < 3 || sect_packet[0] != Opcode::Sect.byte() {
+        return Err(LoadSectionError::MissingSect { index });
+    }
+    let subtype = sect_packet[1];
+    if let Some(clear_subtype) = clear_subtype {
+        if clear_subtype != subtype {
+            return Err(LoadSectionError::SubtypeMismatch { index, expected: clear_subtype, actual: subtype });
+        }
+    }
+    let declared = sect_packet[2];
+    let header = sect_packet[3..].to_vec();
+    index += 1;
+
+    let mut payload = Vec::new();
+    let mut count = 0;
+    loop {
+        let packet = packets.get(index).ok_or(LoadSectionError::MissingEnd)?;
+        let command = packet.first().copied().and_then(Opcode::from_byte);
+
+        match command {
+            Some(Opcode::Data) | Some(Opcode::EndSect) => {
+                let actual = packet.get(1).copied().unwrap_or_default();
+                if actual != subtype {
+                    return Err(LoadSectionError::SubtypeMismatch { index, expected: subtype, actual });
+                }
+            }
+            _ => return Err(LoadSectionError::UnexpectedPacket { index }),
+        }
+
+        if command == Some(Opcode::EndSect) {
+            if packet.len() != 2 {
+                return Err(LoadSectionError::UnexpectedPacket { index });
+            }
+            index += 1;
+            break;
+        }
+
+        let expected = (count + 1) as u8;
+        let actual = packet.get(2).copied().unwrap_or_default();
+        if actual != expected {
+            return Err(LoadSectionError::OutOfOrder { index, expected, actual });
+        }
+        payload.extend_from_slice(&packet[3..]);
+        count += 1;
+        index += 1;
+    }
+
+    if count != declared as usize {
+        return Err(LoadSectionError::PacketCountMismatch { declared, actual: count });
+    }
+
+    Ok((LoadSection { subtype, clear, header, payload }, index - start))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_packets() {
+        let section = LoadSection::new(0x03, false, vec![0xd7], b"binary sound data".to_vec());
+
+        #[rustfmt::skip]
+        let expected = vec![
+            vec![0x90, 0x03, 1, 0xd7],
+            vec![0x91, 0x03, 1, 98, 105, 110, 97, 114, 121, 32, 115, 111, 117, 110, 100, 32, 100, 97, 116, 97],
+            vec![0x92, 0x03],
+        ];
+
+        assert_eq!(section.raw_packets(), Ok(expected));
+    }
+
+    #[test]
+    fn test_too_many_packets() {
+        let section = LoadSection::new(0x02, true, vec![1], vec![0; MAX_PAYLOAD_LENGTH]);
+        assert_eq!(section.raw_packets().unwrap()[1][2], 0xff);
+
+        let section = LoadSection::new(0x02, true, vec![1], vec![0; MAX_PAYLOAD_LENGTH + 1]);
+        assert_eq!(section.raw_packets(), Err(LoadSectionError::TooManyPackets { count: 0x100 }));
+    }
+
+    #[test]
+    fn test_parse_round_trip() {
+        let section = LoadSection::new(0x02, true, vec![1], (0..=255).collect());
+        let packets = section.packets();
+
+        assert_eq!(section.packet_count(), 8);
+        assert_eq!(packets.len(), 11);
+        assert_eq!(LoadSection::parse(&packets), Ok(section));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let section = LoadSection::new(0x01, true, vec![], vec![0; 40]);
+        let mut packets = section.raw_packets().unwrap();
+
+        packets.swap(2, 3);
+        assert_eq!(
+            LoadSection::parse_raw(&packets),
+            Err(LoadSectionError::OutOfOrder { index: 2, expected: 1, actual: 2 })
+        );
+
+        let mut packets = section.raw_packets().unwrap();
+        packets.pop();
+        assert_eq!(LoadSection::parse_raw(&packets), Err(LoadSectionError::MissingEnd));
+
+        let mut packets = section.raw_packets().unwrap();
+        packets[1][2] = 3;
+        assert_eq!(
+            LoadSection::parse_raw(&packets),
+            Err(LoadSectionError::PacketCountMismatch { declared: 3, actual: 2 })
+        );
+    }
+
+    #[test]
+    fn test_extract_load_sections() {
+        let eeprom = LoadSection::new(0x01, true, vec![0; 15], vec![1, 2, 3]);
+        let sound = LoadSection::new(0x03, false, vec![0xfd], vec![4, 5, 6]);
+
+        let mut packets = vec![vec![0x78, 0x55, 0xaa]];
+        packets.extend(crc_packets_wrapper::wrap_packets_with_crc(vec![vec![0x20, 0x00, 0x00, 0x03]]));
+        packets.extend(eeprom.packets());
+        packets.extend(sound.packets());
+        packets.extend(crc_packets_wrapper::wrap_packets_with_crc(vec![vec![0x21]]));
+
+        assert_eq!(extract_load_sections(&packets), Ok(vec![eeprom, sound]));
+    }
+}
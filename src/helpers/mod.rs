@@ -1,4 +1,7 @@
 //! Helper modules for Timex Datalink functionality
 
 pub mod crc_packets_wrapper;
-pub mod cpacket_paginator;
\ No newline at end of file
+pub mod cpacket_paginator;
+pub mod command_packet;
+pub mod load_section;
+pub mod appointment_time;
@@ -0,0 +1,120 @@
+//! Import module
+//!
+//! This module reads data kept by other applications (calendars, address
+//! books, to-do lists) into the watch models, so it does not have to be
+//! copied into a watch data file by hand.
+
+use std::fmt;
+
+use ical::property::Property;
+
+#[cfg(feature = "dav")]
+pub mod dav;
+pub mod ics;
+pub mod todo;
+pub mod vcard;
+
+/// Errors raised while importing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportError {
+    /// The file is not valid in its format
+    Parse { format: &'static str, message: String },
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::Parse { format, message } => write!(f, "Invalid {}: {}", format, message),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+/// An entry that was not imported
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedEntry {
+    /// Name of the entry (e.g. the event summary)
+    pub name: String,
+
+    /// Why the entry was not imported
+    pub reason: String,
+}
+
+impl fmt::Display for SkippedEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"{}\": {}", self.name, self.reason)
+    }
+}
+
+/// Shorten text to fit the watch
+///
+/// Runs of whitespace (including line breaks) become single spaces. Text
+/// that is still too long is cut at the last word boundary in its second
+/// half, or at `max_length` characters if there is none.
+///
+/// # Arguments
+///
+/// * `text` - The text to shorten
+/// * `max_length` - Maximum number of characters
+pub fn shorten(text: &str, max_length: usize) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.chars().count() <= max_length {
+        return text;
+    }
+
+    let cut: Vec<char> = text.chars().take(max_length + 1).collect();
+    let end = match cut.iter().rposition(|&c| c == ' ') {
+        Some(space) if space >= max_length / 2 => space,
+        _ => max_length,
+    };
+
+    cut[..end].iter().collect::<String>().trim_end().to_string()
+}
+
+/// Value of a property, with iCalendar/vCard text escapes (`\n`, `\,`, `\;`, `\\`) resolved
+pub(crate) fn text_value(properties: &[Property], name: &str) -> Option<String> {
+    let value = property(properties, name)?.value.as_deref()?;
+
+    let mut text = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => text.push('\n'),
+            Some(escaped) => text.push(escaped),
+            None => text.push('\\'),
+        }
+    }
+
+    Some(text)
+}
+
+/// First property with a name (names are case-insensitive)
+pub(crate) fn property<'a>(properties: &'a [Property], name: &str) -> Option<&'a Property> {
+    properties.iter().find(|property| property.name.eq_ignore_ascii_case(name))
+}
+
+/// Values of a property parameter (parameter names are case-insensitive)
+pub(crate) fn parameter<'a>(property: &'a Property, name: &str) -> Option<&'a [String]> {
+    property.params.as_ref()?
+        .iter()
+        .find(|(param, _)| param.eq_ignore_ascii_case(name))
+        .map(|(_, values)| values.as_slice())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shorten() {
+        assert_eq!(shorten("Dentist", 31), "Dentist");
+        assert_eq!(shorten("  Team\n sync\t", 31), "Team sync");
+        assert_eq!(shorten("Quarterly planning meeting with the product team", 31), "Quarterly planning meeting with");
+        assert_eq!(shorten("Supercalifragilisticexpialidocious", 10), "Supercalif");
+    }
+}
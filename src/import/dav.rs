@@ -0,0 +1,373 @@
+//! CalDAV/CardDAV sync
+//!
+//! This module fetches events, to-dos and contacts from a CalDAV/CardDAV
+//! server and runs them through the iCalendar, to-do and vCard importers.
+//! Collections are given by URL (e.g. a calendar's or address book's URL as
+//! shown by the server), there is no discovery of principals or home sets.
+//! Only built with the `dav` feature.
+
+use std::fmt;
+use std::time::Duration;
+
+use super::ics::{CalendarImport, IcsImport};
+use super::todo::{TodoImport, TodoList};
+use super::vcard::{self, ContactImport};
+use super::ImportError;
+
+/// Namespace of CalDAV elements
+const CALDAV_NAMESPACE: &str = "urn:ietf:params:xml:ns:caldav";
+
+/// Namespace of CardDAV elements
+const CARDDAV_NAMESPACE: &str = "urn:ietf:params:xml:ns:carddav";
+
+/// REPORT body asking for every object of a calendar (events and to-dos)
+const CALENDAR_QUERY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<c:calendar-query xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:prop><c:calendar-data/></d:prop>
+  <c:filter><c:comp-filter name="VCALENDAR"/></c:filter>
+</c:calendar-query>
+"#;
+
+/// REPORT body asking for every contact of an address book
+const ADDRESSBOOK_QUERY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<card:addressbook-query xmlns:d="DAV:" xmlns:card="urn:ietf:params:xml:ns:carddav">
+  <d:prop><card:address-data/></d:prop>
+</card:addressbook-query>
+"#;
+
+/// Errors raised while syncing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DavError {
+    /// The server could not be reached or the request failed
+    Http { url: String, message: String },
+
+    /// The server answered with an unexpected status
+    Status { url: String, status: u16 },
+
+    /// The answer is not a valid multistatus document
+    Xml { url: String, message: String },
+
+    /// The fetched data could not be imported
+    Import { url: String, error: ImportError },
+}
+
+impl fmt::Display for DavError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DavError::Http { url, message } => write!(f, "{}: {}", url, message),
+            DavError::Status { url, status } => write!(f, "{}: server answered with status {}", url, status),
+            DavError::Xml { url, message } => write!(f, "{}: invalid multistatus response: {}", url, message),
+            DavError::Import { url, error } => write!(f, "{}: {}", url, error),
+        }
+    }
+}
+
+impl std::error::Error for DavError {}
+
+/// Data fetched from the server
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DavData {
+    /// Appointments and anniversaries from the calendars
+    pub calendar: CalendarImport,
+
+    /// List entries from the calendars' to-dos
+    pub todos: TodoList,
+
+    /// Phone book entries from the address books
+    pub contacts: ContactImport,
+}
+
+/// Sync of calendars and address books from a CalDAV/CardDAV server
+#[derive(Debug, Clone)]
+pub struct DavSync {
+    calendars: Vec<String>,
+    addressbooks: Vec<String>,
+    credentials: Option<(String, String)>,
+    events: IcsImport,
+    todos: TodoImport,
+    timeout: Duration,
+}
+
+impl Default for DavSync {
+    fn default() -> Self {
+        DavSync {
+            calendars: Vec::new(),
+            addressbooks: Vec::new(),
+            credentials: None,
+            events: IcsImport::new(),
+            todos: TodoImport::new(),
+            timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+impl DavSync {
+    /// Create a sync without any collections
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Add a calendar collection to fetch events and to-dos from
+    pub fn calendar(mut self, url: &str) -> Self {
+        self.calendars.push(url.to_string());
+        self
+    }
+
+    /// Add an address book collection to fetch contacts from
+    pub fn addressbook(mut self, url: &str) -> Self {
+        self.addressbooks.push(url.to_string());
+        self
+    }
+
+    /// Authenticate with HTTP basic authentication
+    pub fn credentials(mut self, username: &str, password: &str) -> Self {
+        self.credentials = Some((username.to_string(), password.to_string()));
+        self
+    }
+
+    /// Set how events are imported (date range, time zone, rounding)
+    pub fn events(mut self, events: IcsImport) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Set how to-dos are imported (completed tasks, sorting)
+    pub fn todos(mut self, todos: TodoImport) -> Self {
+        self.todos = todos;
+        self
+    }
+
+    /// Set the timeout of each request
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Fetch every collection and import its data
+    ///
+    /// # Returns
+    ///
+    /// The imported models, or the first error
+    pub fn fetch(&self) -> Result<DavData, DavError> {
+        let agent: ureq::Agent = ureq::Agent::config_builder()
+            .http_status_as_error(false)
+            .allow_non_standard_methods(true)
+            .timeout_global(Some(self.timeout))
+            .build()
+            .into();
+
+        let mut calendars = String::new();
+        for url in &self.calendars {
+            calendars.push_str(&self.report(&agent, url, CALENDAR_QUERY, CALDAV_NAMESPACE, "calendar-data")?);
+        }
+        let mut addressbooks = String::new();
+        for url in &self.addressbooks {
+            addressbooks.push_str(&self.report(&agent, url, ADDRESSBOOK_QUERY, CARDDAV_NAMESPACE, "address-data")?);
+        }
+
+        let mut data = DavData::default();
+        let import_error = |urls: &[String], error| DavError::Import { url: urls.join(", "), error };
+        if !calendars.is_empty() {
+            data.calendar = self.events.import(&calendars).map_err(|e| import_error(&self.calendars, e))?;
+            data.todos = self.todos.import_vtodo(&calendars).map_err(|e| import_error(&self.calendars, e))?;
+        }
+        if !addressbooks.is_empty() {
+            data.contacts = vcard::import_vcards(&addressbooks).map_err(|e| import_error(&self.addressbooks, e))?;
+        }
+
+        Ok(data)
+    }
+
+    /// Send a REPORT and join the objects of the multistatus answer
+    fn report(&self, agent: &ureq::Agent, url: &str, query: &str, namespace: &str, element: &str) -> Result<String, DavError> {
+        let http_error = |message: String| DavError::Http { url: url.to_string(), message };
+
+        let mut request = ureq::http::Request::builder()
+            .method("REPORT")
+            .uri(url)
+            .header("Depth", "1")
+            .header("Content-Type", "application/xml; charset=utf-8");
+        if let Some((username, password)) = &self.credentials {
+            request = request.header("Authorization", format!("Basic {}", base64(&format!("{}:{}", username, password))));
+        }
+        let request = request.body(query.to_string()).map_err(|e| http_error(e.to_string()))?;
+
+        let mut response = agent.run(request).map_err(|e| http_error(e.to_string()))?;
+        let status = response.status().as_u16();
+        if status != 207 {
+            return Err(DavError::Status { url: url.to_string(), status });
+        }
+        let body = response.body_mut().read_to_string().map_err(|e| http_error(e.to_string()))?;
+
+        multistatus_objects(&body, namespace, element)
+            .map_err(|message| DavError::Xml { url: url.to_string(), message })
+    }
+}
+
+/// Join the text of every `element` in a multistatus document
+fn multistatus_objects(xml: &str, namespace: &str, element: &str) -> Result<String, String> {
+    let document = roxmltree::Document::parse(xml).map_err(|e| e.to_string())?;
+
+    let mut objects = String::new();
+    for node in document.descendants().filter(|node| node.has_tag_name((namespace, element))) {
+        for text in node.descendants().filter_map(|child| child.text().filter(|_| child.is_text())) {
+            objects.push_str(text);
+        }
+        if !objects.ends_with('\n') {
+            objects.push_str("\r\n");
+        }
+    }
+
+    Ok(objects)
+}
+
+/// Standard base64 with padding, for basic authentication
+fn base64(text: &str) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::new();
+    for chunk in text.as_bytes().chunks(3) {
+        let bytes = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let group = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(group >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+    use std::thread;
+
+    use crate::models::{PhoneEntry, PhoneType, Priority};
+
+    /// A request received by the stand-in server
+    struct Received {
+        request_line: String,
+        headers: Vec<String>,
+        body: String,
+    }
+
+    /// Serve one canned answer per path on a local port, like a CalDAV/CardDAV server would
+    fn stand_in_server(answers: Vec<(&'static str, u16, String)>) -> (String, mpsc::Receiver<Received>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let (sender, receiver) = mpsc::channel();
+
+        thread::spawn(move || {
+            for _ in 0..answers.len() {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut headers = Vec::new();
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    headers.push(line.trim().to_string());
+                }
+                let length = headers.iter()
+                    .find_map(|header| header.to_ascii_lowercase().strip_prefix("content-length:").map(|n| n.trim().parse().unwrap()))
+                    .unwrap_or(0);
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+
+                let path = request_line.split_whitespace().nth(1).unwrap_or("").to_string();
+                let (status, answer) = answers.iter()
+                    .find(|(answer_path, _, _)| *answer_path == path)
+                    .map(|(_, status, answer)| (*status, answer.clone()))
+                    .unwrap_or((404, String::new()));
+
+                let mut stream = reader.into_inner();
+                write!(
+                    stream,
+                    "HTTP/1.1 {} Answer\r\nContent-Type: application/xml; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    answer.len(),
+                    answer
+                ).unwrap();
+
+                sender.send(Received { request_line, headers, body: String::from_utf8(body).unwrap() }).unwrap();
+            }
+        });
+
+        (base, receiver)
+    }
+
+    fn multistatus(namespace: &str, element: &str, objects: &[&str]) -> String {
+        let responses: String = objects.iter()
+            .enumerate()
+            .map(|(i, object)| format!(
+                "<d:response><d:href>/{}.obj</d:href><d:propstat><d:prop><x:{}><![CDATA[{}]]></x:{}></d:prop>\
+                 <d:status>HTTP/1.1 200 OK</d:status></d:propstat></d:response>",
+                i, element, object, element
+            ))
+            .collect();
+        format!(r#"<?xml version="1.0"?><d:multistatus xmlns:d="DAV:" xmlns:x="{}">{}</d:multistatus>"#, namespace, responses)
+    }
+
+    #[test]
+    fn test_fetch() {
+        let event = "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nSUMMARY:Dentist\r\nDTSTART:20251021T160000\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+        let birthday = "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nSUMMARY:Marty\r\nDTSTART;VALUE=DATE:19680609\r\nRRULE:FREQ=YEARLY\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+        let todo = "BEGIN:VCALENDAR\r\nBEGIN:VTODO\r\nSUMMARY:Fix clock\r\nPRIORITY:1\r\nEND:VTODO\r\nEND:VCALENDAR\r\n";
+        let contact = "BEGIN:VCARD\r\nVERSION:3.0\r\nFN:Doc Brown\r\nTEL;TYPE=cell:555-1234\r\nEND:VCARD\r\n";
+
+        let (base, received) = stand_in_server(vec![
+            ("/calendars/doc/home/", 207, multistatus(CALDAV_NAMESPACE, "calendar-data", &[event, birthday, todo])),
+            ("/addressbooks/doc/contacts/", 207, multistatus(CARDDAV_NAMESPACE, "address-data", &[contact])),
+        ]);
+
+        let data = DavSync::new()
+            .calendar(&format!("{}/calendars/doc/home/", base))
+            .addressbook(&format!("{}/addressbooks/doc/contacts/", base))
+            .credentials("doc", "88mph")
+            .fetch()
+            .unwrap();
+
+        assert_eq!(data.calendar.appointments.len(), 1);
+        assert_eq!(data.calendar.appointments[0].message, "Dentist");
+        assert_eq!(data.calendar.anniversaries.len(), 1);
+        assert_eq!(data.todos.entries[0].priority, Some(Priority::One));
+        assert_eq!(data.contacts.phone_numbers, vec![
+            PhoneEntry::new("Doc Brown".to_string(), "5551234".to_string(), PhoneType::Cell),
+        ]);
+
+        let request = received.recv().unwrap();
+        assert_eq!(request.request_line.trim(), "REPORT /calendars/doc/home/ HTTP/1.1");
+        assert!(request.headers.iter().any(|header| header.eq_ignore_ascii_case("depth: 1")));
+        assert!(request.headers.iter().any(|header| header.eq_ignore_ascii_case("authorization: Basic ZG9jOjg4bXBo")));
+        assert!(request.body.contains("calendar-query"));
+        assert!(received.recv().unwrap().body.contains("addressbook-query"));
+    }
+
+    #[test]
+    fn test_fetch_errors() {
+        let (base, _received) = stand_in_server(vec![("/private/", 401, String::new())]);
+
+        let error = DavSync::new().calendar(&format!("{}/private/", base)).fetch().unwrap_err();
+        assert_eq!(error, DavError::Status { url: format!("{}/private/", base), status: 401 });
+    }
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64("doc:88mph"), "ZG9jOjg4bXBo");
+        assert_eq!(base64("a"), "YQ==");
+        assert_eq!(base64("ab"), "YWI=");
+    }
+}
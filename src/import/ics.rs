@@ -0,0 +1,288 @@
+//! iCalendar import
+//!
+//! This module reads the VEVENTs of an `.ics` file. Single events become
+//! appointments, quantized into the watch's 15-minute slots, and events
+//! repeating every year (birthdays, anniversaries) become anniversaries.
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use ical::parser::ical::component::IcalEvent;
+use ical::IcalParser;
+
+use super::{parameter, property, shorten, text_value, ImportError, SkippedEntry};
+use crate::char_encoders::EEPROM_TEXT_LENGTH;
+use crate::helpers::appointment_time::{self, QuantizationWarning, RoundingPolicy};
+use crate::models::{Anniversary, Appointment};
+
+/// Appointments and anniversaries read from a calendar
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CalendarImport {
+    /// Appointments in the date range, ordered by time
+    pub appointments: Vec<Appointment>,
+
+    /// Anniversaries, in the order of the calendar
+    pub anniversaries: Vec<Anniversary>,
+
+    /// Appointments moved to a different time by quantization
+    pub quantized: Vec<QuantizationWarning>,
+
+    /// Events that were not imported
+    pub skipped: Vec<SkippedEntry>,
+}
+
+/// Start of an event
+enum EventStart {
+    /// All-day event
+    Date(NaiveDate),
+    /// Wall-clock time in the watch's time zone
+    DateTime(NaiveDateTime),
+}
+
+impl EventStart {
+    fn date(&self) -> NaiveDate {
+        match self {
+            EventStart::Date(date) => *date,
+            EventStart::DateTime(time) => time.date(),
+        }
+    }
+
+    fn time(&self) -> NaiveDateTime {
+        match self {
+            EventStart::Date(date) => date.and_hms_opt(0, 0, 0).unwrap(),
+            EventStart::DateTime(time) => *time,
+        }
+    }
+}
+
+/// Importer for `.ics` files
+#[derive(Debug, Clone, Default)]
+pub struct IcsImport {
+    from: Option<NaiveDate>,
+    until: Option<NaiveDate>,
+    zone: Option<Tz>,
+    rounding: RoundingPolicy,
+}
+
+impl IcsImport {
+    /// Create an importer for every event, shown in the local time zone
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Only import appointments shown from `from` to `until` (both inclusive)
+    ///
+    /// Anniversaries repeat every year and are always imported.
+    pub fn date_range(mut self, from: Option<NaiveDate>, until: Option<NaiveDate>) -> Self {
+        self.from = from;
+        self.until = until;
+        self
+    }
+
+    /// Set the time zone the watch shows appointments in
+    pub fn time_zone(mut self, zone: Tz) -> Self {
+        self.zone = Some(zone);
+        self
+    }
+
+    /// Set how appointment times are rounded into 15-minute slots
+    pub fn rounding(mut self, rounding: RoundingPolicy) -> Self {
+        self.rounding = rounding;
+        self
+    }
+
+    /// Import the events of a calendar
+    ///
+    /// # Arguments
+    ///
+    /// * `ics` - Contents of an `.ics` file, which may hold several calendars
+    ///
+    /// # Returns
+    ///
+    /// The imported appointments and anniversaries, or an error if the file
+    /// is not valid iCalendar
+    pub fn import(&self, ics: &str) -> Result<CalendarImport, ImportError> {
+        let mut import = CalendarImport::default();
+
+        for calendar in IcalParser::new(ics.as_bytes()) {
+            let calendar = calendar.map_err(|e| ImportError::Parse { format: "iCalendar", message: e.to_string() })?;
+            for event in &calendar.events {
+                self.import_event(event, &mut import);
+            }
+        }
+
+        import.appointments.sort_by_key(|appointment| appointment.time);
+        for (index, appointment) in import.appointments.iter_mut().enumerate() {
+            let requested = appointment.time;
+            appointment.time = appointment_time::quantize(requested, self.rounding);
+            import.quantized.extend(appointment_time::quantization_warning(
+                index,
+                &appointment.message,
+                requested,
+                self.rounding,
+            ));
+        }
+
+        Ok(import)
+    }
+
+    fn import_event(&self, event: &IcalEvent, import: &mut CalendarImport) {
+        let summary = text_value(&event.properties, "SUMMARY").unwrap_or_default();
+        let mut skip = |reason: String| import.skipped.push(SkippedEntry { name: summary.clone(), reason });
+
+        if text_value(&event.properties, "STATUS").is_some_and(|status| status.eq_ignore_ascii_case("CANCELLED")) {
+            return skip("cancelled".to_string());
+        }
+        let start = match self.start(event) {
+            Ok(start) => start,
+            Err(reason) => return skip(reason),
+        };
+        let message = shorten(&summary, EEPROM_TEXT_LENGTH);
+
+        if let Some(rule) = text_value(&event.properties, "RRULE") {
+            let frequency = rule.split(';')
+                .find_map(|part| part.strip_prefix("FREQ="))
+                .unwrap_or("")
+                .to_ascii_uppercase();
+
+            if frequency == "YEARLY" {
+                import.anniversaries.push(Anniversary::from_date(start.date(), message));
+            } else {
+                skip(format!("repeats {}, only yearly events are imported", frequency.to_lowercase()));
+            }
+            return;
+        }
+
+        let time = start.time();
+        let shown = appointment_time::quantize(time, self.rounding).date();
+        if self.from.is_some_and(|from| shown < from) || self.until.is_some_and(|until| shown > until) {
+            return;
+        }
+
+        import.appointments.push(Appointment { time, message, rounding: self.rounding });
+    }
+
+    /// Read DTSTART as a date or a wall-clock time in the watch's time zone
+    fn start(&self, event: &IcalEvent) -> Result<EventStart, String> {
+        let start = property(&event.properties, "DTSTART").ok_or("no start time")?;
+        let value = start.value.as_deref().unwrap_or("").trim();
+        let unsupported = || format!("unsupported start time \"{}\"", value);
+
+        if value.len() == 8 {
+            return NaiveDate::parse_from_str(value, "%Y%m%d").map(EventStart::Date).map_err(|_| unsupported());
+        }
+
+        let (local, utc) = match value.strip_suffix('Z') {
+            Some(local) => (local, true),
+            None => (value, false),
+        };
+        let time = NaiveDateTime::parse_from_str(local, "%Y%m%dT%H%M%S").map_err(|_| unsupported())?;
+
+        let instant = if utc {
+            Utc.from_utc_datetime(&time)
+        } else {
+            // Floating times, and times in zones chrono-tz does not know, are wall-clock times
+            let zone = parameter(start, "TZID")
+                .and_then(|values| values.first())
+                .and_then(|name| name.trim_matches('"').parse::<Tz>().ok());
+            match zone.and_then(|zone| zone.from_local_datetime(&time).earliest()) {
+                Some(zoned) => zoned.with_timezone(&Utc),
+                None => return Ok(EventStart::DateTime(time)),
+            }
+        };
+
+        Ok(EventStart::DateTime(self.wall_clock(&instant)))
+    }
+
+    fn wall_clock(&self, instant: &DateTime<Utc>) -> NaiveDateTime {
+        match self.zone {
+            Some(zone) => appointment_time::wall_clock(&instant.with_timezone(&zone)),
+            None => appointment_time::wall_clock(&instant.with_timezone(&Local)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CALENDAR: &str = "BEGIN:VCALENDAR\r
+VERSION:2.0\r
+PRODID:-//Test//EN\r
+BEGIN:VEVENT\r
+UID:1\r
+SUMMARY:Dentist\\, Dr. Brown\r
+DTSTART;TZID=America/Los_Angeles:20251021T160700\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:2\r
+SUMMARY:Quarterly planning meeting with the product team\r
+DTSTART:20251020T150000Z\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:3\r
+SUMMARY:Marty's birthday\r
+DTSTART;VALUE=DATE:19680609\r
+RRULE:FREQ=YEARLY\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:4\r
+SUMMARY:Standup\r
+DTSTART:20251020T091500\r
+RRULE:FREQ=WEEKLY;BYDAY=MO\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:5\r
+SUMMARY:Cancelled lunch\r
+DTSTART:20251022T120000\r
+STATUS:CANCELLED\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:6\r
+SUMMARY:Next year\r
+DTSTART;VALUE=DATE:20260101\r
+END:VEVENT\r
+END:VCALENDAR\r
+";
+
+    fn datetime(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap().and_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_import_calendar() {
+        let import = IcsImport::new()
+            .time_zone(chrono_tz::Europe::Madrid)
+            .date_range(None, NaiveDate::from_ymd_opt(2025, 12, 31))
+            .import(CALENDAR)
+            .unwrap();
+
+        let appointments: Vec<(NaiveDateTime, &str)> = import.appointments.iter()
+            .map(|appointment| (appointment.time, appointment.message.as_str()))
+            .collect();
+        assert_eq!(appointments, vec![
+            (datetime(2025, 10, 20, 17, 0), "Quarterly planning meeting with"),
+            (datetime(2025, 10, 22, 1, 0), "Dentist, Dr. Brown"),
+        ]);
+
+        assert_eq!(import.anniversaries, vec![Anniversary::new(6, 9, "Marty's birthday".to_string()).unwrap()]);
+
+        assert_eq!(import.quantized.len(), 1);
+        assert_eq!(import.quantized[0].requested, datetime(2025, 10, 22, 1, 7));
+
+        let skipped: Vec<String> = import.skipped.iter().map(|entry| entry.to_string()).collect();
+        assert_eq!(skipped, vec![
+            "\"Standup\": repeats weekly, only yearly events are imported",
+            "\"Cancelled lunch\": cancelled",
+        ]);
+    }
+
+    #[test]
+    fn test_import_errors() {
+        assert!(IcsImport::new().import("BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\n").is_err());
+
+        let import = IcsImport::new()
+            .import("BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nSUMMARY:Someday\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n")
+            .unwrap();
+        assert_eq!(import.skipped[0].reason, "no start time");
+    }
+}
@@ -0,0 +1,373 @@
+//! To-do list import
+//!
+//! This module reads to-do lists from todo.txt files, CSV exports and
+//! iCalendar VTODOs into list entries. Priorities are mapped onto the watch's
+//! 1-5 scale (5 highest), completed tasks are left out unless asked for, and
+//! entries can be sorted with the most important first.
+
+use std::path::Path;
+
+use ical::parser::ical::component::IcalTodo;
+use ical::IcalParser;
+
+use super::{property, shorten, text_value, ImportError, SkippedEntry};
+use crate::char_encoders::EEPROM_TEXT_LENGTH;
+use crate::models::{ListEntry, Priority};
+
+/// File formats to-do lists can be imported from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TodoFormat {
+    /// todo.txt, one task per line
+    TodoTxt,
+    /// CSV with a header row
+    Csv,
+    /// iCalendar VTODOs
+    Ical,
+}
+
+impl TodoFormat {
+    /// Pick the format from a file extension (`.txt`, `.csv` or `.ics`)
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+
+        match extension.as_str() {
+            "txt" => Some(TodoFormat::TodoTxt),
+            "csv" => Some(TodoFormat::Csv),
+            "ics" | "ical" => Some(TodoFormat::Ical),
+            _ => None,
+        }
+    }
+}
+
+/// List entries read from a to-do list
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TodoList {
+    /// List entries
+    pub entries: Vec<ListEntry>,
+
+    /// Number of completed tasks that were left out
+    pub completed: usize,
+
+    /// Tasks that were not imported
+    pub skipped: Vec<SkippedEntry>,
+}
+
+/// A task before filtering and sorting
+struct Task {
+    text: String,
+    priority: Option<Priority>,
+    completed: bool,
+}
+
+/// Importer for to-do lists
+#[derive(Debug, Clone)]
+pub struct TodoImport {
+    include_completed: bool,
+    sort_by_priority: bool,
+}
+
+impl Default for TodoImport {
+    fn default() -> Self {
+        TodoImport { include_completed: false, sort_by_priority: true }
+    }
+}
+
+impl TodoImport {
+    /// Create an importer that skips completed tasks and sorts by priority
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Set whether completed tasks are imported
+    pub fn include_completed(mut self, include_completed: bool) -> Self {
+        self.include_completed = include_completed;
+        self
+    }
+
+    /// Set whether entries are sorted by priority (1 first, entries without
+    /// a priority last) or kept in the order of the file
+    pub fn sort_by_priority(mut self, sort_by_priority: bool) -> Self {
+        self.sort_by_priority = sort_by_priority;
+        self
+    }
+
+    /// Import a to-do list in the given format
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - Contents of the to-do list file
+    /// * `format` - Format of the file
+    pub fn import(&self, text: &str, format: TodoFormat) -> Result<TodoList, ImportError> {
+        match format {
+            TodoFormat::TodoTxt => Ok(self.import_todo_txt(text)),
+            TodoFormat::Csv => self.import_csv(text),
+            TodoFormat::Ical => self.import_vtodo(text),
+        }
+    }
+
+    /// Import a todo.txt file
+    ///
+    /// Priorities `(A)` to `(E)` become 1 to 5, later letters give no
+    /// priority. Lines starting with `x ` are completed tasks. Dates and
+    /// `key:value` tags are dropped, projects and contexts are kept.
+    pub fn import_todo_txt(&self, text: &str) -> TodoList {
+        let tasks = text.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(parse_todo_txt_line)
+            .map(Ok)
+            .collect();
+
+        self.finish(tasks)
+    }
+
+    /// Import a CSV file with a header row
+    ///
+    /// The task text is read from the first column named "task", "title",
+    /// "entry", "name", "subject" or "description". An optional "priority"
+    /// column holds 1-5, A-E or high/medium/low, and an optional "done",
+    /// "completed" or "status" column marks completed tasks.
+    pub fn import_csv(&self, text: &str) -> Result<TodoList, ImportError> {
+        let parse_error = |e: csv::Error| ImportError::Parse { format: "CSV", message: e.to_string() };
+
+        let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(text.as_bytes());
+        let headers: Vec<String> = reader.headers().map_err(parse_error)?
+            .iter()
+            .map(|header| header.trim().to_ascii_lowercase())
+            .collect();
+        let column = |names: &[&str]| headers.iter().position(|header| names.contains(&header.as_str()));
+
+        let text_column = column(&["task", "title", "entry", "name", "subject", "description"]).ok_or_else(|| {
+            ImportError::Parse { format: "CSV", message: "no task, title or entry column".to_string() }
+        })?;
+        let priority_column = column(&["priority"]);
+        let done_column = column(&["done", "completed", "status"]);
+
+        let mut tasks = Vec::new();
+        for record in reader.records() {
+            let record = record.map_err(parse_error)?;
+            let field = |column: Option<usize>| column.and_then(|column| record.get(column)).unwrap_or("").trim();
+
+            let text = field(Some(text_column)).to_string();
+            let priority = field(priority_column);
+            tasks.push(match csv_priority(priority) {
+                Ok(priority) => Ok(Task { text, priority, completed: is_done(field(done_column)) }),
+                Err(()) => Err(SkippedEntry { name: text, reason: format!("unknown priority \"{}\"", priority) }),
+            });
+        }
+
+        Ok(self.finish(tasks))
+    }
+
+    /// Import the VTODOs of an iCalendar file
+    ///
+    /// iCalendar priorities 1-9 (1 highest) are mapped onto 1-5. Tasks with
+    /// STATUS:COMPLETED or a COMPLETED time are completed, cancelled tasks
+    /// are skipped.
+    pub fn import_vtodo(&self, ics: &str) -> Result<TodoList, ImportError> {
+        let mut tasks = Vec::new();
+
+        for calendar in IcalParser::new(ics.as_bytes()) {
+            let calendar = calendar.map_err(|e| ImportError::Parse { format: "iCalendar", message: e.to_string() })?;
+            tasks.extend(calendar.todos.iter().map(vtodo_task));
+        }
+
+        Ok(self.finish(tasks))
+    }
+
+    /// Filter, sort and shorten tasks into list entries
+    fn finish(&self, tasks: Vec<Result<Task, SkippedEntry>>) -> TodoList {
+        let mut list = TodoList::default();
+        let mut kept = Vec::new();
+
+        for task in tasks {
+            match task {
+                Ok(task) if task.text.is_empty() => list.skipped.push(SkippedEntry {
+                    name: String::new(),
+                    reason: "no text".to_string(),
+                }),
+                Ok(task) if task.completed && !self.include_completed => list.completed += 1,
+                Ok(task) => kept.push(task),
+                Err(skipped) => list.skipped.push(skipped),
+            }
+        }
+
+        if self.sort_by_priority {
+            kept.sort_by_key(|task| task.priority.map_or(u8::MAX, Priority::value));
+        }
+
+        list.entries = kept.into_iter()
+            .map(|task| ListEntry::new(shorten(&task.text, EEPROM_TEXT_LENGTH), task.priority))
+            .collect();
+        list
+    }
+}
+
+/// Priority of a letter, (A) is 1
+fn letter_priority(letter: char) -> Option<Priority> {
+    match letter.to_ascii_uppercase() {
+        'A' => Some(Priority::One),
+        'B' => Some(Priority::Two),
+        'C' => Some(Priority::Three),
+        'D' => Some(Priority::Four),
+        'E' => Some(Priority::Five),
+        _ => None,
+    }
+}
+
+fn parse_todo_txt_line(line: &str) -> Task {
+    let mut rest = line.trim();
+
+    let completed = rest.starts_with("x ");
+    if completed {
+        rest = &rest[2..];
+    }
+
+    let mut priority = None;
+    let bytes = rest.as_bytes();
+    if bytes.len() >= 4 && bytes[0] == b'(' && bytes[1].is_ascii_uppercase() && bytes[2] == b')' && bytes[3] == b' ' {
+        priority = letter_priority(bytes[1] as char);
+        rest = &rest[4..];
+    }
+
+    let is_date = |word: &str| chrono::NaiveDate::parse_from_str(word, "%Y-%m-%d").is_ok();
+    let is_tag = |word: &str| {
+        word.split_once(':').is_some_and(|(key, value)| !key.is_empty() && !value.is_empty() && !value.starts_with('/'))
+    };
+
+    let words: Vec<&str> = rest.split_whitespace().collect();
+    // Completion and creation dates come before the description
+    let start = words.iter().take(2).take_while(|word| is_date(word)).count();
+    let text = words[start..].iter()
+        .filter(|word| !is_tag(word))
+        .copied()
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    Task { text, priority, completed }
+}
+
+/// Priority from a CSV column: 1-5, A-E or high/medium/low
+fn csv_priority(value: &str) -> Result<Option<Priority>, ()> {
+    let value = value.trim_matches(|c| c == '(' || c == ')');
+
+    if value.is_empty() || value == "0" {
+        return Ok(None);
+    }
+    if let Ok(level) = value.parse::<u8>() {
+        return Priority::from_value(level).map(Some).ok_or(());
+    }
+
+    match value.to_ascii_lowercase().as_str() {
+        "high" => Ok(Some(Priority::One)),
+        "medium" | "normal" => Ok(Some(Priority::Three)),
+        "low" => Ok(Some(Priority::Five)),
+        letter if letter.len() == 1 => letter_priority(letter.chars().next().unwrap()).map(Some).ok_or(()),
+        _ => Err(()),
+    }
+}
+
+fn is_done(value: &str) -> bool {
+    matches!(
+        value.to_ascii_lowercase().as_str(),
+        "x" | "1" | "y" | "yes" | "true" | "done" | "completed" | "complete"
+    )
+}
+
+fn vtodo_task(todo: &IcalTodo) -> Result<Task, SkippedEntry> {
+    let text = text_value(&todo.properties, "SUMMARY").unwrap_or_default();
+    let status = text_value(&todo.properties, "STATUS").unwrap_or_default().to_ascii_uppercase();
+
+    if status == "CANCELLED" {
+        return Err(SkippedEntry { name: text, reason: "cancelled".to_string() });
+    }
+
+    // RFC 5545: 0 is undefined, 1 is the highest and 9 the lowest
+    let priority = text_value(&todo.properties, "PRIORITY")
+        .and_then(|priority| priority.trim().parse::<u8>().ok())
+        .and_then(|priority| match priority {
+            1 | 2 => Some(Priority::One),
+            3 | 4 => Some(Priority::Two),
+            5 => Some(Priority::Three),
+            6 | 7 => Some(Priority::Four),
+            8 | 9 => Some(Priority::Five),
+            _ => None,
+        });
+    let completed = status == "COMPLETED" || property(&todo.properties, "COMPLETED").is_some();
+
+    Ok(Task { text, priority, completed })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(list: &TodoList) -> Vec<(&str, Option<u8>)> {
+        list.entries.iter()
+            .map(|entry| (entry.entry.as_str(), entry.priority.map(Priority::value)))
+            .collect()
+    }
+
+    #[test]
+    fn test_import_todo_txt() {
+        let text = "\
+(B) 2025-10-01 Call Doc about the flux capacitor +lab due:2025-10-20
+x 2025-10-02 2025-09-30 Buy plutonium
+Pick up dry cleaning @town
+(A) Fix the clock tower wiring
+(F) Someday maybe
+";
+        let list = TodoImport::new().import_todo_txt(text);
+
+        assert_eq!(entries(&list), vec![
+            ("Fix the clock tower wiring", Some(1)),
+            ("Call Doc about the flux", Some(2)),
+            ("Pick up dry cleaning @town", None),
+            ("Someday maybe", None),
+        ]);
+        assert_eq!(list.completed, 1);
+
+        let list = TodoImport::new().include_completed(true).sort_by_priority(false).import_todo_txt(text);
+        assert_eq!(list.entries[1].entry, "Buy plutonium");
+        assert_eq!(list.entries[3].entry, "Fix the clock tower wiring");
+    }
+
+    #[test]
+    fn test_import_csv() {
+        let csv = "Title,Priority,Done\nMilk,low,\n\"Eggs, a dozen\",high,no\nBread,,yes\nJam,urgent,\n";
+        let list = TodoImport::new().import(csv, TodoFormat::Csv).unwrap();
+
+        assert_eq!(entries(&list), vec![("Eggs, a dozen", Some(1)), ("Milk", Some(5))]);
+        assert_eq!(list.completed, 1);
+        assert_eq!(list.skipped[0].to_string(), "\"Jam\": unknown priority \"urgent\"");
+
+        assert!(TodoImport::new().import_csv("Priority\n1\n").is_err());
+    }
+
+    #[test]
+    fn test_import_vtodo() {
+        let ics = "BEGIN:VCALENDAR\r
+BEGIN:VTODO\r
+SUMMARY:File taxes\r
+PRIORITY:1\r
+END:VTODO\r
+BEGIN:VTODO\r
+SUMMARY:Water plants\r
+PRIORITY:9\r
+END:VTODO\r
+BEGIN:VTODO\r
+SUMMARY:Renew passport\r
+STATUS:COMPLETED\r
+END:VTODO\r
+BEGIN:VTODO\r
+SUMMARY:Book hotel\r
+STATUS:CANCELLED\r
+END:VTODO\r
+END:VCALENDAR\r
+";
+        let list = TodoImport::new().import(ics, TodoFormat::Ical).unwrap();
+
+        assert_eq!(entries(&list), vec![("File taxes", Some(1)), ("Water plants", Some(5))]);
+        assert_eq!(list.completed, 1);
+        assert_eq!(list.skipped[0].reason, "cancelled");
+    }
+}
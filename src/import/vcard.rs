@@ -0,0 +1,221 @@
+//! vCard import
+//!
+//! This module reads contacts from vCard 3.0 and 4.0 files. Every TEL
+//! of a contact becomes a phone book entry, typed from its TYPE parameters
+//! and normalized to the characters the watch can show.
+
+use ical::parser::vcard::component::VcardContact;
+use ical::VcardParser;
+
+use super::{parameter, shorten, text_value, ImportError, SkippedEntry};
+use crate::char_encoders::{EEPROM_TEXT_LENGTH, PHONE_CHARS, PHONE_FIELD_LENGTH, PHONE_NUMBER_LENGTH};
+use crate::models::{PhoneEntry, PhoneType};
+
+/// Phone book entries read from contacts
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ContactImport {
+    /// Phone book entries, in the order of the file
+    pub phone_numbers: Vec<PhoneEntry>,
+
+    /// Contacts and numbers that were not imported
+    pub skipped: Vec<SkippedEntry>,
+}
+
+/// Pick the phone type from TYPE labels such as "cell", "HOME" or "work,voice"
+///
+/// Cell phones win over work and home phones when a number has several
+/// labels. Labels the watch cannot show (fax, pager, ...) give `Other`.
+pub fn phone_type_from_labels<S: AsRef<str>>(labels: &[S]) -> PhoneType {
+    let labels: Vec<String> = labels.iter()
+        .flat_map(|label| label.as_ref().split(','))
+        .map(|label| label.trim().trim_matches('"').to_ascii_lowercase())
+        .collect();
+    let has = |names: &[&str]| labels.iter().any(|label| names.contains(&label.as_str()));
+
+    if has(&["cell", "mobile", "iphone"]) {
+        PhoneType::Cell
+    } else if has(&["work"]) {
+        PhoneType::Work
+    } else if has(&["home"]) {
+        PhoneType::Home
+    } else {
+        PhoneType::Other
+    }
+}
+
+/// Normalize a phone number to the watch's phone characters (`0123456789cfhpw `)
+///
+/// Separators such as `+`, `-`, `(`, `)`, `.` and spaces are dropped, a
+/// `tel:` URI prefix is removed, pauses (`,`) become `p` and extensions
+/// (`x`, `ext`, `;ext=`) become a wait (`w`).
+pub fn normalize_phone_number(number: &str) -> String {
+    let number = number.trim().to_ascii_lowercase();
+    let number = number.strip_prefix("tel:").unwrap_or(&number);
+    let number = number.replace(";ext=", "w").replace("ext.", "w").replace("ext", "w");
+
+    number.chars()
+        .filter_map(|c| match c {
+            ',' => Some('p'),
+            'x' => Some('w'),
+            ' ' => None,
+            c if PHONE_CHARS.contains(c) => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Build a phone book entry from a contact's name, number and type labels
+///
+/// # Arguments
+///
+/// * `name` - Contact name, shortened to 31 characters
+/// * `number` - Phone number as written in the address book
+/// * `labels` - TYPE labels of the number
+///
+/// # Returns
+///
+/// The entry, or why the number does not fit the watch
+pub fn contact_phone_entry<S: AsRef<str>>(name: &str, number: &str, labels: &[S]) -> Result<PhoneEntry, String> {
+    let normalized = normalize_phone_number(number);
+
+    if normalized.is_empty() {
+        return Err(format!("number \"{}\" has no digits", number));
+    }
+    if normalized.chars().count() > PHONE_NUMBER_LENGTH {
+        return Err(format!(
+            "number {} is {} characters, only {} fit before the type in the {}-character phone field",
+            normalized,
+            normalized.chars().count(),
+            PHONE_NUMBER_LENGTH,
+            PHONE_FIELD_LENGTH
+        ));
+    }
+
+    Ok(PhoneEntry::new(shorten(name, EEPROM_TEXT_LENGTH), normalized, phone_type_from_labels(labels)))
+}
+
+/// Import the phone numbers of every contact in a vCard file
+///
+/// # Arguments
+///
+/// * `vcf` - Contents of a `.vcf` file, which may hold many contacts
+///
+/// # Returns
+///
+/// The imported phone book entries, or an error if the file is not valid vCard
+pub fn import_vcards(vcf: &str) -> Result<ContactImport, ImportError> {
+    let mut import = ContactImport::default();
+
+    for contact in VcardParser::new(vcf.as_bytes()) {
+        let contact = contact.map_err(|e| ImportError::Parse { format: "vCard", message: e.to_string() })?;
+        import_contact(&contact, &mut import);
+    }
+
+    Ok(import)
+}
+
+fn import_contact(contact: &VcardContact, import: &mut ContactImport) {
+    let name = contact_name(contact);
+    let skip = |import: &mut ContactImport, reason: String| {
+        import.skipped.push(SkippedEntry { name: name.clone().unwrap_or_default(), reason })
+    };
+
+    let name = match &name {
+        Some(name) => name,
+        None => return skip(import, "no name".to_string()),
+    };
+
+    let numbers: Vec<_> = contact.properties.iter()
+        .filter(|property| property.name.eq_ignore_ascii_case("TEL"))
+        .collect();
+    if numbers.is_empty() {
+        return skip(import, "no phone number".to_string());
+    }
+
+    for number in numbers {
+        let labels = parameter(number, "TYPE").unwrap_or_default();
+
+        match contact_phone_entry(name, number.value.as_deref().unwrap_or(""), labels) {
+            Ok(entry) => import.phone_numbers.push(entry),
+            Err(reason) => skip(import, reason),
+        }
+    }
+}
+
+/// Formatted name (FN), or the structured name (N) as "given family"
+fn contact_name(contact: &VcardContact) -> Option<String> {
+    if let Some(name) = text_value(&contact.properties, "FN").filter(|name| !name.trim().is_empty()) {
+        return Some(name);
+    }
+
+    let name = text_value(&contact.properties, "N")?;
+    let parts: Vec<&str> = name.split(';').collect();
+    let full = [parts.get(1), parts.first()].into_iter()
+        .flatten()
+        .filter(|part| !part.is_empty())
+        .copied()
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    (!full.is_empty()).then_some(full)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_phone_number() {
+        assert_eq!(normalize_phone_number("(555) 123-4567"), "5551234567");
+        assert_eq!(normalize_phone_number("tel:+1-555-123"), "1555123");
+        assert_eq!(normalize_phone_number("555.1234 x12"), "5551234w12");
+        assert_eq!(normalize_phone_number("555-1234,,9"), "5551234pp9");
+    }
+
+    #[test]
+    fn test_phone_type_from_labels() {
+        assert_eq!(phone_type_from_labels(&["HOME", "VOICE"]), PhoneType::Home);
+        assert_eq!(phone_type_from_labels(&["\"work,cell\""]), PhoneType::Cell);
+        assert_eq!(phone_type_from_labels(&["fax"]), PhoneType::Other);
+        assert_eq!(phone_type_from_labels::<&str>(&[]), PhoneType::Other);
+    }
+
+    #[test]
+    fn test_import_vcards() {
+        let vcf = "BEGIN:VCARD\r
+VERSION:3.0\r
+FN:Emmett Brown\r
+TEL;TYPE=CELL,VOICE:(555) 123-4567\r
+TEL;TYPE=HOME:+1 (555) 987-6543 x12\r
+END:VCARD\r
+BEGIN:VCARD\r
+VERSION:4.0\r
+N:McFly;Marty;;;\r
+TEL;VALUE=uri;TYPE=\"work,voice\":tel:+1-555-0100\r
+END:VCARD\r
+BEGIN:VCARD\r
+VERSION:3.0\r
+FN:Biff\r
+TEL;TYPE=home:555-0199\r
+END:VCARD\r
+BEGIN:VCARD\r
+VERSION:3.0\r
+FN:Lorraine\r
+EMAIL:lorraine@example.com\r
+END:VCARD\r
+";
+        let import = import_vcards(vcf).unwrap();
+
+        assert_eq!(import.phone_numbers, vec![
+            PhoneEntry::new("Emmett Brown".to_string(), "5551234567".to_string(), PhoneType::Cell),
+            PhoneEntry::new("Marty McFly".to_string(), "15550100".to_string(), PhoneType::Work),
+            PhoneEntry::new("Biff".to_string(), "5550199".to_string(), PhoneType::Home),
+        ]);
+
+        let skipped: Vec<String> = import.skipped.iter().map(|entry| entry.to_string()).collect();
+        assert_eq!(skipped, vec![
+            "\"Emmett Brown\": number 15559876543w12 is 14 characters, only 10 fit before the type in the 12-character phone field",
+            "\"Lorraine\": no phone number",
+        ]);
+    }
+}
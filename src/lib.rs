@@ -21,6 +21,16 @@ pub mod char_encoders;
 pub mod notebook_adapter;
 pub mod oneplus6_led_adapter;
 pub mod devices;
+pub mod packet_annotator;
+pub mod models;
+pub mod watch_config;
+pub mod sync_snapshot;
+pub mod transfer_estimate;
+pub mod transmission_schedule;
+pub mod spc;
+pub mod zap;
+pub mod web_config;
+pub mod import;
 
 #[cfg(target_arch = "wasm32")]
 mod lib_wasm;
@@ -30,5 +40,6 @@ pub use lib_wasm::*;
 
 pub use protocol_3::Protocol3;
 pub use protocol_4::Protocol4;
+pub use watch_config::WatchConfig;
 pub use notebook_adapter::NotebookAdapter;
 pub use oneplus6_led_adapter::OnePlus6LedAdapter;
\ No newline at end of file
@@ -0,0 +1,487 @@
+//! Protocol-neutral models
+//!
+//! This module describes watch data independently of the protocol used to
+//! transmit it. Protocol 3 and Protocol 4 convert these models into their own
+//! packet models with `From` (or `TryFrom` where a model can hold values the
+//! watch cannot show), so the data only has to be described once:
+//!
+//! ```
+//! use timex_datalink::models;
+//! use timex_datalink::{protocol_3, protocol_4, PacketGenerator};
+//!
+//! let alarm = models::Alarm::new(1, 9, 0, "Wake up".to_string());
+//!
+//! assert_eq!(
+//!     protocol_3::Alarm::try_from(&alarm).unwrap().packets(),
+//!     protocol_4::Alarm::try_from(&alarm).unwrap().packets()
+//! );
+//! ```
+
+use std::fmt;
+use std::time::SystemTime;
+
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime};
+
+pub use crate::helpers::appointment_time::RoundingPolicy;
+
+/// Leap year used to validate month/day pairs, so February 29 is accepted
+const LEAP_YEAR: i32 = 2000;
+
+/// Error returned when a month/day pair is not a valid calendar day
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidAnniversaryDate {
+    /// Month that was given
+    pub month: u8,
+
+    /// Day that was given
+    pub day: u8,
+}
+
+impl fmt::Display for InvalidAnniversaryDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid anniversary date: month {}, day {}", self.month, self.day)
+    }
+}
+
+impl std::error::Error for InvalidAnniversaryDate {}
+
+/// Error returned when an alarm hour or minute is not a valid time of day
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidAlarmTime {
+    /// Hour that was given
+    pub hour: u8,
+
+    /// Minute that was given
+    pub minute: u8,
+}
+
+impl fmt::Display for InvalidAlarmTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid alarm time: {:02}:{:02}", self.hour, self.minute)
+    }
+}
+
+impl std::error::Error for InvalidAlarmTime {}
+
+/// Check that a month/day pair exists in some year
+///
+/// # Arguments
+///
+/// * `month` - Month (1-12)
+/// * `day` - Day of the month (1-31)
+///
+/// # Returns
+///
+/// The pair, or an error if the day does not exist even in a leap year
+pub fn validate_month_day(month: u8, day: u8) -> Result<(u8, u8), InvalidAnniversaryDate> {
+    match NaiveDate::from_ymd_opt(LEAP_YEAR, month as u32, day as u32) {
+        Some(_) => Ok((month, day)),
+        None => Err(InvalidAnniversaryDate { month, day }),
+    }
+}
+
+/// Date format shown by the watch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateFormat {
+    /// Month-Day-Year format (e.g., 12-31-99)
+    MonthDashDayDashYear,
+    /// Day-Month-Year format (e.g., 31-12-99)
+    DayDashMonthDashYear,
+    /// Year-Month-Day format (e.g., 99-12-31)
+    YearDashMonthDashDay,
+    /// Month.Day.Year format (e.g., 12.31.99)
+    MonthDotDayDotYear,
+    /// Day.Month.Year format (e.g., 31.12.99)
+    DayDotMonthDotYear,
+    /// Year.Month.Day format (e.g., 99.12.31)
+    YearDotMonthDotDay,
+}
+
+impl DateFormat {
+    /// Value sent to the watch in the time packet
+    pub fn value(self) -> u8 {
+        match self {
+            DateFormat::MonthDashDayDashYear => 0,
+            DateFormat::DayDashMonthDashYear => 1,
+            DateFormat::YearDashMonthDashDay => 2,
+            DateFormat::MonthDotDayDotYear => 4,
+            DateFormat::DayDotMonthDotYear => 5,
+            DateFormat::YearDotMonthDotDay => 6,
+        }
+    }
+}
+
+impl std::str::FromStr for DateFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "MonthDashDayDashYear" => Ok(DateFormat::MonthDashDayDashYear),
+            "DayDashMonthDashYear" => Ok(DateFormat::DayDashMonthDashYear),
+            "YearDashMonthDashDay" => Ok(DateFormat::YearDashMonthDashDay),
+            "MonthDotDayDotYear" => Ok(DateFormat::MonthDotDayDotYear),
+            "DayDotMonthDotYear" => Ok(DateFormat::DayDotMonthDotYear),
+            "YearDotMonthDotDay" => Ok(DateFormat::YearDotMonthDotDay),
+            _ => Err(format!("unknown date format: {}", s)),
+        }
+    }
+}
+
+/// Valid appointment notification minutes (0, 5, 10, 15, 20, 25, 30)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationMinutes {
+    /// No notification
+    None,
+    /// 5 minutes before
+    FiveMinutes,
+    /// 10 minutes before
+    TenMinutes,
+    /// 15 minutes before
+    FifteenMinutes,
+    /// 20 minutes before
+    TwentyMinutes,
+    /// 25 minutes before
+    TwentyFiveMinutes,
+    /// 30 minutes before
+    ThirtyMinutes,
+}
+
+impl NotificationMinutes {
+    /// Create a notification from a number of minutes
+    ///
+    /// Returns `None` unless the minutes are a multiple of 5 from 0 to 30.
+    pub fn from_minutes(minutes: u8) -> Option<Self> {
+        match minutes {
+            0 => Some(NotificationMinutes::None),
+            5 => Some(NotificationMinutes::FiveMinutes),
+            10 => Some(NotificationMinutes::TenMinutes),
+            15 => Some(NotificationMinutes::FifteenMinutes),
+            20 => Some(NotificationMinutes::TwentyMinutes),
+            25 => Some(NotificationMinutes::TwentyFiveMinutes),
+            30 => Some(NotificationMinutes::ThirtyMinutes),
+            _ => None,
+        }
+    }
+
+    /// Number of minutes before the appointment
+    pub fn minutes(self) -> u8 {
+        self.value() * 5
+    }
+
+    /// Value sent to the watch in the EEPROM header
+    pub fn value(self) -> u8 {
+        match self {
+            NotificationMinutes::None => 0,
+            NotificationMinutes::FiveMinutes => 1,
+            NotificationMinutes::TenMinutes => 2,
+            NotificationMinutes::FifteenMinutes => 3,
+            NotificationMinutes::TwentyMinutes => 4,
+            NotificationMinutes::TwentyFiveMinutes => 5,
+            NotificationMinutes::ThirtyMinutes => 6,
+        }
+    }
+}
+
+/// Phone number type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PhoneType {
+    /// Cell phone (shown as 'c')
+    Cell,
+    /// Home phone (shown as 'h')
+    Home,
+    /// Work phone (shown as 'w')
+    Work,
+    /// Other phone type (shown as a blank)
+    #[default]
+    Other,
+}
+
+impl PhoneType {
+    /// Parse a phone type code such as "H", "home" or "c"
+    ///
+    /// Unknown codes are treated as `Other`.
+    pub fn from_code(code: &str) -> Self {
+        match code.trim().to_ascii_lowercase().chars().next() {
+            Some('c') => PhoneType::Cell,
+            Some('h') => PhoneType::Home,
+            Some('w') => PhoneType::Work,
+            _ => PhoneType::Other,
+        }
+    }
+
+    /// Character shown after the phone number
+    pub fn code(self) -> char {
+        match self {
+            PhoneType::Cell => 'c',
+            PhoneType::Home => 'h',
+            PhoneType::Work => 'w',
+            PhoneType::Other => ' ',
+        }
+    }
+}
+
+/// Priority level for list items (1-5)
+///
+/// The watch shows the level as a number. Which end is more urgent is not
+/// documented, so importers keep the numbering of their source, where 1 is
+/// the most urgent (todo.txt `(A)`, iCalendar `PRIORITY:1`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Priority {
+    /// Priority level 1
+    One,
+    /// Priority level 2
+    Two,
+    /// Priority level 3
+    Three,
+    /// Priority level 4
+    Four,
+    /// Priority level 5
+    Five,
+}
+
+impl Priority {
+    /// Create a priority from its level (1-5)
+    pub fn from_value(value: u8) -> Option<Self> {
+        match value {
+            1 => Some(Priority::One),
+            2 => Some(Priority::Two),
+            3 => Some(Priority::Three),
+            4 => Some(Priority::Four),
+            5 => Some(Priority::Five),
+            _ => None,
+        }
+    }
+
+    /// Priority level (1-5)
+    pub fn value(self) -> u8 {
+        match self {
+            Priority::One => 1,
+            Priority::Two => 2,
+            Priority::Three => 3,
+            Priority::Four => 4,
+            Priority::Five => 5,
+        }
+    }
+}
+
+/// An alarm
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alarm {
+    /// Alarm number (1-5)
+    pub number: u8,
+
+    /// Whether the alarm makes a sound
+    pub audible: bool,
+
+    /// Hour of the alarm (0-23)
+    pub hour: u8,
+
+    /// Minute of the alarm (0-59)
+    pub minute: u8,
+
+    /// Alarm message (max 8 characters)
+    pub message: String,
+}
+
+impl Alarm {
+    /// Create a new audible alarm
+    pub fn new(number: u8, hour: u8, minute: u8, message: String) -> Self {
+        Alarm { number, audible: true, hour, minute, message }
+    }
+
+    /// Time of day of the alarm
+    ///
+    /// # Returns
+    ///
+    /// The time, or an error if the hour or minute is out of range
+    pub fn time(&self) -> Result<NaiveTime, InvalidAlarmTime> {
+        NaiveTime::from_hms_opt(self.hour as u32, self.minute as u32, 0)
+            .ok_or(InvalidAlarmTime { hour: self.hour, minute: self.minute })
+    }
+}
+
+/// An appointment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Appointment {
+    /// Wall-clock time of the appointment in the watch's time zone
+    pub time: NaiveDateTime,
+
+    /// Appointment message (max 31 characters)
+    pub message: String,
+
+    /// How the time is rounded into the watch's 15-minute slots
+    pub rounding: RoundingPolicy,
+}
+
+impl Appointment {
+    /// Create a new appointment using the default rounding policy
+    pub fn new(time: NaiveDateTime, message: String) -> Self {
+        Appointment { time, message, rounding: RoundingPolicy::default() }
+    }
+}
+
+/// An anniversary
+///
+/// Anniversaries recur every year, so only the month and day are stored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Anniversary {
+    /// Month of the anniversary (1-12)
+    pub month: u8,
+
+    /// Day of the anniversary (1-31, February 29 is allowed)
+    pub day: u8,
+
+    /// Anniversary text (max 31 characters)
+    pub anniversary: String,
+}
+
+impl Anniversary {
+    /// Create a new anniversary from a month and day
+    ///
+    /// Returns an error if the day does not exist in the month.
+    pub fn new(month: u8, day: u8, anniversary: String) -> Result<Self, InvalidAnniversaryDate> {
+        let (month, day) = validate_month_day(month, day)?;
+
+        Ok(Anniversary { month, day, anniversary })
+    }
+
+    /// Create a new anniversary recurring on the month and day of a date
+    pub fn from_date(date: NaiveDate, anniversary: String) -> Self {
+        Anniversary {
+            month: date.month() as u8,
+            day: date.day() as u8,
+            anniversary,
+        }
+    }
+}
+
+/// A phone book entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhoneEntry {
+    /// Contact name (max 31 characters)
+    pub name: String,
+
+    /// Phone number (max 10 digits)
+    pub number: String,
+
+    /// Type of phone number
+    pub phone_type: PhoneType,
+}
+
+impl PhoneEntry {
+    /// Create a new phone book entry
+    pub fn new(name: String, number: String, phone_type: PhoneType) -> Self {
+        PhoneEntry { name, number, phone_type }
+    }
+}
+
+/// A to-do list entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListEntry {
+    /// Entry text (max 31 characters)
+    pub entry: String,
+
+    /// Priority level (optional)
+    pub priority: Option<Priority>,
+}
+
+impl ListEntry {
+    /// Create a new list entry
+    pub fn new(entry: String, priority: Option<Priority>) -> Self {
+        ListEntry { entry, priority }
+    }
+}
+
+/// Time and display settings of one of the watch's two time zones
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeZone {
+    /// Time zone number (1 or 2)
+    pub zone: u8,
+
+    /// Whether to use 24-hour time format
+    pub is_24h: bool,
+
+    /// Date format to use
+    pub date_format: DateFormat,
+
+    /// Time to set, read as UTC by the watch
+    pub time: SystemTime,
+
+    /// Name of the time zone (max 3 characters)
+    pub name: String,
+}
+
+/// Sound settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SoundOptions {
+    /// Whether the watch chimes every hour
+    pub hourly_chime: bool,
+
+    /// Whether buttons beep when pressed
+    pub button_beep: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{protocol_3, protocol_4, PacketGenerator};
+    use chrono::{NaiveDate, TimeZone as _, Utc};
+
+    #[test]
+    fn test_notification_minutes() {
+        assert_eq!(NotificationMinutes::from_minutes(15), Some(NotificationMinutes::FifteenMinutes));
+        assert_eq!(NotificationMinutes::from_minutes(7), None);
+        assert_eq!(NotificationMinutes::ThirtyMinutes.minutes(), 30);
+        assert_eq!(NotificationMinutes::ThirtyMinutes.value(), 6);
+    }
+
+    #[test]
+    fn test_protocols_share_models() {
+        let alarm = Alarm::new(1, 9, 0, "Wake up".to_string());
+        assert_eq!(
+            protocol_3::Alarm::try_from(&alarm).unwrap().packets(),
+            protocol_4::Alarm::try_from(&alarm).unwrap().packets()
+        );
+
+        // Both protocols reject the same invalid times
+        let alarm = Alarm::new(1, 24, 0, "Nope".to_string());
+        let error = InvalidAlarmTime { hour: 24, minute: 0 };
+        assert_eq!(protocol_3::Alarm::try_from(&alarm).err(), Some(error));
+        assert_eq!(protocol_4::Alarm::try_from(&alarm).err(), Some(error));
+
+        let time = TimeZone {
+            zone: 1,
+            is_24h: false,
+            date_format: DateFormat::MonthDashDayDashYear,
+            time: Utc.with_ymd_and_hms(2022, 9, 5, 3, 39, 44).unwrap().into(),
+            name: "PDT".to_string(),
+        };
+        assert_eq!(protocol_3::Time::from(&time).packets(), protocol_4::Time::from(&time).packets());
+    }
+
+    #[test]
+    fn test_eeprom_from_models() {
+        let appointment = Appointment::new(
+            NaiveDate::from_ymd_opt(2022, 10, 31).unwrap().and_hms_opt(19, 0, 0).unwrap(),
+            "Scare the neighbors".to_string(),
+        );
+        let phone = PhoneEntry::new("Marty McFly".to_string(), "1112223333".to_string(), PhoneType::Home);
+        let list = ListEntry::new("Muffler bearings".to_string(), Some(Priority::Two));
+
+        let mut protocol_3_eeprom = protocol_3::eeprom::Eeprom::new();
+        protocol_3_eeprom.appointments.push((&appointment).into());
+        protocol_3_eeprom.phone_numbers.push((&phone).into());
+        protocol_3_eeprom.lists.push((&list).into());
+        protocol_3_eeprom.appointment_notification_minutes = Some(NotificationMinutes::FifteenMinutes);
+
+        let protocol_4_eeprom = protocol_4::eeprom::Eeprom {
+            appointments: vec![(&appointment).into()],
+            anniversaries: vec![],
+            phone_numbers: vec![(&phone).into()],
+            lists: vec![(&list).into()],
+            appointment_notification_minutes: Some(NotificationMinutes::FifteenMinutes),
+        };
+
+        assert_eq!(protocol_3_eeprom.packets(), protocol_4_eeprom.packets());
+    }
+}
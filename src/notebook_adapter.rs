@@ -10,6 +10,11 @@ use std::time::Duration;
 #[cfg(not(target_arch = "wasm32"))]
 use std::thread::sleep;
 
+#[cfg(not(target_arch = "wasm32"))]
+use crate::transfer_estimate::AdapterTiming;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::transmission_schedule::TransmissionSchedule;
+
 /// Notebook adapter for sending data to Timex watches
 ///
 /// This handles the serial communication with the watch, including
@@ -69,21 +74,18 @@ impl NotebookAdapter {
     #[cfg(not(target_arch = "wasm32"))]
     pub fn write(&self, packets: &[Vec<u8>]) -> io::Result<()> {
         let port = serial2::SerialPort::open(&self.serial_device, 9600)?;
+        let schedule = TransmissionSchedule::new(packets, &AdapterTiming::from(self));
         
-        for packet in packets {
-            for &byte in packet {
-                if self.verbose {
-                    print!("{:02X} ", byte);
-                }
-                
-                port.write(&[byte])?;
-                
-                sleep(Duration::from_secs_f32(self.byte_sleep));
+        for step in &schedule.bytes {
+            if self.verbose {
+                print!("{:02X} ", step.byte);
             }
             
-            sleep(Duration::from_secs_f32(self.packet_sleep));
+            port.write(&[step.byte])?;
             
-            if self.verbose {
+            sleep(Duration::from_secs_f64(step.delay));
+            
+            if self.verbose && step.end_of_packet {
                 println!();
             }
         }
@@ -12,6 +12,11 @@ use std::time::Duration;
 #[cfg(not(target_arch = "wasm32"))]
 use std::thread::sleep;
 
+#[cfg(not(target_arch = "wasm32"))]
+use crate::transfer_estimate::AdapterTiming;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::transmission_schedule::TransmissionSchedule;
+
 /// OnePlus 6 LED adapter for sending data to Timex watches
 ///
 /// This handles the LED communication with the watch, including
@@ -37,6 +42,9 @@ impl OnePlus6LedAdapter {
     /// Default time to sleep after sending a packet (in seconds)
     pub const PACKET_SLEEP_DEFAULT: f32 = 0.25;
     
+    /// Time the LED is held off before the first packet (in seconds)
+    pub const STARTUP_SLEEP: f32 = 0.5;
+    
     /// Default LED sysfs path
     pub const DEFAULT_LED_PATH: &'static str = "/sys/class/leds/rgb:status";
     
@@ -111,33 +119,30 @@ impl OnePlus6LedAdapter {
         // Initialize the LED to white color
         self.init_led()?;
         
+        let schedule = TransmissionSchedule::new(packets, &AdapterTiming::from(self));
+        let bit_sleep = Duration::from_secs_f64(schedule.bit_seconds());
+        
         // Ensure LED is off initially
         self.led_off()?;
-        sleep(Duration::from_secs_f32(0.5));
+        sleep(Duration::from_secs_f32(schedule.timing.startup));
         
-        for packet in packets {
-            for &byte in packet {
-                if self.verbose {
-                    print!("{:02X} ", byte);
-                }
-                
-                // Send each bit of the byte (MSB first)
-                for i in (0..8).rev() {
-                    let bit = (byte >> i) & 1 == 1;
-                    self.send_bit(bit)?;
-                    sleep(Duration::from_secs_f32(self.byte_sleep / 8.0));
-                }
-                
-                // Brief pause between bytes
-                self.led_off()?;
-                sleep(Duration::from_secs_f32(self.byte_sleep));
+        for step in &schedule.bytes {
+            if self.verbose {
+                print!("{:02X} ", step.byte);
+            }
+            
+            // Send each bit of the byte (MSB first)
+            for i in (0..8).rev() {
+                let bit = (step.byte >> i) & 1 == 1;
+                self.send_bit(bit)?;
+                sleep(bit_sleep);
             }
             
-            // Longer pause between packets
+            // Pause between bytes, and for longer between packets
             self.led_off()?;
-            sleep(Duration::from_secs_f32(self.packet_sleep));
+            sleep(Duration::from_secs_f64(step.delay));
             
-            if self.verbose {
+            if self.verbose && step.end_of_packet {
                 println!();
             }
         }
@@ -165,7 +170,7 @@ mod tests {
         assert_eq!(adapter.led_path, OnePlus6LedAdapter::DEFAULT_LED_PATH);
         assert_eq!(adapter.byte_sleep, OnePlus6LedAdapter::BYTE_SLEEP_DEFAULT);
         assert_eq!(adapter.packet_sleep, OnePlus6LedAdapter::PACKET_SLEEP_DEFAULT);
-        assert_eq!(adapter.verbose, false);
+        assert!(!adapter.verbose);
     }
     
     #[test]
@@ -179,6 +184,6 @@ mod tests {
         assert_eq!(adapter.led_path, "/custom/led/path");
         assert_eq!(adapter.byte_sleep, 0.05);
         assert_eq!(adapter.packet_sleep, 0.5);
-        assert_eq!(adapter.verbose, true);
+        assert!(adapter.verbose);
     }
 }
\ No newline at end of file
@@ -0,0 +1,461 @@
+//! Packet annotator module
+//!
+//! This module turns the raw packets emitted by the Protocol 3 and Protocol 4
+//! models back into a human-readable listing: the command byte name, the
+//! meaning of every field and whether the CRC footer is valid. Commands are
+//! decoded with the layouts of the protocols' command registries.
+
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::char_encoders::CHARS;
+use crate::helpers::command_packet::{
+    CommandLayout, CommandRegistry, DecodedField, FieldKind, FieldValue, Opcode,
+};
+use crate::helpers::crc_packets_wrapper::{self, CrcError};
+use crate::{protocol_3, protocol_4};
+
+pub mod capture;
+
+/// Command registries packets are decoded with
+static REGISTRIES: [&CommandRegistry; 2] = [&protocol_3::commands::COMMANDS, &protocol_4::commands::COMMANDS];
+
+/// Result of checking the CRC footer of a packet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum CrcStatus {
+    /// The length header and CRC footer match the packet contents
+    Valid,
+
+    /// The CRC footer does not match the packet contents
+    Invalid {
+        /// CRC calculated from the packet contents
+        expected: u16,
+        /// CRC found in the packet footer
+        actual: u16,
+    },
+
+    /// The length header does not match the packet length
+    BadLength {
+        /// Length declared in the header byte
+        declared: u8,
+        /// Actual length of the packet
+        actual: usize,
+    },
+
+    /// The packet is not CRC wrapped (e.g. sync packets)
+    NotApplicable,
+}
+
+/// A single decoded field of a packet
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldAnnotation {
+    /// Field name
+    pub name: String,
+
+    /// Raw bytes of the field
+    pub raw: Vec<u8>,
+
+    /// Human-readable meaning of the field
+    pub meaning: String,
+}
+
+/// Annotated view of a single packet
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PacketAnnotation {
+    /// Position of the packet in the packet list
+    pub index: usize,
+
+    /// Command byte, if the packet has one
+    pub command: Option<u8>,
+
+    /// Command name (e.g. "TIME", "SECT")
+    pub name: String,
+
+    /// Decoded fields
+    pub fields: Vec<FieldAnnotation>,
+
+    /// CRC check result
+    pub crc: CrcStatus,
+
+    /// Raw packet bytes
+    pub bytes: Vec<u8>,
+}
+
+impl PacketAnnotation {
+    /// Whether the packet passed its length and CRC checks
+    pub fn is_valid(&self) -> bool {
+        matches!(self.crc, CrcStatus::Valid | CrcStatus::NotApplicable)
+    }
+}
+
+/// Annotate a list of packets
+///
+/// # Arguments
+///
+/// * `packets` - The packets to annotate, as returned by `PacketGenerator::packets`
+///
+/// # Returns
+///
+/// One annotation per packet, in the same order
+pub fn annotate_packets(packets: &[Vec<u8>]) -> Vec<PacketAnnotation> {
+    packets.iter()
+        .enumerate()
+        .map(|(index, packet)| annotate_packet(index, packet))
+        .collect()
+}
+
+/// Annotate a single packet
+///
+/// # Arguments
+///
+/// * `index` - Position of the packet in its packet list
+/// * `packet` - The packet bytes, including CRC header and footer
+pub fn annotate_packet(index: usize, packet: &[u8]) -> PacketAnnotation {
+    if crc_packets_wrapper::is_sync_packet(packet) {
+        return annotate_sync(index, packet);
+    }
+
+    let crc = check_crc(packet);
+
+    // Strip the length header and CRC footer to get to the command payload
+    let payload = if packet.len() >= 3 {
+        &packet[1..packet.len() - 2]
+    } else {
+        &[][..]
+    };
+
+    let (command, name, fields) = match payload.first() {
+        Some(&command) => {
+            let (name, fields) = decode_command(payload);
+            (Some(command), name, fields)
+        }
+        None => (None, "EMPTY".to_string(), Vec::new()),
+    };
+
+    PacketAnnotation {
+        index,
+        command,
+        name,
+        fields,
+        crc,
+        bytes: packet.to_vec(),
+    }
+}
+
+/// Format annotations as a text listing
+pub fn format_listing(annotations: &[PacketAnnotation]) -> String {
+    annotations.iter()
+        .map(|annotation| annotation.to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+impl fmt::Display for PacketAnnotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let command = match self.command {
+            Some(command) => format!(" (0x{:02x})", command),
+            None => String::new(),
+        };
+
+        let crc = match self.crc {
+            CrcStatus::Valid => "crc ok".to_string(),
+            CrcStatus::Invalid { expected, actual } => {
+                format!("crc BAD (expected 0x{:04x}, got 0x{:04x})", expected, actual)
+            }
+            CrcStatus::BadLength { declared, actual } => {
+                format!("length BAD (header says {}, packet has {})", declared, actual)
+            }
+            CrcStatus::NotApplicable => "no crc".to_string(),
+        };
+
+        writeln!(f, "#{:<3} {}{}  [{} bytes, {}]", self.index, self.name, command, self.bytes.len(), crc)?;
+
+        for field in &self.fields {
+            writeln!(f, "     {:<24} {:<24} {}", field.name, hex(&field.raw), field.meaning)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Annotate a sync packet
+fn annotate_sync(index: usize, packet: &[u8]) -> PacketAnnotation {
+    let sync_1 = packet.iter().filter(|&&byte| byte == 0x55).count();
+    let sync_2 = packet.iter().filter(|&&byte| byte == 0xaa).count();
+
+    PacketAnnotation {
+        index,
+        command: None,
+        name: "SYNC".to_string(),
+        fields: vec![
+            field("ping", &packet[..1], "ping byte"),
+            field("sync_1", &[], format!("{} x 0x55", sync_1)),
+            field("sync_2", &[], format!("{} x 0xaa", sync_2)),
+        ],
+        crc: CrcStatus::NotApplicable,
+        bytes: packet.to_vec(),
+    }
+}
+
+/// Verify the length header and CRC footer of a packet
+fn check_crc(packet: &[u8]) -> CrcStatus {
+    match crc_packets_wrapper::unwrap_packet_with_crc(packet) {
+        Ok(_) => CrcStatus::Valid,
+        Err(CrcError::CrcMismatch { expected, actual }) => CrcStatus::Invalid { expected, actual },
+        Err(CrcError::LengthMismatch { declared, actual }) => CrcStatus::BadLength { declared, actual },
+        Err(CrcError::TooShort { length }) => CrcStatus::BadLength {
+            declared: packet.first().copied().unwrap_or(0),
+            actual: length,
+        },
+    }
+}
+
+/// Decode a command payload into a name and field list
+///
+/// The layout is looked up in the Protocol 3 and Protocol 4 command
+/// registries, so the annotation always follows the layouts the packets are
+/// built from.
+fn decode_command(payload: &[u8]) -> (String, Vec<FieldAnnotation>) {
+    let layout = match REGISTRIES.iter().find_map(|registry| registry.identify(payload)) {
+        Some(layout) => layout,
+        None => return decode_unknown(payload),
+    };
+
+    let mut fields: Vec<FieldAnnotation> = prefix_field(layout).into_iter().collect();
+    match layout.decode(payload) {
+        Ok(decoded) => fields.extend(
+            layout.fields.iter()
+                .zip(&decoded.fields)
+                .map(|(layout_field, decoded_field)| describe_field(layout_field.kind, decoded_field)),
+        ),
+        Err(e) => fields.push(field("data", &payload[layout.prefix.len()..], e.to_string())),
+    }
+
+    (layout.name.to_string(), fields)
+}
+
+/// Name a command that no registry has a layout for
+fn decode_unknown(payload: &[u8]) -> (String, Vec<FieldAnnotation>) {
+    let name = match Opcode::from_byte(payload[0]) {
+        Some(opcode) => opcode.name(),
+        None => "UNKNOWN",
+    };
+
+    (name.to_string(), vec![field("data", &payload[1..], "undecoded")])
+}
+
+/// Describe the fixed bytes after the command byte (protocol or section subtype)
+fn prefix_field(layout: &CommandLayout) -> Option<FieldAnnotation> {
+    let rest = &layout.prefix[1..];
+
+    match (layout.opcode(), rest.last()) {
+        (Some(Opcode::Start), Some(&protocol)) => Some(field("protocol", rest, format!("protocol {}", protocol))),
+        (_, Some(&subtype)) => Some(field("subtype", rest, section_name(subtype))),
+        (_, None) => None,
+    }
+}
+
+/// Describe a decoded field from its kind and name
+fn describe_field(kind: FieldKind, decoded: &DecodedField) -> FieldAnnotation {
+    let meaning = match (&decoded.value, decoded.name) {
+        (FieldValue::Bool(flag), _) => yes_no(*flag),
+        (FieldValue::U16(address), _) => format!("0x{:04x}", address),
+        (FieldValue::U8(zone), "zone") => format!("time zone {}", zone),
+        (FieldValue::U8(year), "year" | "earliest_year") => format!("{:02}", year),
+        (FieldValue::U8(weekday), "weekday") => weekday_name(*weekday),
+        (FieldValue::U8(format), "format") => hour_format_name(*format),
+        (FieldValue::U8(format), "date_format") => date_format_name(*format),
+        (FieldValue::U8(number), "number") => format!("alarm {}", number),
+        (FieldValue::U8(hour), "hour") => format!("hour {}", hour),
+        (FieldValue::U8(minute), "minute") => format!("minute {}", minute),
+        (FieldValue::U8(second), "second") => format!("second {}", second),
+        (FieldValue::U8(packets), "packets") => format!("{} data packets", packets),
+        (FieldValue::U8(index), "index") => format!("packet {}", index),
+        (FieldValue::U8(0xff), "notification") => "no notification".to_string(),
+        (FieldValue::U8(steps), "notification") => format!("{} minutes before", *steps as u16 * 5),
+        (FieldValue::U8(offset), "offset") => format!("{} bytes of sound data", 0x100 - *offset as usize),
+        (FieldValue::U8(value), _) => value.to_string(),
+        (FieldValue::Bytes(bytes), _) => match kind {
+            FieldKind::Chars(_) => format!("\"{}\"", decode_chars(bytes)),
+            FieldKind::Tail => format!("{} bytes", bytes.len()),
+            FieldKind::Reserved(_) => "reserved".to_string(),
+            _ => String::new(),
+        },
+    };
+
+    field(decoded.name, &decoded.raw, meaning)
+}
+
+fn section_name(subtype: u8) -> String {
+    match subtype {
+        0x01 => "EEPROM".to_string(),
+        0x02 => "WRIST_APP".to_string(),
+        0x03 => "SOUND_THEME".to_string(),
+        other => format!("0x{:02x}", other),
+    }
+}
+
+fn weekday_name(weekday: u8) -> String {
+    const WEEKDAYS: [&str; 7] = ["Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday", "Sunday"];
+    WEEKDAYS.get(weekday as usize)
+        .map(|name| name.to_string())
+        .unwrap_or_else(|| format!("unknown ({})", weekday))
+}
+
+fn hour_format_name(format: u8) -> String {
+    match format {
+        1 => "12 hour".to_string(),
+        2 => "24 hour".to_string(),
+        other => format!("unknown ({})", other),
+    }
+}
+
+fn date_format_name(format: u8) -> String {
+    match format {
+        0 => "MM-DD-YY".to_string(),
+        1 => "DD-MM-YY".to_string(),
+        2 => "YY-MM-DD".to_string(),
+        4 => "MM.DD.YY".to_string(),
+        5 => "DD.MM.YY".to_string(),
+        6 => "YY.MM.DD".to_string(),
+        other => format!("unknown ({})", other),
+    }
+}
+
+fn yes_no(flag: bool) -> String {
+    if flag { "yes".to_string() } else { "no".to_string() }
+}
+
+/// Decode watch characters back to text
+fn decode_chars(bytes: &[u8]) -> String {
+    bytes.iter()
+        .map(|&byte| CHARS.chars().nth(byte as usize).unwrap_or('?'))
+        .collect()
+}
+
+fn field(name: &str, raw: &[u8], meaning: impl Into<String>) -> FieldAnnotation {
+    FieldAnnotation {
+        name: name.to_string(),
+        raw: raw.to_vec(),
+        meaning: meaning.into(),
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PacketGenerator;
+    use crate::char_encoders::CharString;
+    use crate::protocol_3::{Alarm, End, Start, Sync};
+
+    #[test]
+    fn test_annotate_alarm() {
+        let alarm = Alarm {
+            number: 1,
+            audible: true,
+            hour: 9,
+            minute: 0,
+            message: CharString::new("Wake up", true),
+        };
+
+        let annotation = annotate_packet(0, &alarm.packets()[0]);
+
+        assert_eq!(annotation.name, "ALARM");
+        assert_eq!(annotation.command, Some(0x50));
+        assert_eq!(annotation.crc, CrcStatus::Valid);
+        assert_eq!(annotation.fields[0].meaning, "alarm 1");
+        assert_eq!(annotation.fields[4].meaning, "\"wake up \"");
+        assert_eq!(annotation.fields[5].meaning, "yes");
+    }
+
+    #[test]
+    fn test_annotate_session() {
+        let mut packets = Sync { length: 10 }.packets();
+        packets.extend(Start.packets());
+        packets.extend(End.packets());
+
+        let annotations = annotate_packets(&packets);
+        let names: Vec<&str> = annotations.iter().map(|a| a.name.as_str()).collect();
+
+        assert_eq!(names, vec!["SYNC", "START", "END"]);
+        assert!(annotations.iter().all(|a| a.is_valid()));
+        assert_eq!(annotations[1].fields[0].meaning, "protocol 3");
+    }
+
+    #[test]
+    fn test_annotate_protocol_4_session() {
+        let alarm = protocol_4::Alarm {
+            number: 2,
+            audible: false,
+            time: std::time::UNIX_EPOCH + std::time::Duration::from_secs(12 * 3600 + 30 * 60),
+            message: CharString::new("Lunch", true),
+        };
+        let mut packets = protocol_4::Sync { length: 10 }.packets();
+        packets.extend(protocol_4::Start {}.packets());
+        packets.extend(alarm.packets());
+        packets.extend(protocol_4::End {}.packets());
+
+        let annotations = annotate_packets(&packets);
+        let names: Vec<&str> = annotations.iter().map(|a| a.name.as_str()).collect();
+
+        assert_eq!(names, vec!["SYNC", "START", "ALARM", "END"]);
+        assert!(annotations.iter().all(|a| a.is_valid()));
+        assert_eq!(annotations[1].fields[0].meaning, "protocol 4");
+
+        let meanings: Vec<&str> = annotations[2].fields.iter().map(|f| f.meaning.as_str()).collect();
+        assert_eq!(meanings, vec!["alarm 2", "hour 12", "minute 30", "reserved", "\"lunch   \"", "no"]);
+
+        let names: Vec<&str> = annotations[2].fields.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["number", "hour", "minute", "reserved", "message", "audible"]);
+    }
+
+    #[test]
+    fn test_annotate_bad_crc() {
+        let mut packet = End.packets().remove(0);
+        let last = packet.len() - 1;
+        packet[last] ^= 0xff;
+
+        let annotation = annotate_packet(0, &packet);
+
+        assert!(matches!(annotation.crc, CrcStatus::Invalid { .. }));
+        assert!(!annotation.is_valid());
+    }
+
+    #[test]
+    fn test_annotate_undecodable() {
+        let packets = crc_packets_wrapper::wrap_packets_with_crc(vec![
+            vec![0x50, 1, 9],
+            vec![0x90, 0x04, 1],
+            vec![0x42, 1],
+        ]);
+        let annotations = annotate_packets(&packets);
+
+        assert_eq!(annotations[0].name, "ALARM");
+        assert_eq!(annotations[0].fields[0].meaning, "ALARM: payload ends inside field minute");
+        assert_eq!(annotations[1].name, "SECT");
+        assert_eq!(annotations[2].name, "UNKNOWN");
+        assert_eq!(annotations[2].fields[0].meaning, "undecoded");
+    }
+
+    #[test]
+    fn test_annotate_eeprom_section() {
+        // From golden fixture: appointment.jsonl
+        let packet = vec![20, 144, 1, 1, 2, 54, 2, 73, 2, 73, 2, 73, 1, 0, 0, 0, 22, 255, 146, 12];
+
+        let annotation = annotate_packet(0, &packet);
+
+        assert_eq!(annotation.name, "SECT EEPROM");
+        assert_eq!(annotation.crc, CrcStatus::Valid);
+        assert!(annotation.fields.iter().any(|f| f.name == "appointments_count" && f.meaning == "1"));
+        assert!(annotation.fields.iter().any(|f| f.name == "notification" && f.meaning == "no notification"));
+    }
+}
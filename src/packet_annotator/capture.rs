@@ -0,0 +1,356 @@
+//! Capture decoding
+//!
+//! This module decodes a captured sync session, e.g. a hex dump pasted by a
+//! customer or a raw dump of the bytes sent to the watch. The stream is split
+//! into packets, every packet is annotated, and load sections are reassembled
+//! so that EEPROM entries (appointments, lists, phone numbers and
+//! anniversaries) can be shown field by field.
+
+use std::fmt;
+
+use serde::Serialize;
+
+use super::{annotate_packets, field, section_name, FieldAnnotation, PacketAnnotation};
+use crate::char_encoders::{EepromString, PhoneString, PHONE_FIELD_LENGTH};
+use crate::helpers::appointment_time;
+use crate::helpers::command_packet::FieldValue;
+use crate::helpers::crc_packets_wrapper;
+use crate::helpers::load_section::{self, LoadSection};
+use crate::protocol_3::commands::{EEPROM_SECT, SECTION_EEPROM};
+
+/// Maximum number of bytes of an encoded phone number (two characters per byte)
+const PHONE_BYTES: usize = PHONE_FIELD_LENGTH / 2;
+
+/// Errors raised while reading a hex capture
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CaptureError {
+    /// A token is not a hex byte or a run of hex bytes
+    InvalidHex { line: usize, token: String },
+}
+
+impl fmt::Display for CaptureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CaptureError::InvalidHex { line, token } => write!(f, "line {}: \"{}\" is not hex", line, token),
+        }
+    }
+}
+
+impl std::error::Error for CaptureError {}
+
+/// A decoded EEPROM entry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EntryAnnotation {
+    /// Entry kind ("appointment", "list", "phone_number" or "anniversary")
+    pub kind: String,
+
+    /// Decoded fields
+    pub fields: Vec<FieldAnnotation>,
+
+    /// Raw entry bytes, including the length byte
+    pub bytes: Vec<u8>,
+}
+
+/// A reassembled load section
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SectionAnnotation {
+    /// Section name (e.g. "EEPROM", "WRIST_APP")
+    pub name: String,
+
+    /// Section subtype
+    pub subtype: u8,
+
+    /// Number of payload bytes
+    pub payload_bytes: usize,
+
+    /// Decoded entries (EEPROM sections only)
+    pub entries: Vec<EntryAnnotation>,
+}
+
+/// Decoded view of a capture
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CaptureAnnotation {
+    /// Every packet in the capture
+    pub packets: Vec<PacketAnnotation>,
+
+    /// Reassembled load sections
+    pub sections: Vec<SectionAnnotation>,
+
+    /// Why the load sections could not be reassembled, if they could not
+    pub section_error: Option<String>,
+
+    /// Bytes at the end of the capture that do not form a whole packet
+    pub trailing: Vec<u8>,
+}
+
+impl fmt::Display for CaptureAnnotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for packet in &self.packets {
+            write!(f, "{}", packet)?;
+        }
+        if !self.trailing.is_empty() {
+            writeln!(f, "trailing bytes: {}", super::hex(&self.trailing))?;
+        }
+        if let Some(error) = &self.section_error {
+            writeln!(f, "load sections: {}", error)?;
+        }
+
+        for section in &self.sections {
+            writeln!(f, "{} section, {} payload bytes", section.name, section.payload_bytes)?;
+            for entry in &section.entries {
+                writeln!(f, "  {}", entry.kind)?;
+                for field in &entry.fields {
+                    writeln!(f, "     {:<24} {:<24} {}", field.name, super::hex(&field.raw), field.meaning)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Read a hex capture
+///
+/// Bytes may be separated by whitespace, commas or colons and prefixed with
+/// `0x`, or written as runs of hex digits. Text after `#` or `//` on a line
+/// is a comment.
+///
+/// # Returns
+///
+/// The captured bytes, or the first token that is not hex
+pub fn parse_hex_capture(text: &str) -> Result<Vec<u8>, CaptureError> {
+    let mut bytes = Vec::new();
+
+    for (number, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("");
+        let line = line.split("//").next().unwrap_or("");
+
+        for token in line.split(|c: char| c.is_whitespace() || c == ',' || c == ':').filter(|token| !token.is_empty()) {
+            let invalid = || CaptureError::InvalidHex { line: number + 1, token: token.to_string() };
+            let digits = token.strip_prefix("0x").or_else(|| token.strip_prefix("0X")).unwrap_or(token);
+
+            if digits.len() == 1 {
+                bytes.push(u8::from_str_radix(digits, 16).map_err(|_| invalid())?);
+                continue;
+            }
+            if digits.is_empty() || !digits.len().is_multiple_of(2) || !digits.is_ascii() {
+                return Err(invalid());
+            }
+            for i in (0..digits.len()).step_by(2) {
+                bytes.push(u8::from_str_radix(&digits[i..i + 2], 16).map_err(|_| invalid())?);
+            }
+        }
+    }
+
+    Ok(bytes)
+}
+
+/// Decode a captured byte stream
+///
+/// # Arguments
+///
+/// * `stream` - The raw bytes as sent to the watch, sync packets included
+pub fn annotate_capture(stream: &[u8]) -> CaptureAnnotation {
+    let (packets, trailing) = crc_packets_wrapper::split_packets(stream);
+
+    let (sections, section_error) = match load_section::extract_load_sections(&packets) {
+        Ok(sections) => (sections.iter().map(annotate_section).collect(), None),
+        Err(e) => (Vec::new(), Some(e.to_string())),
+    };
+
+    CaptureAnnotation {
+        packets: annotate_packets(&packets),
+        sections,
+        section_error,
+        trailing,
+    }
+}
+
+/// Annotate a reassembled load section
+fn annotate_section(section: &LoadSection) -> SectionAnnotation {
+    let entries = if section.subtype == SECTION_EEPROM {
+        decode_eeprom_entries(section)
+    } else {
+        Vec::new()
+    };
+
+    SectionAnnotation {
+        name: section_name(section.subtype),
+        subtype: section.subtype,
+        payload_bytes: section.payload.len(),
+        entries,
+    }
+}
+
+/// Split EEPROM data into entries using the counts in the section header
+fn decode_eeprom_entries(section: &LoadSection) -> Vec<EntryAnnotation> {
+    let header = match section.raw_packets().unwrap_or_default().iter().find_map(|packet| EEPROM_SECT.decode(packet).ok()) {
+        Some(header) => header,
+        None => return Vec::new(),
+    };
+    let kinds = [
+        ("appointment", "appointments_count"),
+        ("list", "lists_count"),
+        ("phone_number", "phone_numbers_count"),
+        ("anniversary", "anniversaries_count"),
+    ];
+
+    let mut entries = Vec::new();
+    let mut data = section.payload.as_slice();
+
+    for (kind, count_field) in kinds {
+        let count = match header.get(count_field) {
+            Some(&FieldValue::U8(count)) => count,
+            _ => return entries,
+        };
+        for _ in 0..count {
+            let length = match data.first() {
+                Some(&length) if length >= 1 && length as usize <= data.len() => length as usize,
+                _ => return entries,
+            };
+            let (entry, rest) = data.split_at(length);
+            data = rest;
+
+            entries.push(EntryAnnotation {
+                kind: kind.to_string(),
+                fields: decode_entry(kind, &entry[1..]),
+                bytes: entry.to_vec(),
+            });
+        }
+    }
+
+    entries
+}
+
+/// Decode the fields of one EEPROM entry (without its length byte)
+fn decode_entry(kind: &str, body: &[u8]) -> Vec<FieldAnnotation> {
+    let text = |raw: &[u8]| format!("\"{}\"", EepromString::from_bytes(raw).decode());
+
+    match kind {
+        "appointment" if body.len() >= 3 => {
+            let time = match appointment_time::slot_time(body[2]) {
+                Some(time) => time.format("%H:%M").to_string(),
+                None => format!("invalid slot {}", body[2]),
+            };
+            vec![
+                field("month", &body[0..1], body[0].to_string()),
+                field("day", &body[1..2], body[1].to_string()),
+                field("time", &body[2..3], time),
+                field("message", &body[3..], text(&body[3..])),
+            ]
+        }
+        "list" if !body.is_empty() => {
+            let priority = match body[0] {
+                0 => "none".to_string(),
+                value => value.to_string(),
+            };
+            vec![
+                field("priority", &body[0..1], priority),
+                field("entry", &body[1..], text(&body[1..])),
+            ]
+        }
+        "phone_number" => {
+            let split = phone_number_length(body);
+            let number = PhoneString::from_bytes(&body[..split]).decode();
+            vec![
+                field("number", &body[..split], format!("\"{}\"", number.trim())),
+                field("name", &body[split..], text(&body[split..])),
+            ]
+        }
+        "anniversary" if body.len() >= 2 => vec![
+            field("month", &body[0..1], body[0].to_string()),
+            field("day", &body[1..2], body[1].to_string()),
+            field("message", &body[2..], text(&body[2..])),
+        ],
+        _ => vec![field("data", body, "truncated")],
+    }
+}
+
+/// Find where the packed number of a phone entry ends and the name begins
+///
+/// Neither part stores its length. Numbers are stored as "<number> <type>",
+/// so the longest split whose number ends that way and whose remainder
+/// re-encodes exactly as an EEPROM string is picked.
+fn phone_number_length(body: &[u8]) -> usize {
+    let name_fits = |split: usize| {
+        let name = EepromString::from_bytes(&body[split..]).decode();
+        EepromString::new(&name).as_bytes() == &body[split..]
+    };
+    let number_fits = |split: usize| {
+        let number: Vec<char> = PhoneString::from_bytes(&body[..split]).decode().chars().collect();
+        number.len() >= 2 && number[number.len() - 2] == ' '
+    };
+
+    let splits = || (1..=PHONE_BYTES.min(body.len())).rev();
+    splits().find(|&split| number_fits(split) && name_fits(split))
+        .or_else(|| splits().find(|&split| name_fits(split)))
+        .unwrap_or(body.len().min(PHONE_BYTES))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    use crate::models::{self, PhoneType};
+    use crate::watch_config::{ProtocolVersion, WatchConfig};
+
+    #[test]
+    fn test_parse_hex_capture() {
+        let text = "# start packet\n07 20 00 00 03 01 FE\n0x04,0x21, d8:c2 // end\n7855aa";
+
+        assert_eq!(
+            parse_hex_capture(text),
+            Ok(vec![0x07, 0x20, 0x00, 0x00, 0x03, 0x01, 0xfe, 0x04, 0x21, 0xd8, 0xc2, 0x78, 0x55, 0xaa])
+        );
+        assert_eq!(
+            parse_hex_capture("07 20\n0g"),
+            Err(CaptureError::InvalidHex { line: 2, token: "0g".to_string() })
+        );
+        assert!(parse_hex_capture("123").is_err());
+    }
+
+    #[test]
+    fn test_annotate_capture() {
+        let time = NaiveDate::from_ymd_opt(2025, 3, 14).unwrap().and_hms_opt(9, 30, 0).unwrap();
+        let config = WatchConfig::builder()
+            .sync_length(10)
+            .appointment(models::Appointment {
+                time,
+                message: "Dentist".to_string(),
+                rounding: Default::default(),
+            })
+            .list_entry(models::ListEntry::new("Milk".to_string(), None))
+            .phone_entry(models::PhoneEntry::new("Doc Brown".to_string(), "555123".to_string(), PhoneType::Home))
+            .anniversary(models::Anniversary::from_date(time.date(), "Pi day".to_string()))
+            .build()
+            .unwrap();
+
+        for protocol in ProtocolVersion::ALL {
+            let mut stream = config.packets(protocol).concat();
+            stream.extend([0x09, 0x91]);
+
+            let capture = annotate_capture(&stream);
+            assert_eq!(capture.trailing, vec![0x09, 0x91]);
+            assert_eq!(capture.section_error, None);
+            assert_eq!(capture.packets[0].name, "SYNC");
+
+            let eeprom = &capture.sections[0];
+            assert_eq!(eeprom.name, "EEPROM");
+
+            let meanings: Vec<Vec<&str>> = eeprom.entries.iter()
+                .map(|entry| entry.fields.iter().map(|field| field.meaning.as_str()).collect())
+                .collect();
+            assert_eq!(meanings, vec![
+                vec!["3", "14", "09:30", "\"dentist\""],
+                vec!["none", "\"milk\""],
+                vec!["\"555123 h\"", "\"doc brown\""],
+                vec!["3", "14", "\"pi day\""],
+            ]);
+        }
+
+        let listing = annotate_capture(&config.packets(ProtocolVersion::Protocol3).concat()).to_string();
+        assert!(listing.contains("EEPROM section"));
+        assert!(listing.contains("\"doc brown\""));
+    }
+}
@@ -4,7 +4,9 @@
 
 use crate::PacketGenerator;
 use crate::helpers::crc_packets_wrapper;
-use crate::char_encoders::CharString;
+use crate::protocol_3::commands;
+use crate::char_encoders::{CharString, ALARM_MESSAGE_LENGTH};
+use crate::models::InvalidAlarmTime;
 
 /// Alarm structure for Protocol 3
 ///
@@ -23,36 +25,42 @@ pub struct Alarm {
     pub minute: u8,
     
     /// Message to display (8 chars max)
-    pub message: CharString<8>,
+    pub message: CharString<ALARM_MESSAGE_LENGTH>,
 }
 
 impl PacketGenerator for Alarm {
     fn packets(&self) -> Vec<Vec<u8>> {
-        // Define constants from Ruby implementation
-        const CPACKET_ALARM: u8 = 0x50;
-
-        // Create the raw packet
-        let mut raw_packet = Vec::with_capacity(16);
-        raw_packet.push(CPACKET_ALARM);   // Alarm command
-        raw_packet.push(self.number);      // Alarm number (1-5)
-        raw_packet.push(self.hour);        // Hour
-        raw_packet.push(self.minute);      // Minute
-        raw_packet.push(0);                // Unused bytes in protocol 3
-        raw_packet.push(0);                // Unused bytes in protocol 3
-        
-        // Add message characters (8 chars)
-        for &byte in self.message.as_bytes() {
-            raw_packet.push(byte);
-        }
-        
-        // Audible flag
-        raw_packet.push(if self.audible { 1 } else { 0 });
+        // Create the raw packet (the two reserved bytes are filled by the layout)
+        let raw_packet = commands::ALARM.builder()
+            .u8("number", self.number)
+            .u8("hour", self.hour)
+            .u8("minute", self.minute)
+            .bytes("message", self.message.as_bytes())
+            .bool("audible", self.audible)
+            .build()
+            .expect("ALARM fields match layout");
         
         // Apply CRC wrapping
         crc_packets_wrapper::wrap_packets_with_crc(vec![raw_packet])
     }
 }
 
+impl TryFrom<&crate::models::Alarm> for Alarm {
+    type Error = InvalidAlarmTime;
+
+    fn try_from(alarm: &crate::models::Alarm) -> Result<Self, Self::Error> {
+        alarm.time()?;
+
+        Ok(Alarm {
+            number: alarm.number,
+            audible: alarm.audible,
+            hour: alarm.hour,
+            minute: alarm.minute,
+            message: CharString::new(&alarm.message, true),
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,227 @@
+//! Command registry for Protocol 3
+//!
+//! This module declares the layout of every command packet sent by the
+//! Protocol 3 models.
+
+use crate::helpers::command_packet::{
+    CommandLayout, CommandRegistry, FieldKind, FieldLayout, Opcode,
+};
+
+/// Protocol version sent in the start command
+pub const PROTOCOL_VERSION: u8 = 3;
+
+/// Subtype of load sections holding EEPROM data
+pub const SECTION_EEPROM: u8 = 0x01;
+
+/// Subtype of load sections holding a wrist app
+pub const SECTION_WRIST_APP: u8 = 0x02;
+
+/// Subtype of load sections holding a sound theme
+pub const SECTION_SOUND_THEME: u8 = 0x03;
+
+/// Start of a data transfer
+pub static START: CommandLayout = CommandLayout {
+    name: "START",
+    prefix: &[Opcode::Start as u8, 0x00, 0x00, PROTOCOL_VERSION],
+    fields: &[],
+};
+
+/// End of a data transfer
+pub static END: CommandLayout = CommandLayout {
+    name: "END",
+    prefix: &[Opcode::End as u8],
+    fields: &[],
+};
+
+/// Time and time zone settings
+pub static TIME: CommandLayout = CommandLayout {
+    name: "TIME",
+    prefix: &[Opcode::Time as u8],
+    fields: &[
+        FieldLayout::new("zone", FieldKind::U8),
+        FieldLayout::new("second", FieldKind::U8),
+        FieldLayout::new("hour", FieldKind::U8),
+        FieldLayout::new("minute", FieldKind::U8),
+        FieldLayout::new("month", FieldKind::U8),
+        FieldLayout::new("day", FieldKind::U8),
+        FieldLayout::new("year", FieldKind::U8),
+        FieldLayout::new("name", FieldKind::Chars(3)),
+        FieldLayout::new("weekday", FieldKind::U8),
+        FieldLayout::new("format", FieldKind::U8),
+        FieldLayout::new("date_format", FieldKind::U8),
+    ],
+};
+
+/// Alarm settings
+pub static ALARM: CommandLayout = CommandLayout {
+    name: "ALARM",
+    prefix: &[Opcode::Alarm as u8],
+    fields: &[
+        FieldLayout::new("number", FieldKind::U8),
+        FieldLayout::new("hour", FieldKind::U8),
+        FieldLayout::new("minute", FieldKind::U8),
+        FieldLayout::new("reserved", FieldKind::Reserved(2)),
+        FieldLayout::new("message", FieldKind::Chars(8)),
+        FieldLayout::new("audible", FieldKind::Bool),
+    ],
+};
+
+/// Hourly chime and button beep settings
+pub static BEEPS: CommandLayout = CommandLayout {
+    name: "BEEPS",
+    prefix: &[Opcode::Beeps as u8],
+    fields: &[
+        FieldLayout::new("hourly_chime", FieldKind::Bool),
+        FieldLayout::new("button_beep", FieldKind::Bool),
+    ],
+};
+
+/// Clear the EEPROM area
+pub static EEPROM_CLEAR: CommandLayout = CommandLayout {
+    name: "CLEAR EEPROM",
+    prefix: &[Opcode::Clear as u8, SECTION_EEPROM],
+    fields: &[],
+};
+
+/// Start of the EEPROM load section
+pub static EEPROM_SECT: CommandLayout = CommandLayout {
+    name: "SECT EEPROM",
+    prefix: &[Opcode::Sect as u8, SECTION_EEPROM],
+    fields: &[
+        FieldLayout::new("packets", FieldKind::U8),
+        FieldLayout::new("appointments_address", FieldKind::U16),
+        FieldLayout::new("lists_address", FieldKind::U16),
+        FieldLayout::new("phone_numbers_address", FieldKind::U16),
+        FieldLayout::new("anniversaries_address", FieldKind::U16),
+        FieldLayout::new("appointments_count", FieldKind::U8),
+        FieldLayout::new("lists_count", FieldKind::U8),
+        FieldLayout::new("phone_numbers_count", FieldKind::U8),
+        FieldLayout::new("anniversaries_count", FieldKind::U8),
+        FieldLayout::new("earliest_year", FieldKind::U8),
+        FieldLayout::new("notification", FieldKind::U8),
+    ],
+};
+
+/// EEPROM load section payload
+pub static EEPROM_DATA: CommandLayout = CommandLayout {
+    name: "DATA EEPROM",
+    prefix: &[Opcode::Data as u8, SECTION_EEPROM],
+    fields: &[
+        FieldLayout::new("index", FieldKind::U8),
+        FieldLayout::new("payload", FieldKind::Tail),
+    ],
+};
+
+/// End of the EEPROM load section
+pub static EEPROM_END: CommandLayout = CommandLayout {
+    name: "END_SECT EEPROM",
+    prefix: &[Opcode::EndSect as u8, SECTION_EEPROM],
+    fields: &[],
+};
+
+/// Clear the wrist app area
+pub static WRIST_APP_CLEAR: CommandLayout = CommandLayout {
+    name: "CLEAR WRIST_APP",
+    prefix: &[Opcode::Clear as u8, SECTION_WRIST_APP],
+    fields: &[],
+};
+
+/// Start of the wrist app load section
+pub static WRIST_APP_SECT: CommandLayout = CommandLayout {
+    name: "SECT WRIST_APP",
+    prefix: &[Opcode::Sect as u8, SECTION_WRIST_APP],
+    fields: &[
+        FieldLayout::new("packets", FieldKind::U8),
+        FieldLayout::new("flag", FieldKind::U8),
+    ],
+};
+
+/// Wrist app load section payload
+pub static WRIST_APP_DATA: CommandLayout = CommandLayout {
+    name: "DATA WRIST_APP",
+    prefix: &[Opcode::Data as u8, SECTION_WRIST_APP],
+    fields: &[
+        FieldLayout::new("index", FieldKind::U8),
+        FieldLayout::new("payload", FieldKind::Tail),
+    ],
+};
+
+/// End of the wrist app load section
+pub static WRIST_APP_END: CommandLayout = CommandLayout {
+    name: "END_SECT WRIST_APP",
+    prefix: &[Opcode::EndSect as u8, SECTION_WRIST_APP],
+    fields: &[],
+};
+
+/// Start of the sound theme load section
+pub static SOUND_THEME_SECT: CommandLayout = CommandLayout {
+    name: "SECT SOUND_THEME",
+    prefix: &[Opcode::Sect as u8, SECTION_SOUND_THEME],
+    fields: &[
+        FieldLayout::new("packets", FieldKind::U8),
+        FieldLayout::new("offset", FieldKind::U8),
+    ],
+};
+
+/// Sound theme load section payload
+pub static SOUND_THEME_DATA: CommandLayout = CommandLayout {
+    name: "DATA SOUND_THEME",
+    prefix: &[Opcode::Data as u8, SECTION_SOUND_THEME],
+    fields: &[
+        FieldLayout::new("index", FieldKind::U8),
+        FieldLayout::new("payload", FieldKind::Tail),
+    ],
+};
+
+/// End of the sound theme load section
+pub static SOUND_THEME_END: CommandLayout = CommandLayout {
+    name: "END_SECT SOUND_THEME",
+    prefix: &[Opcode::EndSect as u8, SECTION_SOUND_THEME],
+    fields: &[],
+};
+
+/// All commands understood by Protocol 3 watches
+pub static COMMANDS: CommandRegistry = CommandRegistry {
+    commands: &[
+        &START,
+        &END,
+        &TIME,
+        &ALARM,
+        &BEEPS,
+        &EEPROM_CLEAR,
+        &EEPROM_SECT,
+        &EEPROM_DATA,
+        &EEPROM_END,
+        &WRIST_APP_CLEAR,
+        &WRIST_APP_SECT,
+        &WRIST_APP_DATA,
+        &WRIST_APP_END,
+        &SOUND_THEME_SECT,
+        &SOUND_THEME_DATA,
+        &SOUND_THEME_END,
+    ],
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::command_packet::FieldValue;
+
+    #[test]
+    fn test_identify() {
+        assert_eq!(COMMANDS.identify(&[0x20, 0x00, 0x00, 0x03]).map(|c| c.name), Some("START"));
+        assert_eq!(COMMANDS.identify(&[0x91, 0x02, 0x01, 0xff]).map(|c| c.name), Some("DATA WRIST_APP"));
+        assert_eq!(COMMANDS.identify(&[0x20, 0x00, 0x00, 0x04]), None);
+    }
+
+    #[test]
+    fn test_decode_eeprom_sect() {
+        // From golden fixture: appointment.jsonl (without CRC framing)
+        let payload = [144, 1, 1, 2, 54, 2, 73, 2, 73, 2, 73, 1, 0, 0, 0, 22, 255];
+        let decoded = COMMANDS.decode(&payload).unwrap();
+
+        assert_eq!(decoded.layout.name, "SECT EEPROM");
+        assert_eq!(decoded.get("appointments_address"), Some(&FieldValue::U16(0x0236)));
+        assert_eq!(decoded.get("earliest_year"), Some(&FieldValue::U8(22)));
+    }
+}
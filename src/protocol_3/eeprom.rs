@@ -3,9 +3,10 @@
 //! This module handles EEPROM data storage for Timex Datalink watches.
 
 use crate::PacketGenerator;
-use crate::helpers::crc_packets_wrapper;
-use crate::helpers::cpacket_paginator;
-use chrono::{DateTime, Utc, Datelike};
+use crate::helpers::load_section::LoadSection;
+use crate::protocol_3::commands;
+use crate::helpers::appointment_time::{self, QuantizationWarning};
+use chrono::Datelike;
 
 pub mod anniversary;
 pub mod appointment;
@@ -16,6 +17,7 @@ pub use anniversary::Anniversary;
 pub use appointment::Appointment;
 pub use list::List;
 pub use phone_number::PhoneNumber;
+pub use crate::models::NotificationMinutes;
 
 // Common trait for all EEPROM models in Protocol 3
 pub trait EepromModel {
@@ -39,8 +41,8 @@ pub struct Eeprom {
     /// List of list items
     pub lists: Vec<List>,
     
-    /// Appointment notification minutes (in 5-minute increments from 0 to 30, or None)
-    pub appointment_notification_minutes: Option<u8>,
+    /// Appointment notification minutes
+    pub appointment_notification_minutes: Option<NotificationMinutes>,
 }
 
 impl Eeprom {
@@ -98,6 +100,13 @@ impl Eeprom {
         addresses
     }
     
+    // Helper to order anniversaries by month and day, as the watch expects
+    fn sorted_anniversaries(&self) -> Vec<&Anniversary> {
+        let mut anniversaries: Vec<&Anniversary> = self.anniversaries.iter().collect();
+        anniversaries.sort_by_key(|anniversary| (anniversary.month, anniversary.day));
+        anniversaries
+    }
+    
     // Helper to get item counts
     fn items_lengths(&self) -> Vec<u8> {
         vec![
@@ -110,29 +119,10 @@ impl Eeprom {
     
     // Helper to find the earliest appointment year
     fn earliest_appointment_year(&self) -> u8 {
-        if self.appointments.is_empty() {
-            return 0;
-        }
-        
-        let mut earliest_year = 99; // Initialize to a high value
-        
-        for appt in &self.appointments {
-            let duration = appt.time
-                .duration_since(std::time::UNIX_EPOCH)
-                .expect("Time went backwards");
-                
-            let dt = DateTime::<Utc>::from_timestamp(
-                duration.as_secs() as i64,
-                0
-            ).expect("Invalid timestamp");
-            
-            let year = (dt.year() % 100) as u8;
-            if year < earliest_year {
-                earliest_year = year;
-            }
-        }
-        
-        earliest_year
+        self.appointments.iter()
+            .map(|appt| (appt.displayed_time().year() % 100) as u8)
+            .min()
+            .unwrap_or(0)
     }
     
     // Helper to convert appointment notification minutes to value
@@ -140,28 +130,27 @@ impl Eeprom {
         const APPOINTMENT_NO_NOTIFICATION: u8 = 0xff;
         
         match self.appointment_notification_minutes {
-            Some(minutes) => minutes / 5,
+            Some(minutes) => minutes.value(),
             None => APPOINTMENT_NO_NOTIFICATION,
         }
     }
+    
+    /// Report every appointment whose displayed time differs from its requested time
+    ///
+    /// The watch stores appointment times in 15-minute slots, so times are
+    /// rounded according to each appointment's rounding policy.
+    pub fn appointment_quantization_report(&self) -> Vec<QuantizationWarning> {
+        self.appointments.iter()
+            .enumerate()
+            .filter_map(|(index, appt)| {
+                appointment_time::quantization_warning(index, &appt.message, appt.time, appt.rounding)
+            })
+            .collect()
+    }
 }
 
 impl PacketGenerator for Eeprom {
     fn packets(&self) -> Vec<Vec<u8>> {
-        // Define constants from Ruby implementation
-        const CPACKET_CLEAR: [u8; 2] = [0x93, 0x01];
-        const CPACKET_SECT: [u8; 2] = [0x90, 0x01];
-        const CPACKET_DATA: [u8; 2] = [0x91, 0x01];
-        const CPACKET_END: [u8; 2] = [0x92, 0x01];
-        const CPACKET_DATA_LENGTH: usize = 32;
-        
-        // Create the clear packet
-        let clear_packet = CPACKET_CLEAR.to_vec();
-        
-        // Create header packet
-        let mut header = Vec::with_capacity(16);
-        header.extend_from_slice(&CPACKET_SECT);
-        
         // All models combined into a single byte array
         let mut all_packets = Vec::new();
         for item in &self.appointments {
@@ -173,36 +162,18 @@ impl PacketGenerator for Eeprom {
         for item in &self.phone_numbers {
             all_packets.extend(item.packet());
         }
-        for item in &self.anniversaries {
+        for item in self.sorted_anniversaries() {
             all_packets.extend(item.packet());
         }
         
-        // Calculate payloads
-        let payloads = cpacket_paginator::paginate_cpackets(
-            &CPACKET_DATA,
-            CPACKET_DATA_LENGTH,
-            &all_packets
-        );
-        
-        // Continue building header
-        header.push(payloads.len() as u8);
+        // Header fields following the payload packet count
+        let mut header = Vec::with_capacity(14);
         header.extend(self.items_addresses());
         header.extend(self.items_lengths());
         header.push(self.earliest_appointment_year());
         header.push(self.appointment_notification_minutes_value());
         
-        // Create the end packet
-        let end_packet = CPACKET_END.to_vec();
-        
-        // Combine all packets
-        let mut all_packets = Vec::with_capacity(2 + payloads.len() + 1);
-        all_packets.push(clear_packet);
-        all_packets.push(header);
-        all_packets.extend(payloads);
-        all_packets.push(end_packet);
-        
-        // Wrap with CRC
-        crc_packets_wrapper::wrap_packets_with_crc(all_packets)
+        LoadSection::new(commands::SECTION_EEPROM, true, header, all_packets).packets()
     }
 }
 
@@ -215,24 +186,14 @@ impl Default for Eeprom {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::TimeZone;
-    use std::time::SystemTime;
+    use chrono::NaiveDateTime;
     
-    // Helper function to create a SystemTime from date components
-    fn system_time_from_date(year: i32, month: u32, day: u32, hour: u32, min: u32) -> SystemTime {
-        let naive_dt = chrono::NaiveDate::from_ymd_opt(year, month, day)
+    // Helper function to create a wall-clock time from date components
+    fn datetime(year: i32, month: u32, day: u32, hour: u32, min: u32) -> NaiveDateTime {
+        chrono::NaiveDate::from_ymd_opt(year, month, day)
             .unwrap()
             .and_hms_opt(hour, min, 0)
-            .unwrap();
-        
-        let dt = Utc.from_utc_datetime(&naive_dt);
-        
-        if dt.timestamp() < 0 {
-            // For dates before 1970
-            SystemTime::UNIX_EPOCH
-        } else {
-            SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(dt.timestamp() as u64)
-        }
+            .unwrap()
     }
     
     #[test]
@@ -249,7 +210,7 @@ mod tests {
         
         // Add an appointment
         eeprom.appointments.push(Appointment::new(
-            system_time_from_date(2022, 10, 31, 19, 0),
+            datetime(2022, 10, 31, 19, 0),
             "Scare the neighbors".to_string()
         ));
         
@@ -274,9 +235,10 @@ mod tests {
         
         // Add an anniversary
         eeprom.anniversaries.push(Anniversary::new(
-            system_time_from_date(1985, 7, 3, 0, 0),
+            7,
+            3,
             "Release of Back to the Future".to_string()
-        ));
+        ).unwrap());
         
         // Generate the packets
         let packets = eeprom.packets();
@@ -343,17 +305,39 @@ mod tests {
         
         assert_eq!(packets, expected, "EEPROM list packets don't match golden fixture");
     }
-    
+
+    #[test]
+    fn test_eeprom_sorts_anniversaries() {
+        let mut eeprom = Eeprom::new();
+
+        eeprom.anniversaries.push(Anniversary::new(7, 3, "Back to the Future".to_string()).unwrap());
+        eeprom.anniversaries.push(Anniversary::new(2, 29, "Leap day".to_string()).unwrap());
+        eeprom.anniversaries.push(Anniversary::new(4, 6, "2001".to_string()).unwrap());
+
+        let packets = eeprom.packets();
+        let section = crate::helpers::load_section::LoadSection::parse(&packets).unwrap();
+
+        // Walk the length-prefixed anniversary packets in the payload
+        let mut dates = Vec::new();
+        let mut rest = section.payload.as_slice();
+        while let Some(&length) = rest.first() {
+            dates.push((rest[1], rest[2]));
+            rest = &rest[length as usize..];
+        }
+
+        assert_eq!(dates, vec![(2, 29), (4, 6), (7, 3)]);
+    }
+
     #[test]
     fn test_eeprom_with_notification() {
         let mut eeprom = Eeprom::new();
         
-        // Set notification minutes (must be multiple of 5)
-        eeprom.appointment_notification_minutes = Some(15);
+        // Set notification minutes
+        eeprom.appointment_notification_minutes = Some(NotificationMinutes::FifteenMinutes);
         
         // Add an appointment
         eeprom.appointments.push(Appointment::new(
-            system_time_from_date(2022, 10, 31, 19, 0),
+            datetime(2022, 10, 31, 19, 0),
             "Scare the neighbors".to_string()
         ));
         
@@ -363,4 +347,17 @@ mod tests {
         // Verify there are multiple packets
         assert!(packets.len() > 1);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_appointment_quantization_report() {
+        let mut eeprom = Eeprom::new();
+        
+        eeprom.appointments.push(Appointment::new(datetime(2022, 10, 31, 19, 0), "On time".to_string()));
+        eeprom.appointments.push(Appointment::new(datetime(2022, 10, 31, 19, 10), "Late".to_string()));
+        
+        let report = eeprom.appointment_quantization_report();
+        assert_eq!(report.len(), 1);
+        assert_eq!(report[0].index, 1);
+        assert_eq!(report[0].displayed, datetime(2022, 10, 31, 19, 0));
+    }
+}
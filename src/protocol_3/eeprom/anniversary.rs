@@ -2,58 +2,74 @@
 //!
 //! This module handles anniversary data for Timex Datalink watches.
 
-use std::time::SystemTime;
-use chrono::{DateTime, Utc, Datelike};
+use chrono::NaiveDate;
 use crate::char_encoders::EepromString;
+use crate::models;
 use crate::protocol_3::eeprom::EepromModel;
 
+pub use crate::models::{validate_month_day, InvalidAnniversaryDate};
+
 /// Anniversary structure for Protocol 3
 ///
 /// This represents an anniversary to be stored in the watch's EEPROM.
+/// Anniversaries recur every year, so only the month and day are stored.
 pub struct Anniversary {
-    /// Date of the anniversary
-    pub time: SystemTime,
-    
+    /// Month of the anniversary (1-12)
+    pub month: u8,
+
+    /// Day of the anniversary (1-31, February 29 is allowed)
+    pub day: u8,
+
     /// Anniversary text (max 31 chars)
     pub anniversary: String,
 }
 
 impl Anniversary {
-    /// Create a new Anniversary
-    pub fn new(time: SystemTime, anniversary: String) -> Self {
-        Anniversary { time, anniversary }
+    /// Create a new Anniversary from a month and day
+    ///
+    /// Returns an error if the day does not exist in the month.
+    pub fn new(month: u8, day: u8, anniversary: String) -> Result<Self, InvalidAnniversaryDate> {
+        models::Anniversary::new(month, day, anniversary).map(Self::from)
+    }
+
+    /// Create a new Anniversary recurring on the month and day of a date
+    pub fn from_date(date: NaiveDate, anniversary: String) -> Self {
+        Self::from(models::Anniversary::from_date(date, anniversary))
+    }
+}
+
+impl From<models::Anniversary> for Anniversary {
+    fn from(anniversary: models::Anniversary) -> Self {
+        Anniversary {
+            month: anniversary.month,
+            day: anniversary.day,
+            anniversary: anniversary.anniversary,
+        }
+    }
+}
+
+impl From<&models::Anniversary> for Anniversary {
+    fn from(anniversary: &models::Anniversary) -> Self {
+        Self::from(anniversary.clone())
     }
 }
 
 impl EepromModel for Anniversary {
     fn packet(&self) -> Vec<u8> {
-        let duration = self.time
-            .duration_since(std::time::UNIX_EPOCH)
-            .expect("Time went backwards");
-            
-        let dt = DateTime::<Utc>::from_timestamp(
-            duration.as_secs() as i64,
-            0
-        ).expect("Invalid timestamp");
-        
-        // Encode the anniversary date
-        let month = dt.month() as u8;
-        let day = dt.day() as u8;
-        
         // Encode the anniversary text
         let anniversary_bytes = EepromString::new(&self.anniversary).as_bytes().to_vec();
-        
+
         // Combine the data
         let mut data = Vec::with_capacity(2 + anniversary_bytes.len());
-        data.push(month);
-        data.push(day);
+        data.push(self.month);
+        data.push(self.day);
         data.extend(anniversary_bytes);
-        
+
         // Add packet length byte at the beginning
         let mut packet = Vec::with_capacity(data.len() + 1);
         packet.push((data.len() + 1) as u8); // +1 for the length byte itself
         packet.extend(data);
-        
+
         packet
     }
 }
@@ -61,35 +77,43 @@ impl EepromModel for Anniversary {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::TimeZone;
-    
-    // Helper function to create a SystemTime from date components
-    fn system_time_from_date(year: i32, month: u32, day: u32) -> SystemTime {
-        let naive_dt = chrono::NaiveDate::from_ymd_opt(year, month, day)
-            .unwrap()
-            .and_hms_opt(0, 0, 0)
-            .unwrap();
-        
-        let dt = Utc.from_utc_datetime(&naive_dt);
-        
-        SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(dt.timestamp() as u64)
-    }
-    
+
     #[test]
     fn test_anniversary_packet() {
         let anniversary = Anniversary::new(
-            system_time_from_date(1985, 7, 3),
+            7,
+            3,
             "Release of Back to the Future".to_string()
-        );
-        
+        ).unwrap();
+
         // The exact bytes depend on the EepromString encoding, but we can at least
         // check the first few bytes which we know the exact values of
         let packet = anniversary.packet();
-        
+
         // Check the structure (don't check the encoded message since it's tested in the EepromString tests)
         assert!(!packet.is_empty());
         assert_eq!(packet[0], packet.len() as u8); // Length byte is correct
         assert_eq!(packet[1], 7); // Month - July
         assert_eq!(packet[2], 3); // Day - 3
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_anniversary_dates() {
+        // February 29 recurs on leap years
+        let leap_day = Anniversary::new(2, 29, "Leap day".to_string()).unwrap();
+        assert_eq!((leap_day.month, leap_day.day), (2, 29));
+
+        let from_date = Anniversary::from_date(
+            NaiveDate::from_ymd_opt(1968, 4, 6).unwrap(),
+            "Release of 2001".to_string()
+        );
+        assert_eq!((from_date.month, from_date.day), (4, 6));
+
+        assert_eq!(
+            Anniversary::new(2, 30, "Nope".to_string()).err(),
+            Some(InvalidAnniversaryDate { month: 2, day: 30 })
+        );
+        assert!(Anniversary::new(13, 1, "Nope".to_string()).is_err());
+        assert!(Anniversary::new(4, 0, "Nope".to_string()).is_err());
+    }
+}
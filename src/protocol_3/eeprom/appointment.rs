@@ -3,57 +3,70 @@
 //! This module handles appointment data for Timex Datalink watches.
 
 use std::time::SystemTime;
-use chrono::{DateTime, Utc, Datelike, Timelike};
+use chrono::{DateTime, Datelike, NaiveDateTime, TimeZone};
 use crate::char_encoders::EepromString;
+use crate::helpers::appointment_time::{self, RoundingPolicy};
 use crate::protocol_3::eeprom::EepromModel;
 
 /// Appointment structure for Protocol 3
 ///
 /// This represents an appointment to be stored in the watch's EEPROM.
 pub struct Appointment {
-    /// Time of the appointment
-    pub time: SystemTime,
+    /// Wall-clock time of the appointment, as shown by the watch
+    pub time: NaiveDateTime,
     
     /// Message text for the appointment (max 31 chars)
     pub message: String,
+    
+    /// How the time is rounded into the watch's 15-minute slots
+    pub rounding: RoundingPolicy,
 }
 
 impl Appointment {
-    /// Create a new Appointment
-    pub fn new(time: SystemTime, message: String) -> Self {
-        Appointment { time, message }
+    /// Create a new Appointment at a wall-clock time
+    pub fn new(time: NaiveDateTime, message: String) -> Self {
+        Appointment { time, message, rounding: RoundingPolicy::default() }
     }
     
-    // Helper to convert the time to 15-minute intervals
-    fn time_15m(&self) -> u8 {
-        let duration = self.time
-            .duration_since(std::time::UNIX_EPOCH)
-            .expect("Time went backwards");
-            
-        let dt = DateTime::<Utc>::from_timestamp(
-            duration.as_secs() as i64,
-            0
-        ).expect("Invalid timestamp");
-        
-        (dt.hour() * 4 + dt.minute() / 15) as u8
+    /// Create a new Appointment from a timezone-aware datetime
+    ///
+    /// The appointment is shown at the datetime's local time in its zone.
+    pub fn from_datetime<Tz: TimeZone>(time: &DateTime<Tz>, message: String) -> Self {
+        Self::new(appointment_time::wall_clock(time), message)
+    }
+    
+    /// Create a new Appointment from an instant, shown in the given time zone
+    pub fn from_system_time<Tz: TimeZone>(time: SystemTime, zone: &Tz, message: String) -> Self {
+        Self::new(appointment_time::wall_clock_in(time, zone), message)
+    }
+    
+    /// Set the rounding policy
+    pub fn with_rounding(mut self, rounding: RoundingPolicy) -> Self {
+        self.rounding = rounding;
+        self
+    }
+    
+    /// Wall-clock time shown by the watch after rounding
+    pub fn displayed_time(&self) -> NaiveDateTime {
+        appointment_time::quantize(self.time, self.rounding)
+    }
+}
+
+impl From<&crate::models::Appointment> for Appointment {
+    fn from(appointment: &crate::models::Appointment) -> Self {
+        Appointment::new(appointment.time, appointment.message.clone())
+            .with_rounding(appointment.rounding)
     }
 }
 
 impl EepromModel for Appointment {
     fn packet(&self) -> Vec<u8> {
-        let duration = self.time
-            .duration_since(std::time::UNIX_EPOCH)
-            .expect("Time went backwards");
-            
-        let dt = DateTime::<Utc>::from_timestamp(
-            duration.as_secs() as i64,
-            0
-        ).expect("Invalid timestamp");
+        let displayed = self.displayed_time();
         
         // Encode the appointment
-        let month = dt.month() as u8;
-        let day = dt.day() as u8;
-        let time_15m = self.time_15m();
+        let month = displayed.month() as u8;
+        let day = displayed.day() as u8;
+        let time_15m = appointment_time::slot(displayed);
         
         // Encode the message
         let message_bytes = EepromString::new(&self.message).as_bytes().to_vec();
@@ -77,24 +90,19 @@ impl EepromModel for Appointment {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::TimeZone;
+    use chrono::NaiveDate;
     
-    // Helper function to create a SystemTime from date components
-    fn system_time_from_date(year: i32, month: u32, day: u32, hour: u32, min: u32) -> SystemTime {
-        let naive_dt = chrono::NaiveDate::from_ymd_opt(year, month, day)
+    fn datetime(year: i32, month: u32, day: u32, hour: u32, min: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(year, month, day)
             .unwrap()
             .and_hms_opt(hour, min, 0)
-            .unwrap();
-        
-        let dt = Utc.from_utc_datetime(&naive_dt);
-        
-        SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(dt.timestamp() as u64)
+            .unwrap()
     }
     
     #[test]
     fn test_appointment_packet() {
         let appointment = Appointment::new(
-            system_time_from_date(2022, 10, 31, 19, 0),
+            datetime(2022, 10, 31, 19, 0),
             "Scare the neighbors".to_string()
         );
         
@@ -109,4 +117,28 @@ mod tests {
         assert_eq!(packet[2], 31); // Day - 31
         assert_eq!(packet[3], 19 * 4); // Time - 19:00 = 19*4 + 0 = 76
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_appointment_rounding() {
+        let appointment = Appointment::new(
+            datetime(2022, 10, 31, 23, 50),
+            "Midnight snack".to_string()
+        ).with_rounding(RoundingPolicy::Ceil);
+        
+        // Rounding up past midnight moves the appointment to the next day
+        let packet = appointment.packet();
+        assert_eq!(&packet[1..4], &[11, 1, 0]);
+    }
+    
+    #[test]
+    fn test_appointment_in_zone() {
+        let instant = chrono::Utc.with_ymd_and_hms(2022, 11, 1, 2, 0, 0).unwrap();
+        let appointment = Appointment::from_system_time(
+            instant.into(),
+            &chrono_tz::America::Los_Angeles,
+            "Scare the neighbors".to_string()
+        );
+        
+        assert_eq!(appointment.time, datetime(2022, 10, 31, 19, 0));
+    }
+}
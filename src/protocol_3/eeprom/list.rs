@@ -23,7 +23,7 @@ impl List {
     pub fn new(list_entry: String, priority: Option<u8>) -> Self {
         // Validate priority (should be 1-5 or None)
         if let Some(p) = priority {
-            assert!(p >= 1 && p <= 5, "Priority must be between 1 and 5");
+            assert!((1..=5).contains(&p), "Priority must be between 1 and 5");
         }
         
         List { list_entry, priority }
@@ -35,6 +35,12 @@ impl List {
     }
 }
 
+impl From<&crate::models::ListEntry> for List {
+    fn from(entry: &crate::models::ListEntry) -> Self {
+        List::new(entry.entry.clone(), entry.priority.map(|priority| priority.value()))
+    }
+}
+
 impl EepromModel for List {
     fn packet(&self) -> Vec<u8> {
         // Get priority value
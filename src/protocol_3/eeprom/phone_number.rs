@@ -32,6 +32,16 @@ impl PhoneNumber {
     }
 }
 
+impl From<&crate::models::PhoneEntry> for PhoneNumber {
+    fn from(entry: &crate::models::PhoneEntry) -> Self {
+        PhoneNumber::new(
+            entry.name.clone(),
+            entry.number.clone(),
+            Some(entry.phone_type.code().to_string())
+        )
+    }
+}
+
 impl EepromModel for PhoneNumber {
     fn packet(&self) -> Vec<u8> {
         // Encode number_with_type as in Ruby
@@ -4,6 +4,7 @@
 
 use crate::PacketGenerator;
 use crate::helpers::crc_packets_wrapper;
+use crate::protocol_3::commands;
 
 /// End structure for Protocol 3
 ///
@@ -12,11 +13,12 @@ pub struct End;
 
 impl PacketGenerator for End {
     fn packets(&self) -> Vec<Vec<u8>> {
-        // Define constants matching Ruby implementation
-        const CPACKET_SKIP: [u8; 1] = [0x21];
-        
+        let raw_packet = commands::END.builder()
+            .build()
+            .expect("END layout has no fields");
+
         // Wrap packets with CRC
-        crc_packets_wrapper::wrap_packets_with_crc(vec![CPACKET_SKIP.to_vec()])
+        crc_packets_wrapper::wrap_packets_with_crc(vec![raw_packet])
     }
 }
 
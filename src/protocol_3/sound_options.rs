@@ -4,6 +4,7 @@
 
 use crate::PacketGenerator;
 use crate::helpers::crc_packets_wrapper;
+use crate::protocol_3::commands;
 
 /// Sound Options structure for Protocol 3
 ///
@@ -18,20 +19,27 @@ pub struct SoundOptions {
 
 impl PacketGenerator for SoundOptions {
     fn packets(&self) -> Vec<Vec<u8>> {
-        // Define constants from Ruby implementation
-        const CPACKET_BEEPS: u8 = 0x71;
-
         // Create the raw packet
-        let mut raw_packet = Vec::with_capacity(3);
-        raw_packet.push(CPACKET_BEEPS);
-        raw_packet.push(if self.hourly_chime { 1 } else { 0 });
-        raw_packet.push(if self.button_beep { 1 } else { 0 });
+        let raw_packet = commands::BEEPS.builder()
+            .bool("hourly_chime", self.hourly_chime)
+            .bool("button_beep", self.button_beep)
+            .build()
+            .expect("BEEPS fields match layout");
         
         // Apply CRC wrapping
         crc_packets_wrapper::wrap_packets_with_crc(vec![raw_packet])
     }
 }
 
+impl From<&crate::models::SoundOptions> for SoundOptions {
+    fn from(options: &crate::models::SoundOptions) -> Self {
+        SoundOptions {
+            hourly_chime: options.hourly_chime,
+            button_beep: options.button_beep,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2,80 +2,68 @@
 //!
 //! This module handles sound theme functionality for Timex Datalink watches.
 
-use std::fs;
 use std::io;
 use std::path::Path;
 use crate::PacketGenerator;
-use crate::helpers::crc_packets_wrapper;
-use crate::helpers::cpacket_paginator;
+use crate::helpers::load_section::LoadSection;
+use crate::protocol_3::commands;
+use crate::spc::{Spc, SpcError};
 
 /// Sound Theme structure for Protocol 3
 ///
 /// This allows loading sound themes from SPC files or raw data.
 pub struct SoundTheme {
-    /// The sound theme data bytes
-    pub sound_theme_data: Vec<u8>,
+    /// The validated sound theme
+    spc: Spc,
 }
 
 impl SoundTheme {
     /// Create a new SoundTheme instance from raw data
-    pub fn new(sound_theme_data: Vec<u8>) -> Self {
-        SoundTheme { sound_theme_data }
+    ///
+    /// # Arguments
+    ///
+    /// * `sound_theme_data` - The sound data, with or without the SPC header
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the sound data is empty or does not fit the
+    /// watch's sound area
+    pub fn new(sound_theme_data: Vec<u8>) -> Result<Self, SpcError> {
+        Ok(SoundTheme::from_spc(&Spc::from_bytes(&sound_theme_data)?))
+    }
+    
+    /// Create a new SoundTheme instance from a parsed SPC file
+    pub fn from_spc(spc: &Spc) -> Self {
+        SoundTheme { spc: spc.clone() }
+    }
+
+    /// Sound data loaded onto the watch (without SPC header)
+    pub fn sound_data(&self) -> &[u8] {
+        self.spc.sound_data()
     }
     
     /// Create a new SoundTheme instance from an SPC file
+    ///
+    /// Only the length of the sound data is validated, the sound data itself
+    /// is loaded as-is.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or its sound data does not
+    /// fit the watch's sound area
     pub fn from_spc_file<P: AsRef<Path>>(spc_file: P) -> io::Result<Self> {
-        // Define the header that needs to be removed
-        const SOUND_DATA_HEADER: &[u8] = &[0x25, 0x04, 0x19, 0x69];
-        
-        // Read the file
-        let data = fs::read(spc_file)?;
-        
-        // Remove the header if present
-        let sound_data = if data.starts_with(SOUND_DATA_HEADER) {
-            data[SOUND_DATA_HEADER.len()..].to_vec()
-        } else {
-            data
-        };
-        
-        Ok(SoundTheme::new(sound_data))
+        Ok(SoundTheme::from_spc(&Spc::read(spc_file)?))
     }
 }
 
 impl PacketGenerator for SoundTheme {
     fn packets(&self) -> Vec<Vec<u8>> {
-        // Define constants from Ruby implementation
-        const CPACKET_SECT: [u8; 2] = [0x90, 0x03];
-        const CPACKET_DATA: [u8; 2] = [0x91, 0x03];
-        const CPACKET_END: [u8; 2] = [0x92, 0x03];
-        const CPACKET_DATA_LENGTH: usize = 32;
-        
-        // Paginate the data into chunks
-        let payloads = cpacket_paginator::paginate_cpackets(
-            &CPACKET_DATA, 
-            CPACKET_DATA_LENGTH, 
-            &self.sound_theme_data
-        );
-        
-        // Calculate offset as in Ruby implementation
-        let offset = 0x100 - self.sound_theme_data.len();
-        
-        // Create the section header packet
-        let mut load_sect = Vec::with_capacity(4);
-        load_sect.extend_from_slice(&CPACKET_SECT);
-        load_sect.push(payloads.len() as u8);
-        load_sect.push(offset as u8);
-        
-        // Create the end packet
-        let end_packet = CPACKET_END.to_vec();
-        
-        // Combine all packets and wrap with CRC
-        let mut all_packets = Vec::with_capacity(payloads.len() + 2);
-        all_packets.push(load_sect);
-        all_packets.extend(payloads);
-        all_packets.push(end_packet);
-        
-        crc_packets_wrapper::wrap_packets_with_crc(all_packets)
+        LoadSection::new(
+            commands::SECTION_SOUND_THEME,
+            false,
+            vec![self.spc.load_offset()],
+            self.spc.sound_data().to_vec()
+        ).packets()
     }
 }
 
@@ -104,4 +92,11 @@ mod tests {
 
         assert_eq!(sound_theme.packets(), expected);
     }
+
+    #[test]
+    fn test_new_validates_sound_data() {
+        assert_eq!(SoundTheme::new(vec![0x11; 3]).unwrap().sound_data(), &[0x11; 3]);
+        assert_eq!(SoundTheme::new(Vec::new()).err(), Some(SpcError::Empty));
+        assert_eq!(SoundTheme::new(vec![0x11; 0x101]).err(), Some(SpcError::TooLarge { length: 0x101 }));
+    }
 }
\ No newline at end of file
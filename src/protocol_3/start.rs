@@ -4,6 +4,7 @@
 
 use crate::PacketGenerator;
 use crate::helpers::crc_packets_wrapper;
+use crate::protocol_3::commands;
 
 /// Start structure for Protocol 3
 ///
@@ -12,11 +13,12 @@ pub struct Start;
 
 impl PacketGenerator for Start {
     fn packets(&self) -> Vec<Vec<u8>> {
-        // Define constants matching Ruby implementation
-        const CPACKET_START: [u8; 4] = [0x20, 0x00, 0x00, 0x03];
-        
+        let raw_packet = commands::START.builder()
+            .build()
+            .expect("START layout has no fields");
+
         // Wrap packets with CRC
-        crc_packets_wrapper::wrap_packets_with_crc(vec![CPACKET_START.to_vec()])
+        crc_packets_wrapper::wrap_packets_with_crc(vec![raw_packet])
     }
 }
 
@@ -5,30 +5,11 @@
 use std::time::SystemTime;
 use crate::PacketGenerator;
 use chrono::{DateTime, Utc, Timelike, Datelike};
+use crate::protocol_3::commands;
 use crate::helpers::crc_packets_wrapper;
 use crate::char_encoders::CharString;
 
-/// Date format options for Protocol 3
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum DateFormat {
-    /// Format: MM-DD-YY
-    MonthDashDayDashYear,
-    
-    /// Format: DD-MM-YY
-    DayDashMonthDashYear,
-    
-    /// Format: YY-MM-DD
-    YearDashMonthDashDay,
-    
-    /// Format: MM.DD.YY
-    MonthDotDayDotYear,
-    
-    /// Format: DD.MM.YY
-    DayDotMonthDotYear,
-    
-    /// Format: YY.MM.DD
-    YearDotMonthDotDay,
-}
+pub use crate::models::DateFormat;
 
 /// Time structure for Protocol 3
 pub struct Time {
@@ -50,9 +31,6 @@ pub struct Time {
 
 impl PacketGenerator for Time {
     fn packets(&self) -> Vec<Vec<u8>> {
-        // Define constants from Ruby implementation
-        const CPACKET_TIME: u8 = 0x32;
-
         // Convert SystemTime to DateTime to extract components
         let duration_since_epoch = self.time
             .duration_since(std::time::UNIX_EPOCH)
@@ -78,40 +56,41 @@ impl PacketGenerator for Time {
         let is_24h_value = if self.is_24h { 2 } else { 1 };
         
         // Get date format value from enum
-        let date_format_value = match self.date_format {
-            DateFormat::MonthDashDayDashYear => 0,
-            DateFormat::DayDashMonthDashYear => 1,
-            DateFormat::YearDashMonthDashDay => 2,
-            DateFormat::MonthDotDayDotYear => 4,
-            DateFormat::DayDotMonthDotYear => 5,
-            DateFormat::YearDotMonthDotDay => 6,
-        };
+        let date_format_value = self.date_format.value();
         
         // Create the raw packet
-        let mut raw_packet = Vec::with_capacity(16);
-        raw_packet.push(CPACKET_TIME);  // Time command code
-        raw_packet.push(self.zone);     // Time zone number (1 or 2) 
-        raw_packet.push(second);        // Seconds
-        raw_packet.push(hour);          // Hour
-        raw_packet.push(minute);        // Minute
-        raw_packet.push(month);         // Month
-        raw_packet.push(day);           // Day
-        raw_packet.push(year_mod_1900); // Year (modulo 100)
-        
-        // Add name characters (3 chars)
-        for &byte in self.name.as_array() {
-            raw_packet.push(byte);
-        }
-        
-        raw_packet.push(wday_from_monday); // Weekday (0 = Monday)
-        raw_packet.push(is_24h_value);     // 12/24 hour format (1=12h, 2=24h)
-        raw_packet.push(date_format_value); // Date format
+        let raw_packet = commands::TIME.builder()
+            .u8("zone", self.zone)
+            .u8("second", second)
+            .u8("hour", hour)
+            .u8("minute", minute)
+            .u8("month", month)
+            .u8("day", day)
+            .u8("year", year_mod_1900)
+            .bytes("name", self.name.as_array())
+            .u8("weekday", wday_from_monday)
+            .u8("format", is_24h_value)
+            .u8("date_format", date_format_value)
+            .build()
+            .expect("TIME fields match layout");
         
         // Apply CRC wrapping
         crc_packets_wrapper::wrap_packets_with_crc(vec![raw_packet])
     }
 }
 
+impl From<&crate::models::TimeZone> for Time {
+    fn from(time_zone: &crate::models::TimeZone) -> Self {
+        Time {
+            zone: time_zone.zone,
+            is_24h: time_zone.is_24h,
+            date_format: time_zone.date_format,
+            time: time_zone.time,
+            name: CharString::new(&time_zone.name, true),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -4,8 +4,10 @@
 
 use std::time::SystemTime;
 use crate::PacketGenerator;
-use chrono::{DateTime, Utc, Timelike};
-use crate::char_encoders::CharString;
+use chrono::{DateTime, NaiveDate, Utc, Timelike};
+use crate::char_encoders::{CharString, ALARM_MESSAGE_LENGTH};
+use crate::models::InvalidAlarmTime;
+use crate::protocol_4::commands;
 
 /// Alarm structure for Protocol 4
 pub struct Alarm {
@@ -19,14 +21,11 @@ pub struct Alarm {
     pub time: SystemTime,
     
     /// Alarm message text (max 8 characters)
-    pub message: CharString<8>,
+    pub message: CharString<ALARM_MESSAGE_LENGTH>,
 }
 
 impl PacketGenerator for Alarm {
     fn packets(&self) -> Vec<Vec<u8>> {
-        // Constants from Ruby implementation
-        const CPACKET_ALARM: u8 = 0x50;
-        
         // Extract hour and minute from SystemTime 
         // Convert SystemTime to chrono::DateTime to easily get hour and minute
         let duration_since_epoch = self.time
@@ -41,22 +40,15 @@ impl PacketGenerator for Alarm {
         let minute = datetime.minute() as u8;
         
         // Create the raw packet without CRC
-        let mut raw_packet = Vec::with_capacity(16); // Approximate capacity
-        raw_packet.push(CPACKET_ALARM);  // Alarm command
-        raw_packet.push(self.number);    // Alarm number
-        raw_packet.push(hour);           // Hour
-        raw_packet.push(minute);         // Minute
-        raw_packet.push(0);              // Two zeros as per Ruby implementation
-        raw_packet.push(0);
-        
-        // Add message characters - make sure to get the full 8 characters
-        // We need to ensure we're getting all 8 characters (padded if necessary)
-        for &byte in self.message.as_array() {
-            raw_packet.push(byte);
-        }
-        
-        // Add audible flag
-        raw_packet.push(if self.audible { 1 } else { 0 });
+        // The layout adds the two zero bytes as per Ruby implementation
+        let raw_packet = commands::ALARM.builder()
+            .u8("number", self.number)
+            .u8("hour", hour)
+            .u8("minute", minute)
+            .bytes("message", self.message.as_array())
+            .bool("audible", self.audible)
+            .build()
+            .expect("ALARM fields match layout");
         
         // Apply CRC wrapping
         use crate::helpers::crc_packets_wrapper::wrap_packets_with_crc;
@@ -64,6 +56,25 @@ impl PacketGenerator for Alarm {
     }
 }
 
+impl TryFrom<&crate::models::Alarm> for Alarm {
+    type Error = InvalidAlarmTime;
+
+    fn try_from(alarm: &crate::models::Alarm) -> Result<Self, Self::Error> {
+        // Only the hour and minute of the time are sent, read as UTC
+        let time = NaiveDate::from_ymd_opt(2000, 1, 1)
+            .expect("2000-01-01 is a valid date")
+            .and_time(alarm.time()?)
+            .and_utc();
+
+        Ok(Alarm {
+            number: alarm.number,
+            audible: alarm.audible,
+            time: time.into(),
+            message: CharString::new(&alarm.message, false),
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
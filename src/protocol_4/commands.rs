@@ -0,0 +1,57 @@
+//! Command registry for Protocol 4
+//!
+//! This module declares the layout of every command packet sent by the
+//! Protocol 4 models. Apart from the start command, Protocol 4 uses the same
+//! layouts as Protocol 3.
+
+use crate::helpers::command_packet::{CommandLayout, CommandRegistry, Opcode};
+
+pub use crate::protocol_3::commands::{
+    ALARM, BEEPS, END, EEPROM_CLEAR, EEPROM_DATA, EEPROM_END, EEPROM_SECT,
+    SECTION_EEPROM, SECTION_SOUND_THEME, SECTION_WRIST_APP, SOUND_THEME_DATA,
+    SOUND_THEME_END, SOUND_THEME_SECT, TIME, WRIST_APP_CLEAR, WRIST_APP_DATA,
+    WRIST_APP_END, WRIST_APP_SECT,
+};
+
+/// Protocol version sent in the start command
+pub const PROTOCOL_VERSION: u8 = 4;
+
+/// Start of a data transfer
+pub static START: CommandLayout = CommandLayout {
+    name: "START",
+    prefix: &[Opcode::Start as u8, 0x00, 0x00, PROTOCOL_VERSION],
+    fields: &[],
+};
+
+/// All commands understood by Protocol 4 watches
+pub static COMMANDS: CommandRegistry = CommandRegistry {
+    commands: &[
+        &START,
+        &END,
+        &TIME,
+        &ALARM,
+        &BEEPS,
+        &EEPROM_CLEAR,
+        &EEPROM_SECT,
+        &EEPROM_DATA,
+        &EEPROM_END,
+        &WRIST_APP_CLEAR,
+        &WRIST_APP_SECT,
+        &WRIST_APP_DATA,
+        &WRIST_APP_END,
+        &SOUND_THEME_SECT,
+        &SOUND_THEME_DATA,
+        &SOUND_THEME_END,
+    ],
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identify_start() {
+        assert_eq!(COMMANDS.identify(&[0x20, 0x00, 0x00, 0x04]).map(|c| c.name), Some("START"));
+        assert_eq!(COMMANDS.identify(&[0x20, 0x00, 0x00, 0x03]), None);
+    }
+}
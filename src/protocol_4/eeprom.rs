@@ -4,6 +4,9 @@
 
 use crate::PacketGenerator;
 use chrono::Datelike;
+use crate::helpers::appointment_time::{self, QuantizationWarning};
+use crate::helpers::load_section::LoadSection;
+use crate::protocol_4::commands;
 
 pub mod anniversary;
 pub mod appointment;
@@ -15,24 +18,7 @@ pub use appointment::Appointment;
 pub use phone_number::PhoneNumber;
 pub use list::List;
 
-/// Valid appointment notification minutes (0, 5, 10, 15, 20, 25, 30)
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum NotificationMinutes {
-    /// No notification
-    None,
-    /// 5 minutes before
-    FiveMinutes,
-    /// 10 minutes before
-    TenMinutes,
-    /// 15 minutes before
-    FifteenMinutes,
-    /// 20 minutes before
-    TwentyMinutes,
-    /// 25 minutes before
-    TwentyFiveMinutes,
-    /// 30 minutes before
-    ThirtyMinutes,
-}
+pub use crate::models::NotificationMinutes;
 
 /// EEPROM structure for Protocol 4
 pub struct Eeprom {
@@ -55,25 +41,30 @@ pub struct Eeprom {
 /// Convert NotificationMinutes to its numeric value
 fn notification_minutes_value(minutes: Option<NotificationMinutes>) -> u8 {
     match minutes {
-        Some(NotificationMinutes::None) => 0,
-        Some(NotificationMinutes::FiveMinutes) => 1,
-        Some(NotificationMinutes::TenMinutes) => 2,
-        Some(NotificationMinutes::FifteenMinutes) => 3,
-        Some(NotificationMinutes::TwentyMinutes) => 4,
-        Some(NotificationMinutes::TwentyFiveMinutes) => 5,
-        Some(NotificationMinutes::ThirtyMinutes) => 6,
+        Some(minutes) => minutes.value(),
         None => 0xFF, // APPOINTMENT_NO_NOTIFICATION
     }
 }
 
+impl Eeprom {
+    /// Report every appointment whose displayed time differs from its requested time
+    ///
+    /// The watch stores appointment times in 15-minute slots, so times are
+    /// rounded according to each appointment's rounding policy.
+    pub fn appointment_quantization_report(&self) -> Vec<QuantizationWarning> {
+        self.appointments.iter()
+            .enumerate()
+            .filter_map(|(index, app)| {
+                let message = app.message.to_string();
+                appointment_time::quantization_warning(index, &message, app.time, app.rounding)
+            })
+            .collect()
+    }
+}
+
 impl PacketGenerator for Eeprom {
     fn packets(&self) -> Vec<Vec<u8>> {
         // Constants from Ruby implementation
-        const CPACKET_CLEAR: [u8; 2] = [0x93, 0x01];
-        const CPACKET_SECT: [u8; 2] = [0x90, 0x01];
-        const CPACKET_DATA: [u8; 2] = [0x91, 0x01];
-        const CPACKET_END: [u8; 2] = [0x92, 0x01];
-        const CPACKET_DATA_LENGTH: usize = 32;
         const START_ADDRESS: u16 = 0x0236;
         
         // Get packet data for each type
@@ -89,7 +80,10 @@ impl PacketGenerator for Eeprom {
             .map(|phone| phone.packet())
             .collect();
             
-        let anniversary_packets: Vec<Vec<u8>> = self.anniversaries.iter()
+        // Anniversaries are ordered by month and day, as the watch expects
+        let mut anniversaries: Vec<&Anniversary> = self.anniversaries.iter().collect();
+        anniversaries.sort_by_key(|anniv| (anniv.month, anniv.day));
+        let anniversary_packets: Vec<Vec<u8>> = anniversaries.iter()
             .map(|anniv| anniv.packet())
             .collect();
         
@@ -128,9 +122,6 @@ impl PacketGenerator for Eeprom {
         // Calculate address for anniversary section
         addresses.push((address >> 8) as u8);
         addresses.push((address & 0xFF) as u8);
-        for packet in &anniversary_packets {
-            address += packet.len() as u16;
-        }
         
         // Get all items lengths
         let items_lengths = vec![
@@ -143,95 +134,40 @@ impl PacketGenerator for Eeprom {
         // Get earliest appointment year
         let earliest_appointment_year = self.appointments
             .iter()
-            .min_by_key(|app| {
-                let duration_since_epoch = app.time
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .expect("Time went backwards");
-                
-                let datetime = chrono::DateTime::<chrono::Utc>::from_timestamp(
-                    duration_since_epoch.as_secs() as i64,
-                    0
-                ).expect("Invalid timestamp");
-                
-                datetime.year()
-            })
-            .map(|app| {
-                let duration_since_epoch = app.time
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .expect("Time went backwards");
-                
-                let datetime = chrono::DateTime::<chrono::Utc>::from_timestamp(
-                    duration_since_epoch.as_secs() as i64,
-                    0
-                ).expect("Invalid timestamp");
-                
-                (datetime.year() % 100) as u8
-            })
+            .map(|app| (app.displayed_time().year() % 100) as u8)
+            .min()
             .unwrap_or(0);
         
         // Get appointment notification minutes value
         let appointment_notification_minutes_value = 
             notification_minutes_value(self.appointment_notification_minutes);
         
-        // Create the header packet
+        // Header fields following the payload packet count
         let mut header = Vec::new();
-        header.extend_from_slice(&CPACKET_SECT);
-        
-        // Calculate number of payload packets
-        let num_payload_packets = (all_items.len() / CPACKET_DATA_LENGTH) + 1;
-        header.push(num_payload_packets as u8); // Number of payload packets
         header.extend_from_slice(&addresses);
         header.extend_from_slice(&items_lengths);
         header.push(earliest_appointment_year);
         header.push(appointment_notification_minutes_value);
         
-        // Create payload packets using the paginator
-        use crate::helpers::cpacket_paginator::paginate_cpackets;
         let all_data: Vec<u8> = all_items.into_iter().flatten().collect();
-        let payloads = paginate_cpackets(&CPACKET_DATA, CPACKET_DATA_LENGTH, &all_data);
-        
-        // Create the end packet
-        let end_packet = CPACKET_END.to_vec();
-        
-        // Combine all packets
-        let mut all_packets = Vec::with_capacity(payloads.len() + 3);
-        all_packets.push(CPACKET_CLEAR.to_vec());
-        all_packets.push(header);
-        all_packets.extend(payloads);
-        all_packets.push(end_packet);
-        
-        // Apply CRC wrapping
-        use crate::helpers::crc_packets_wrapper::wrap_packets_with_crc;
-        wrap_packets_with_crc(all_packets)
+        LoadSection::new(commands::SECTION_EEPROM, true, header, all_data).packets()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::{TimeZone, Utc};
+    use chrono::NaiveDateTime;
     use crate::char_encoders::{EepromString, PhoneString};
     use phone_number::PhoneType;
     use list::Priority;
-    use std::time::SystemTime;
 
-    // Helper function to create a SystemTime from date components
-    fn system_time_from_date(year: i32, month: u32, day: u32, hour: u32, min: u32) -> SystemTime {
-        // Create a DateTime with chrono
-        let naive_dt = chrono::NaiveDate::from_ymd_opt(year, month, day)
+    // Helper function to create a wall-clock time from date components
+    fn datetime(year: i32, month: u32, day: u32, hour: u32, min: u32) -> NaiveDateTime {
+        chrono::NaiveDate::from_ymd_opt(year, month, day)
             .unwrap()
             .and_hms_opt(hour, min, 0)
-            .unwrap();
-        
-        let dt = Utc.from_utc_datetime(&naive_dt);
-        
-        // Convert to system time
-        SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(dt.timestamp() as u64)
-    }
-
-    // Helper function for anniversaries (no time component)
-    fn anniversary_time_from_date(year: i32, month: u32, day: u32) -> SystemTime {
-        system_time_from_date(year, month, day, 0, 0)
+            .unwrap()
     }
 
     #[test]
@@ -239,7 +175,8 @@ mod tests {
         let eeprom = Eeprom {
             anniversaries: vec![
                 Anniversary {
-                    time: anniversary_time_from_date(1985, 7, 3),
+                    month: 7,
+                    day: 3,
                     anniversary: EepromString::new("Release of Back to the Future"),
                 },
             ],
@@ -266,10 +203,10 @@ mod tests {
         let eeprom = Eeprom {
             anniversaries: vec![],
             appointments: vec![
-                Appointment {
-                    time: system_time_from_date(2022, 10, 31, 19, 0),
-                    message: EepromString::new("Scare the neighbors"),
-                },
+                Appointment::new(
+                    datetime(2022, 10, 31, 19, 0),
+                    EepromString::new("Scare the neighbors"),
+                ),
             ],
             phone_numbers: vec![],
             lists: vec![],
@@ -342,4 +279,25 @@ mod tests {
 
         assert_eq!(eeprom.packets(), expected);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_eeprom_packet_count() {
+        let phone_number = || PhoneNumber {
+            name: EepromString::new("Marty McFly"),
+            number: PhoneString::new("1112223333"),
+            phone_type: PhoneType::Home,
+        };
+        let eeprom = Eeprom {
+            anniversaries: vec![],
+            appointments: vec![],
+            phone_numbers: vec![phone_number(), phone_number(), phone_number()],
+            lists: vec![],
+            appointment_notification_minutes: None,
+        };
+
+        // Three phone numbers need 48 bytes, so two data packets
+        let section = LoadSection::parse(&eeprom.packets()).unwrap();
+        assert_eq!(section.packet_count(), 2);
+        assert_eq!(section.payload.len(), 48);
+    }
+}
@@ -2,71 +2,72 @@
 //!
 //! This module handles anniversaries stored in the watch's EEPROM.
 
-use std::time::SystemTime;
-use chrono::Datelike;
+use chrono::NaiveDate;
 use crate::char_encoders::EepromString;
+use crate::models;
+
+pub use crate::models::{validate_month_day, InvalidAnniversaryDate};
 
 /// Anniversary structure for Protocol 4 EEPROM
+///
+/// Anniversaries recur every year, so only the month and day are stored.
 pub struct Anniversary {
-    /// Time of anniversary
-    pub time: SystemTime,
-    
+    /// Month of anniversary (1-12)
+    pub month: u8,
+
+    /// Day of anniversary (1-31, February 29 is allowed)
+    pub day: u8,
+
     /// Anniversary text (EEPROM encoded, max 31 characters)
     pub anniversary: EepromString,
 }
 
 impl Anniversary {
+    /// Create a new Anniversary from a month and day
+    ///
+    /// Returns an error if the day does not exist in the month.
+    pub fn new(month: u8, day: u8, anniversary: EepromString) -> Result<Self, InvalidAnniversaryDate> {
+        models::Anniversary::new(month, day, String::new()).map(|date| Self::with_text(&date, anniversary))
+    }
+
+    /// Create a new Anniversary recurring on the month and day of a date
+    pub fn from_date(date: NaiveDate, anniversary: EepromString) -> Self {
+        Self::with_text(&models::Anniversary::from_date(date, String::new()), anniversary)
+    }
+
+    /// Take the month and day of a model anniversary, with already encoded text
+    fn with_text(date: &models::Anniversary, anniversary: EepromString) -> Self {
+        Anniversary { month: date.month, day: date.day, anniversary }
+    }
+
     /// Create the packet for an anniversary, similar to Ruby's packet method
-    /// 
+    ///
     /// This returns the raw packet bytes without the length prefix
     fn packet_content(&self) -> Vec<u8> {
-        // Convert SystemTime to chrono::DateTime for easier month/day extraction
-        // We need to handle dates that may be before the Unix epoch
-        let datetime = match self.time.duration_since(std::time::UNIX_EPOCH) {
-            Ok(duration) => {
-                chrono::DateTime::<chrono::Utc>::from_timestamp(
-                    duration.as_secs() as i64,
-                    0
-                ).expect("Invalid positive timestamp")
-            },
-            Err(_) => {
-                // For dates before Unix epoch (1970-01-01)
-                // We need to handle this differently
-                // First convert to a duration before epoch
-                let duration_before_epoch = std::time::UNIX_EPOCH
-                    .duration_since(self.time)
-                    .expect("Time error");
-                
-                // Create a DateTime by subtracting from epoch
-                let seconds_before_epoch = -(duration_before_epoch.as_secs() as i64);
-                chrono::DateTime::<chrono::Utc>::from_timestamp(
-                    seconds_before_epoch,
-                    0
-                ).expect("Invalid negative timestamp")
-            }
-        };
-        
-        let month = datetime.month() as u8;
-        let day = datetime.day() as u8;
-        
         // Combine month, day, and anniversary text
         let mut packet = Vec::new();
-        packet.push(month);
-        packet.push(day);
+        packet.push(self.month);
+        packet.push(self.day);
         packet.extend_from_slice(self.anniversary.as_bytes());
-        
+
         packet
     }
-    
+
     /// Create the full packet including length prefix (LengthPacketWrapper in Ruby)
     pub fn packet(&self) -> Vec<u8> {
         let content = self.packet_content();
         let mut result = Vec::with_capacity(content.len() + 1);
-        
+
         // Add length byte (content length + 1 for the length byte itself)
         result.push((content.len() + 1) as u8);
         result.extend(content);
-        
+
         result
     }
 }
+
+impl From<&models::Anniversary> for Anniversary {
+    fn from(anniversary: &models::Anniversary) -> Self {
+        Self::with_text(anniversary, EepromString::new(&anniversary.anniversary))
+    }
+}
@@ -3,40 +3,62 @@
 //! This module handles appointments stored in the watch's EEPROM.
 
 use std::time::SystemTime;
-use chrono::{Datelike, Timelike};
+use chrono::{DateTime, Datelike, NaiveDateTime, TimeZone};
 use crate::char_encoders::EepromString;
+use crate::helpers::appointment_time::{self, RoundingPolicy};
 
 /// Appointment structure for Protocol 4 EEPROM
 pub struct Appointment {
-    /// Time of appointment
-    pub time: SystemTime,
+    /// Wall-clock time of appointment, as shown by the watch
+    pub time: NaiveDateTime,
     
     /// Appointment message text (EEPROM encoded, max 31 characters)
     pub message: EepromString,
+    
+    /// How the time is rounded into the watch's 15-minute slots
+    pub rounding: RoundingPolicy,
 }
 
 impl Appointment {
+    /// Create a new Appointment at a wall-clock time
+    pub fn new(time: NaiveDateTime, message: EepromString) -> Self {
+        Appointment { time, message, rounding: RoundingPolicy::default() }
+    }
+    
+    /// Create a new Appointment from a timezone-aware datetime
+    ///
+    /// The appointment is shown at the datetime's local time in its zone.
+    pub fn from_datetime<Tz: TimeZone>(time: &DateTime<Tz>, message: EepromString) -> Self {
+        Self::new(appointment_time::wall_clock(time), message)
+    }
+    
+    /// Create a new Appointment from an instant, shown in the given time zone
+    pub fn from_system_time<Tz: TimeZone>(time: SystemTime, zone: &Tz, message: EepromString) -> Self {
+        Self::new(appointment_time::wall_clock_in(time, zone), message)
+    }
+    
+    /// Set the rounding policy
+    pub fn with_rounding(mut self, rounding: RoundingPolicy) -> Self {
+        self.rounding = rounding;
+        self
+    }
+    
+    /// Wall-clock time shown by the watch after rounding
+    pub fn displayed_time(&self) -> NaiveDateTime {
+        appointment_time::quantize(self.time, self.rounding)
+    }
+    
     /// Create the packet for an appointment, similar to Ruby's packet method
     /// 
     /// This returns the raw packet bytes without the length prefix
     fn packet_content(&self) -> Vec<u8> {
-        // Extract time components from SystemTime
-        let duration_since_epoch = self.time
-            .duration_since(std::time::UNIX_EPOCH)
-            .expect("Time went backwards");
-        
-        let datetime = chrono::DateTime::<chrono::Utc>::from_timestamp(
-            duration_since_epoch.as_secs() as i64,
-            0
-        ).expect("Invalid timestamp");
+        let displayed = self.displayed_time();
         
-        let month = datetime.month() as u8;
-        let day = datetime.day() as u8;
-        let hour = datetime.hour() as u8;
-        let minute = datetime.minute() as u8;
+        let month = displayed.month() as u8;
+        let day = displayed.day() as u8;
         
         // Calculate time_15m (each 15-minute slot of the day)
-        let time_15m = hour * 4 + minute / 15;
+        let time_15m = appointment_time::slot(displayed);
         
         // Create the packet
         let mut packet = Vec::new();
@@ -60,3 +82,26 @@ impl Appointment {
         result
     }
 }
+
+impl From<&crate::models::Appointment> for Appointment {
+    fn from(appointment: &crate::models::Appointment) -> Self {
+        Appointment::new(appointment.time, EepromString::new(&appointment.message))
+            .with_rounding(appointment.rounding)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, Timelike};
+
+    #[test]
+    fn test_appointment_rounding() {
+        let time = NaiveDate::from_ymd_opt(2022, 10, 31).unwrap().and_hms_opt(19, 1, 0).unwrap();
+        let appointment = Appointment::new(time, EepromString::new("Scare the neighbors"))
+            .with_rounding(RoundingPolicy::Ceil);
+
+        assert_eq!(appointment.displayed_time(), time.with_minute(15).unwrap());
+        assert_eq!(appointment.packet()[3], 19 * 4 + 1);
+    }
+}
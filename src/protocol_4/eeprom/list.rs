@@ -2,20 +2,7 @@
 //!
 //! This module handles to-do lists stored in the watch's EEPROM.
 
-/// Priority level for list items (1-5, with 5 being highest priority)
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum Priority {
-    /// Priority level 1 (lowest)
-    One,
-    /// Priority level 2
-    Two,
-    /// Priority level 3
-    Three,
-    /// Priority level 4
-    Four,
-    /// Priority level 5 (highest)
-    Five,
-}
+pub use crate::models::Priority;
 
 use crate::char_encoders::EepromString;
 
@@ -31,14 +18,7 @@ pub struct List {
 impl List {
     /// Convert Priority enum to its numeric value
     fn priority_value(&self) -> u8 {
-        match self.priority {
-            Some(Priority::One) => 1,
-            Some(Priority::Two) => 2,
-            Some(Priority::Three) => 3,
-            Some(Priority::Four) => 4,
-            Some(Priority::Five) => 5,
-            None => 0,
-        }
+        self.priority.map_or(0, Priority::value)
     }
     
     /// Create the packet for a list item, similar to Ruby's packet method
@@ -68,3 +48,12 @@ impl List {
         result
     }
 }
+
+impl From<&crate::models::ListEntry> for List {
+    fn from(entry: &crate::models::ListEntry) -> Self {
+        List {
+            list_entry: EepromString::new(&entry.entry),
+            priority: entry.priority,
+        }
+    }
+}
@@ -2,18 +2,7 @@
 //!
 //! This module handles phone numbers stored in the watch's EEPROM.
 
-/// Phone number type 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum PhoneType {
-    /// Cell phone (represented by 'c' in the Ruby implementation)
-    Cell,
-    /// Home phone (represented by 'h' in the Ruby implementation)
-    Home,
-    /// Work phone (represented by 'w' in the Ruby implementation)
-    Work,
-    /// Other phone type (any other character in the Ruby implementation)
-    Other,
-}
+pub use crate::models::PhoneType;
 
 use crate::char_encoders::{EepromString, PhoneString};
 
@@ -88,16 +77,6 @@ fn phone_encoding(s: &str) -> Vec<u8> {
 }
 
 impl PhoneNumber {
-    /// Convert PhoneType to its character representation
-    fn phone_type_to_char(&self) -> char {
-        match self.phone_type {
-            PhoneType::Cell => 'c',
-            PhoneType::Home => 'h',
-            PhoneType::Work => 'w',
-            PhoneType::Other => ' ',
-        }
-    }
-    
     /// Create the packet for a phone number, similar to Ruby's packet method
     /// 
     /// This returns the raw packet bytes without the length prefix
@@ -136,7 +115,7 @@ impl PhoneNumber {
         }
         
         // Get the type as a character
-        let type_char = self.phone_type_to_char();
+        let type_char = self.phone_type.code();
         
         // Create the number_with_type string (number + " " + type)
         let number_with_type = format!("{} {}", number_str, type_char);
@@ -163,3 +142,13 @@ impl PhoneNumber {
         result
     }
 }
+
+impl From<&crate::models::PhoneEntry> for PhoneNumber {
+    fn from(entry: &crate::models::PhoneEntry) -> Self {
+        PhoneNumber {
+            name: EepromString::new(&entry.name),
+            number: PhoneString::new(&entry.number),
+            phone_type: entry.phone_type,
+        }
+    }
+}
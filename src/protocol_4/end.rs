@@ -3,9 +3,7 @@
 //! This module handles the end command for Timex Datalink watches.
 
 use crate::PacketGenerator;
-
-/// CPACKET_SKIP value for the end command
-const CPACKET_SKIP: u8 = 0x21;
+use crate::protocol_4::commands;
 
 /// End structure for Protocol 4
 pub struct End {}
@@ -13,7 +11,9 @@ pub struct End {}
 impl PacketGenerator for End {
     fn packets(&self) -> Vec<Vec<u8>> {
         // Generate raw packets
-        let raw_packets = vec![vec![CPACKET_SKIP]];
+        let raw_packets = vec![
+            commands::END.builder().build().expect("END layout has no fields")
+        ];
         
         // Apply CRC wrapping (like the Ruby prepend CrcPacketsWrapper)
         use crate::helpers::crc_packets_wrapper::wrap_packets_with_crc;
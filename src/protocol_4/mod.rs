@@ -1,5 +1,6 @@
 //! Protocol 4 implementation for Timex Datalink watches.
 
+pub mod commands;
 pub mod sync;
 pub mod start;
 pub mod time;
@@ -46,6 +47,12 @@ impl Protocol4 {
     }
 }
 
+impl Default for Protocol4 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl PacketGenerator for Protocol4 {
     fn packets(&self) -> Vec<Vec<u8>> {
         self.models.iter()
@@ -3,6 +3,7 @@
 //! This module handles sound options for Timex Datalink watches.
 
 use crate::PacketGenerator;
+use crate::protocol_4::commands;
 
 /// SoundOptions structure for Protocol 4
 pub struct SoundOptions {
@@ -15,15 +16,12 @@ pub struct SoundOptions {
 
 impl PacketGenerator for SoundOptions {
     fn packets(&self) -> Vec<Vec<u8>> {
-        // Constants from Ruby implementation
-        const CPACKET_BEEPS: u8 = 0x71;
-        
         // Create the raw packet
-        let raw_packet = vec![
-            CPACKET_BEEPS,
-            if self.hourly_chime { 1 } else { 0 },
-            if self.button_beep { 1 } else { 0 }
-        ];
+        let raw_packet = commands::BEEPS.builder()
+            .bool("hourly_chime", self.hourly_chime)
+            .bool("button_beep", self.button_beep)
+            .build()
+            .expect("BEEPS fields match layout");
         
         // Apply CRC wrapping
         use crate::helpers::crc_packets_wrapper::wrap_packets_with_crc;
@@ -31,6 +29,15 @@ impl PacketGenerator for SoundOptions {
     }
 }
 
+impl From<&crate::models::SoundOptions> for SoundOptions {
+    fn from(options: &crate::models::SoundOptions) -> Self {
+        SoundOptions {
+            hourly_chime: options.hourly_chime,
+            button_beep: options.button_beep,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -3,53 +3,50 @@
 //! This module handles sound themes for Timex Datalink watches.
 
 use crate::PacketGenerator;
-use crate::helpers::cpacket_paginator::paginate_cpackets;
+use crate::helpers::load_section::LoadSection;
+use crate::protocol_4::commands;
+use crate::spc::{Spc, SpcError};
 
 /// SoundTheme structure for Protocol 4
 pub struct SoundTheme {
-    /// Sound theme data bytes
-    pub sound_theme_data: Vec<u8>,
+    /// The validated sound theme
+    spc: Spc,
+}
+
+impl SoundTheme {
+    /// Create a new SoundTheme from raw data
+    ///
+    /// # Arguments
+    ///
+    /// * `sound_theme_data` - The sound data, with or without the SPC header
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the sound data is empty or does not fit the
+    /// watch's sound area
+    pub fn new(sound_theme_data: Vec<u8>) -> Result<Self, SpcError> {
+        Ok(SoundTheme::from_spc(&Spc::from_bytes(&sound_theme_data)?))
+    }
+
+    /// Create a new SoundTheme from a parsed SPC file
+    pub fn from_spc(spc: &Spc) -> Self {
+        SoundTheme { spc: spc.clone() }
+    }
+
+    /// Sound data loaded onto the watch (without SPC header)
+    pub fn sound_data(&self) -> &[u8] {
+        self.spc.sound_data()
+    }
 }
 
 impl PacketGenerator for SoundTheme {
     fn packets(&self) -> Vec<Vec<u8>> {
-        // Constants from Ruby implementation
-        const CPACKET_SECT: [u8; 2] = [0x90, 0x03];
-        const CPACKET_DATA: [u8; 2] = [0x91, 0x03];
-        const CPACKET_END: [u8; 2] = [0x92, 0x03];
-        const CPACKET_DATA_LENGTH: usize = 32;
-        
-        // Check if data has the SPC file header and remove it if present
-        const SOUND_DATA_HEADER: &[u8] = &[0x25, 0x04, 0x19, 0x69];
-        
-        let sound_data = if self.sound_theme_data.starts_with(SOUND_DATA_HEADER) {
-            &self.sound_theme_data[SOUND_DATA_HEADER.len()..]
-        } else {
-            &self.sound_theme_data
-        };
-        
-        // Calculate offset similar to Ruby implementation
-        let offset = 0x100 - sound_data.len();
-        
-        // Create load_sect packet
-        let payloads = paginate_cpackets(&CPACKET_DATA, CPACKET_DATA_LENGTH, sound_data);
-        let mut load_sect = Vec::new();
-        load_sect.extend_from_slice(&CPACKET_SECT);
-        load_sect.push(payloads.len() as u8);
-        load_sect.push(offset as u8);
-        
-        // Create end packet
-        let end_packet = CPACKET_END.to_vec();
-        
-        // Combine all packets
-        let mut all_packets = Vec::with_capacity(payloads.len() + 2);
-        all_packets.push(load_sect);
-        all_packets.extend(payloads);
-        all_packets.push(end_packet);
-        
-        // Apply CRC wrapping
-        use crate::helpers::crc_packets_wrapper::wrap_packets_with_crc;
-        wrap_packets_with_crc(all_packets)
+        LoadSection::new(
+            commands::SECTION_SOUND_THEME,
+            false,
+            vec![self.spc.load_offset()],
+            self.spc.sound_data().to_vec()
+        ).packets()
     }
 }
 
@@ -63,9 +60,7 @@ mod tests {
 
     #[test]
     fn test_sound_theme() {
-        let sound_theme = SoundTheme {
-            sound_theme_data: EXAMPLE_SPC.to_vec(),
-        };
+        let sound_theme = SoundTheme::new(EXAMPLE_SPC.to_vec()).unwrap();
 
         // From golden fixture: sound_theme.jsonl
         #[rustfmt::skip]
@@ -77,5 +72,6 @@ mod tests {
         ];
 
         assert_eq!(sound_theme.packets(), expected);
+        assert_eq!(SoundTheme::new(Vec::new()).err(), Some(SpcError::Empty));
     }
 }
\ No newline at end of file
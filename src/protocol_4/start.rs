@@ -3,9 +3,7 @@
 //! This module handles the start command for Timex Datalink watches.
 
 use crate::PacketGenerator;
-
-/// CPACKET_START value for the start command
-const CPACKET_START: [u8; 4] = [0x20, 0x00, 0x00, 0x04];
+use crate::protocol_4::commands;
 
 /// Start structure for Protocol 4
 pub struct Start {}
@@ -13,7 +11,9 @@ pub struct Start {}
 impl PacketGenerator for Start {
     fn packets(&self) -> Vec<Vec<u8>> {
         // Generate raw packets
-        let raw_packets = vec![CPACKET_START.to_vec()];
+        let raw_packets = vec![
+            commands::START.builder().build().expect("START layout has no fields")
+        ];
         
         // Apply CRC wrapping (like the Ruby prepend CrcPacketsWrapper)
         use crate::helpers::crc_packets_wrapper::wrap_packets_with_crc;
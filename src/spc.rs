@@ -0,0 +1,153 @@
+//! SPC sound theme module
+//!
+//! This module parses, validates and writes SPC sound theme files.
+//!
+//! An SPC file is a 4-byte header followed by the sound data that is loaded
+//! into the end of the watch's 256-byte sound area. The sound data is loaded
+//! as-is: its layout is not documented, so only its length is validated.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Header of every SPC file
+pub const SPC_HEADER: [u8; 4] = [0x25, 0x04, 0x19, 0x69];
+
+/// Size of the watch's sound area (in bytes)
+pub const SOUND_AREA_SIZE: usize = 0x100;
+
+/// Errors raised while parsing sound data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpcError {
+    /// The file holds no sound data
+    Empty,
+    /// The sound data does not fit the watch's sound area
+    TooLarge { length: usize },
+}
+
+impl fmt::Display for SpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpcError::Empty => write!(f, "sound data is empty"),
+            SpcError::TooLarge { length } => write!(
+                f,
+                "sound data is {} bytes, the sound area holds {} bytes",
+                length, SOUND_AREA_SIZE
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SpcError {}
+
+impl From<SpcError> for io::Error {
+    fn from(e: SpcError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, e)
+    }
+}
+
+/// Offset byte sent in the sound theme section header
+///
+/// The sound data is loaded into the end of the sound area, so the offset is
+/// the start address of the data within the area.
+///
+/// # Arguments
+///
+/// * `length` - Length of the sound data (without SPC header)
+///
+/// # Returns
+///
+/// The offset, or an error if the data does not fit the sound area
+pub fn load_offset(length: usize) -> Result<u8, SpcError> {
+    match length {
+        0 => Err(SpcError::Empty),
+        length if length > SOUND_AREA_SIZE => Err(SpcError::TooLarge { length }),
+        length => Ok((SOUND_AREA_SIZE - length) as u8),
+    }
+}
+
+/// A sound theme, as stored in an SPC file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Spc {
+    sound_data: Vec<u8>,
+}
+
+impl Spc {
+    /// Parse SPC file contents
+    ///
+    /// The header is optional so that raw sound data is accepted as well.
+    /// Only the length is validated.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SpcError> {
+        let sound_data = bytes.strip_prefix(&SPC_HEADER[..]).unwrap_or(bytes);
+        load_offset(sound_data.len())?;
+
+        Ok(Spc { sound_data: sound_data.to_vec() })
+    }
+
+    /// Read an SPC file
+    pub fn read<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Self::from_bytes(&fs::read(path)?)?)
+    }
+
+    /// Write an SPC file
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_bytes())
+    }
+
+    /// SPC file contents, including the header
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = SPC_HEADER.to_vec();
+        bytes.extend_from_slice(&self.sound_data);
+        bytes
+    }
+
+    /// Sound data loaded onto the watch
+    pub fn sound_data(&self) -> &[u8] {
+        &self.sound_data
+    }
+
+    /// Offset byte sent in the sound theme section header
+    pub fn load_offset(&self) -> u8 {
+        (SOUND_AREA_SIZE - self.sound_data.len()) as u8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE_SPC: &[u8] = include_bytes!("../fixtures/EXAMPLE.SPC");
+
+    #[test]
+    fn test_load_offset() {
+        assert_eq!(load_offset(41), Ok(0xd7));
+        assert_eq!(load_offset(256), Ok(0x00));
+        assert_eq!(load_offset(257), Err(SpcError::TooLarge { length: 257 }));
+        assert_eq!(load_offset(0), Err(SpcError::Empty));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let spc = Spc::from_bytes(EXAMPLE_SPC).unwrap();
+        assert_eq!(spc.sound_data(), &EXAMPLE_SPC[4..]);
+        assert_eq!(spc.load_offset(), 0xd7);
+        assert_eq!(spc.to_bytes(), EXAMPLE_SPC);
+
+        // Raw sound data without a header is accepted too
+        assert_eq!(Spc::from_bytes(&EXAMPLE_SPC[4..]).unwrap(), spc);
+        assert_eq!(Spc::from_bytes(&SPC_HEADER), Err(SpcError::Empty));
+        assert_eq!(Spc::from_bytes(&[1; 300]), Err(SpcError::TooLarge { length: 300 }));
+    }
+
+    #[test]
+    fn test_write_and_read() {
+        let path = std::env::temp_dir().join(format!("timex_datalink_{}.spc", std::process::id()));
+
+        let spc = Spc::from_bytes(&[0x31, 0x41, 0x00]).unwrap();
+        spc.write(&path).unwrap();
+        assert_eq!(Spc::read(&path).unwrap(), spc);
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,261 @@
+//! Sync Snapshot module
+//!
+//! Sending a full session resends large sections such as the EEPROM data and
+//! wrist apps, which takes minutes over the LED or serial link. This module
+//! keeps a snapshot of the last state sent to each watch, keyed by a
+//! user-chosen watch id, and plans sessions that skip every section whose
+//! packets have not changed since.
+//!
+//! The time section is always sent, since the watch's clock has to be set
+//! on every sync. Sections missing from a config are not sent and keep their
+//! previous snapshot, as the watch keeps that data.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::watch_config::{ProtocolVersion, Section, WatchConfig};
+
+/// Snapshot of the last state sent to one watch
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct WatchSnapshot {
+    /// Protocol number the watch was synced with
+    pub protocol: u8,
+
+    /// Hash of the packets last sent for each section
+    pub sections: BTreeMap<Section, String>,
+}
+
+/// Snapshots of every known watch, stored as a JSON file
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct SnapshotStore {
+    /// Snapshots keyed by watch id
+    pub watches: BTreeMap<String, WatchSnapshot>,
+}
+
+/// A planned session that only sends changed sections
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IncrementalSession {
+    /// Packets to transmit
+    pub packets: Vec<Vec<u8>>,
+
+    /// Sections included in the packets
+    pub sent: Vec<Section>,
+
+    /// Sections skipped because the watch already has them
+    pub skipped: Vec<Section>,
+
+    /// Snapshot of the watch once the packets have been transmitted
+    pub snapshot: WatchSnapshot,
+}
+
+/// Errors raised while loading or saving snapshots
+#[derive(Debug)]
+pub enum SnapshotError {
+    /// The snapshot file could not be read or written
+    Io(io::Error),
+    /// The snapshot file is not valid JSON
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Io(e) => write!(f, "snapshot file error: {}", e),
+            SnapshotError::Json(e) => write!(f, "invalid snapshot file: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+impl From<io::Error> for SnapshotError {
+    fn from(e: io::Error) -> Self {
+        SnapshotError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for SnapshotError {
+    fn from(e: serde_json::Error) -> Self {
+        SnapshotError::Json(e)
+    }
+}
+
+impl SnapshotStore {
+    /// Create an empty snapshot store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load snapshots from a JSON file
+    ///
+    /// A missing file is treated as an empty store.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, SnapshotError> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Ok(serde_json::from_str(&contents)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Save snapshots to a JSON file, creating its directory if needed
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), SnapshotError> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Snapshot of a watch, if it has been synced before
+    pub fn get(&self, watch_id: &str) -> Option<&WatchSnapshot> {
+        self.watches.get(watch_id)
+    }
+
+    /// Plan a session that skips sections the watch already has
+    ///
+    /// # Arguments
+    ///
+    /// * `watch_id` - User-chosen id of the watch
+    /// * `config` - Desired state of the watch
+    /// * `protocol` - The protocol of the watch
+    /// * `force_full` - Send every section regardless of the snapshot
+    pub fn plan(
+        &self,
+        watch_id: &str,
+        config: &WatchConfig,
+        protocol: ProtocolVersion,
+        force_full: bool,
+    ) -> IncrementalSession {
+        let previous = self.get(watch_id)
+            .filter(|snapshot| !force_full && snapshot.protocol == protocol.number());
+
+        let mut snapshot = WatchSnapshot {
+            protocol: protocol.number(),
+            sections: previous.map(|snapshot| snapshot.sections.clone()).unwrap_or_default(),
+        };
+        let mut sent = Vec::new();
+        let mut skipped = Vec::new();
+
+        for (section, packets) in config.section_packets(protocol) {
+            let hash = section_hash(&packets);
+            let unchanged = previous
+                .and_then(|snapshot| snapshot.sections.get(&section))
+                .is_some_and(|previous_hash| *previous_hash == hash);
+
+            if unchanged && section != Section::Time {
+                skipped.push(section);
+            } else {
+                sent.push(section);
+            }
+            snapshot.sections.insert(section, hash);
+        }
+
+        let packets = config.packets_with(protocol, |section| sent.contains(&section));
+
+        IncrementalSession { packets, sent, skipped, snapshot }
+    }
+
+    /// Record a session after it has been transmitted to a watch
+    pub fn record(&mut self, watch_id: &str, session: &IncrementalSession) {
+        self.watches.insert(watch_id.to_string(), session.snapshot.clone());
+    }
+}
+
+/// Hash a section's packets with 64-bit FNV-1a
+///
+/// Packet boundaries are included so that re-paginated data hashes
+/// differently.
+fn section_hash(packets: &[Vec<u8>]) -> String {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const FNV_PRIME: u64 = 0x100000001b3;
+
+    let mut hash = FNV_OFFSET_BASIS;
+    for packet in packets {
+        for &byte in (packet.len() as u32).to_le_bytes().iter().chain(packet) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    format!("{:016x}", hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Alarm, DateFormat, ListEntry, TimeZone};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn config(alarm_message: &str, seconds: u64) -> WatchConfig {
+        WatchConfig::builder()
+            .sync_length(10)
+            .time_zone(TimeZone {
+                zone: 1,
+                is_24h: true,
+                date_format: DateFormat::YearDotMonthDotDay,
+                time: UNIX_EPOCH + Duration::from_secs(seconds),
+                name: "UTC".to_string(),
+            })
+            .alarm(Alarm::new(1, 9, 0, alarm_message.to_string()))
+            .list_entry(ListEntry::new("Muffler bearings".to_string(), None))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_section_hash() {
+        assert_eq!(section_hash(&[]), "cbf29ce484222325");
+        assert_ne!(section_hash(&[vec![1, 2]]), section_hash(&[vec![1], vec![2]]));
+    }
+
+    #[test]
+    fn test_plan_skips_unchanged_sections() {
+        let mut store = SnapshotStore::new();
+        let protocol = ProtocolVersion::Protocol3;
+
+        let first = store.plan("wrist", &config("Wake up", 0), protocol, false);
+        assert_eq!(first.sent, vec![Section::Time, Section::Alarms, Section::Eeprom]);
+        assert_eq!(first.packets, config("Wake up", 0).packets(protocol));
+        store.record("wrist", &first);
+
+        // Only the time changed, which is always sent
+        let second = store.plan("wrist", &config("Wake up", 60), protocol, false);
+        assert_eq!(second.sent, vec![Section::Time]);
+        assert_eq!(second.skipped, vec![Section::Alarms, Section::Eeprom]);
+        assert_eq!(
+            second.packets,
+            config("Wake up", 60).packets_with(protocol, |section| section == Section::Time)
+        );
+
+        let changed = store.plan("wrist", &config("Get up", 60), protocol, false);
+        assert_eq!(changed.sent, vec![Section::Time, Section::Alarms]);
+
+        // Forcing, another watch or another protocol send everything
+        assert!(store.plan("wrist", &config("Wake up", 60), protocol, true).skipped.is_empty());
+        assert!(store.plan("other", &config("Wake up", 60), protocol, false).skipped.is_empty());
+        assert!(store.plan("wrist", &config("Wake up", 60), ProtocolVersion::Protocol4, false).skipped.is_empty());
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let path = std::env::temp_dir()
+            .join(format!("timex_datalink_snapshots_{}", std::process::id()))
+            .join("snapshots.json");
+
+        assert_eq!(SnapshotStore::load(&path).unwrap(), SnapshotStore::new());
+
+        let mut store = SnapshotStore::new();
+        let session = store.plan("wrist", &config("Wake up", 0), ProtocolVersion::Protocol3, false);
+        store.record("wrist", &session);
+        store.save(&path).unwrap();
+
+        assert_eq!(SnapshotStore::load(&path).unwrap(), store);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
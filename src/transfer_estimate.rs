@@ -0,0 +1,313 @@
+//! Transfer Estimate module
+//!
+//! This module estimates how long a packet stream takes to transmit with a
+//! given adapter, broken down by section, so that users can see what each
+//! part of a sync costs before sending it (a wrist app alone can take
+//! minutes).
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::helpers::command_packet::Opcode;
+use crate::helpers::crc_packets_wrapper;
+use crate::notebook_adapter::NotebookAdapter;
+use crate::oneplus6_led_adapter::OnePlus6LedAdapter;
+
+/// Timing of an adapter, in seconds
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AdapterTiming {
+    /// Time to sleep after sending each byte
+    pub byte_sleep: f32,
+
+    /// Time to sleep after sending a packet
+    pub packet_sleep: f32,
+
+    /// Time each bit is held (LED adapters send bytes bit by bit)
+    #[serde(default)]
+    pub bit_sleep: f32,
+
+    /// Time spent before the first packet is sent
+    #[serde(default)]
+    pub startup: f32,
+}
+
+impl AdapterTiming {
+    /// Timing of a serial adapter such as the Notebook Adapter
+    ///
+    /// # Arguments
+    ///
+    /// * `byte_sleep` - Time to sleep after sending each byte (in seconds)
+    /// * `packet_sleep` - Time to sleep after sending a packet (in seconds)
+    pub fn serial(byte_sleep: f32, packet_sleep: f32) -> Self {
+        AdapterTiming { byte_sleep, packet_sleep, bit_sleep: 0.0, startup: 0.0 }
+    }
+
+    /// Timing of an LED adapter, which holds each bit for an eighth of the byte sleep
+    ///
+    /// # Arguments
+    ///
+    /// * `byte_sleep` - Time to sleep after sending each byte (in seconds)
+    /// * `packet_sleep` - Time to sleep after sending a packet (in seconds)
+    pub fn led(byte_sleep: f32, packet_sleep: f32) -> Self {
+        AdapterTiming {
+            byte_sleep,
+            packet_sleep,
+            bit_sleep: byte_sleep / 8.0,
+            startup: OnePlus6LedAdapter::STARTUP_SLEEP,
+        }
+    }
+
+    /// Time taken to send a single packet (in seconds)
+    pub fn packet_seconds(&self, packet: &[u8]) -> f64 {
+        let byte_seconds = self.byte_sleep as f64 + 8.0 * self.bit_sleep as f64;
+        packet.len() as f64 * byte_seconds + self.packet_sleep as f64
+    }
+}
+
+impl Default for AdapterTiming {
+    /// Timing of the Notebook Adapter with its default delays
+    fn default() -> Self {
+        AdapterTiming::serial(NotebookAdapter::BYTE_SLEEP_DEFAULT, NotebookAdapter::PACKET_SLEEP_DEFAULT)
+    }
+}
+
+impl From<&NotebookAdapter> for AdapterTiming {
+    fn from(adapter: &NotebookAdapter) -> Self {
+        AdapterTiming::serial(adapter.byte_sleep, adapter.packet_sleep)
+    }
+}
+
+impl From<&OnePlus6LedAdapter> for AdapterTiming {
+    fn from(adapter: &OnePlus6LedAdapter) -> Self {
+        AdapterTiming::led(adapter.byte_sleep, adapter.packet_sleep)
+    }
+}
+
+/// Part of a session a packet belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferSection {
+    /// Sync, start and end packets
+    Sync,
+    /// Time and time zone settings
+    Time,
+    /// Alarm settings
+    Alarms,
+    /// EEPROM data (appointments, anniversaries, phone numbers and lists)
+    Eeprom,
+    /// Hourly chime and button beep settings
+    SoundOptions,
+    /// Sound theme upload
+    SoundTheme,
+    /// Wrist app upload
+    WristApp,
+    /// Packets that could not be classified
+    Other,
+}
+
+impl TransferSection {
+    /// Human-readable section name
+    pub fn name(self) -> &'static str {
+        match self {
+            TransferSection::Sync => "sync",
+            TransferSection::Time => "time",
+            TransferSection::Alarms => "alarms",
+            TransferSection::Eeprom => "EEPROM",
+            TransferSection::SoundOptions => "sound options",
+            TransferSection::SoundTheme => "sound theme",
+            TransferSection::WristApp => "wrist app",
+            TransferSection::Other => "other",
+        }
+    }
+
+    /// Classify a CRC-wrapped packet (or a sync packet)
+    pub fn of_packet(packet: &[u8]) -> Self {
+        if crc_packets_wrapper::is_sync_packet(packet) {
+            return TransferSection::Sync;
+        }
+
+        match packet.get(1).copied().and_then(Opcode::from_byte) {
+            Some(Opcode::Start | Opcode::End) => TransferSection::Sync,
+            Some(Opcode::Time) => TransferSection::Time,
+            Some(Opcode::Alarm) => TransferSection::Alarms,
+            Some(Opcode::Beeps) => TransferSection::SoundOptions,
+            Some(Opcode::Sect | Opcode::Data | Opcode::EndSect | Opcode::Clear) => match packet.get(2) {
+                Some(0x01) => TransferSection::Eeprom,
+                Some(0x02) => TransferSection::WristApp,
+                Some(0x03) => TransferSection::SoundTheme,
+                _ => TransferSection::Other,
+            },
+            None => TransferSection::Other,
+        }
+    }
+}
+
+/// Estimated transfer time of one section
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SectionEstimate {
+    /// The section
+    pub section: TransferSection,
+
+    /// Number of packets in the section
+    pub packets: usize,
+
+    /// Number of bytes in the section
+    pub bytes: usize,
+
+    /// Estimated time to send the section (in seconds)
+    pub seconds: f64,
+}
+
+/// Estimated transfer time of a packet stream
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TransferEstimate {
+    /// Estimated time to send every packet (in seconds)
+    pub total_seconds: f64,
+
+    /// Breakdown by section, in the order the sections are first sent
+    pub sections: Vec<SectionEstimate>,
+}
+
+impl TransferEstimate {
+    /// Estimate for a single section, if the stream contains it
+    pub fn section(&self, section: TransferSection) -> Option<&SectionEstimate> {
+        self.sections.iter().find(|estimate| estimate.section == section)
+    }
+}
+
+/// Estimate the time needed to transmit a packet stream
+///
+/// # Arguments
+///
+/// * `packets` - The packets to send, as returned by `PacketGenerator::packets`
+/// * `timing` - Timing of the adapter the packets are sent with
+///
+/// # Returns
+///
+/// The total time and a per-section breakdown
+pub fn estimate_transfer(packets: &[Vec<u8>], timing: &AdapterTiming) -> TransferEstimate {
+    let mut sections: Vec<SectionEstimate> = Vec::new();
+
+    for packet in packets {
+        let section = TransferSection::of_packet(packet);
+        let index = match sections.iter().position(|estimate| estimate.section == section) {
+            Some(index) => index,
+            None => {
+                sections.push(SectionEstimate { section, packets: 0, bytes: 0, seconds: 0.0 });
+                sections.len() - 1
+            }
+        };
+
+        let estimate = &mut sections[index];
+        estimate.packets += 1;
+        estimate.bytes += packet.len();
+        estimate.seconds += timing.packet_seconds(packet);
+    }
+
+    let total_seconds = timing.startup as f64 + sections.iter()
+        .map(|estimate| estimate.seconds)
+        .sum::<f64>();
+
+    // The adapter's startup delay is part of getting the watch in sync
+    if let Some(sync) = sections.iter_mut().find(|estimate| estimate.section == TransferSection::Sync) {
+        sync.seconds += timing.startup as f64;
+    }
+
+    TransferEstimate { total_seconds, sections }
+}
+
+impl fmt::Display for TransferEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Estimated transfer time: {}", format_duration(self.total_seconds))?;
+        for estimate in &self.sections {
+            writeln!(
+                f,
+                "  {:<14} {:>9} {:>4} packets {:>6} bytes",
+                estimate.section.name(),
+                format_duration(estimate.seconds),
+                estimate.packets,
+                estimate.bytes,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Format seconds as e.g. "2m 05.3s" or "12.5s"
+fn format_duration(seconds: f64) -> String {
+    let minutes = (seconds / 60.0).floor();
+    if minutes >= 1.0 {
+        format!("{}m {:04.1}s", minutes, seconds - minutes * 60.0)
+    } else {
+        format!("{:.1}s", seconds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::crc_packets_wrapper::wrap_packets_with_crc;
+
+    fn session() -> Vec<Vec<u8>> {
+        let mut packets = vec![[vec![0x78], vec![0x55; 5], vec![0xaa; 4]].concat()];
+        packets.extend(wrap_packets_with_crc(vec![
+            vec![0x20, 0x00, 0x00, 0x03],
+            vec![0x50, 0x01, 0x09, 0x00],
+            vec![0x93, 0x02],
+            vec![0x90, 0x02, 0x01, 0x00],
+            vec![0x91, 0x02, 0x01, 0xaa, 0xbb],
+            vec![0x92, 0x02],
+            vec![0x21],
+        ]));
+        packets
+    }
+
+    #[test]
+    fn test_classify_packets() {
+        let sections: Vec<TransferSection> = session().iter()
+            .map(|packet| TransferSection::of_packet(packet))
+            .collect();
+
+        assert_eq!(sections, vec![
+            TransferSection::Sync,
+            TransferSection::Sync,
+            TransferSection::Alarms,
+            TransferSection::WristApp,
+            TransferSection::WristApp,
+            TransferSection::WristApp,
+            TransferSection::WristApp,
+            TransferSection::Sync,
+        ]);
+    }
+
+    #[test]
+    fn test_serial_estimate() {
+        let estimate = estimate_transfer(&session(), &AdapterTiming::serial(0.025, 0.25));
+
+        let sections: Vec<TransferSection> = estimate.sections.iter().map(|e| e.section).collect();
+        assert_eq!(sections, vec![TransferSection::Sync, TransferSection::Alarms, TransferSection::WristApp]);
+
+        // Sync packet, start (7 bytes) and end (4 bytes)
+        let sync = estimate.section(TransferSection::Sync).unwrap();
+        assert_eq!((sync.packets, sync.bytes), (3, 21));
+        assert!((sync.seconds - (21.0 * 0.025 + 3.0 * 0.25)).abs() < 1e-6);
+
+        let total = 53.0 * 0.025 + 8.0 * 0.25;
+        assert!((estimate.total_seconds - total).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_led_estimate() {
+        let timing = AdapterTiming::led(0.025, 0.25);
+        let estimate = estimate_transfer(&session(), &timing);
+
+        // Each byte is eight bits followed by the byte pause, plus the startup delay
+        let total = 0.5 + 53.0 * 0.05 + 8.0 * 0.25;
+        assert!((estimate.total_seconds - total).abs() < 1e-6);
+
+        let listing = estimate.to_string();
+        assert!(listing.starts_with("Estimated transfer time: 5."));
+        assert!(listing.lines().any(|line| line.trim_start().starts_with("wrist app")));
+    }
+}
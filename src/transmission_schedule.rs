@@ -0,0 +1,111 @@
+//! Transmission Schedule module
+//!
+//! This module works out when each byte of a packet stream is sent and how
+//! long the adapter pauses after it. The native adapters and the web app
+//! both follow this schedule, so they pace transfers identically and timing
+//! fixes only need to be made here.
+
+use serde::Serialize;
+
+use crate::transfer_estimate::AdapterTiming;
+
+/// A byte of the stream and when it is sent
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ScheduledByte {
+    /// Index of the packet the byte belongs to
+    pub packet: usize,
+
+    /// The byte to send
+    pub byte: u8,
+
+    /// Time the byte starts being sent, from the start of the session (in seconds)
+    pub at: f64,
+
+    /// Time to wait once the byte has been sent (in seconds)
+    pub delay: f64,
+
+    /// Whether this is the last byte of its packet
+    pub end_of_packet: bool,
+}
+
+/// Byte-by-byte schedule of a packet stream
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TransmissionSchedule {
+    /// Timing the schedule was computed with
+    pub timing: AdapterTiming,
+
+    /// Bytes in the order they are sent
+    pub bytes: Vec<ScheduledByte>,
+
+    /// Time until the last pause is over (in seconds)
+    pub total_seconds: f64,
+}
+
+impl TransmissionSchedule {
+    /// Schedule a packet stream
+    ///
+    /// # Arguments
+    ///
+    /// * `packets` - The packets to send, as returned by `PacketGenerator::packets`
+    /// * `timing` - Timing of the adapter the packets are sent with
+    pub fn new(packets: &[Vec<u8>], timing: &AdapterTiming) -> Self {
+        let byte_sleep = timing.byte_sleep as f64;
+        let packet_sleep = timing.packet_sleep as f64;
+        // LED adapters hold each of the eight bits before the byte pause
+        let byte_length = 8.0 * timing.bit_sleep as f64;
+
+        let mut at = timing.startup as f64;
+        let mut bytes = Vec::new();
+
+        for (index, packet) in packets.iter().enumerate() {
+            for (position, &byte) in packet.iter().enumerate() {
+                let end_of_packet = position + 1 == packet.len();
+                let delay = if end_of_packet { byte_sleep + packet_sleep } else { byte_sleep };
+
+                bytes.push(ScheduledByte { packet: index, byte, at, delay, end_of_packet });
+                at += byte_length + delay;
+            }
+        }
+
+        TransmissionSchedule { timing: *timing, bytes, total_seconds: at }
+    }
+
+    /// Time each bit is held (in seconds, zero for serial adapters)
+    pub fn bit_seconds(&self) -> f64 {
+        self.timing.bit_sleep as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transfer_estimate::estimate_transfer;
+
+    #[test]
+    fn test_serial_schedule() {
+        let packets = vec![vec![0x01, 0x02], vec![0x03]];
+        let schedule = TransmissionSchedule::new(&packets, &AdapterTiming::serial(0.025, 0.25));
+
+        let steps: Vec<(usize, u8, bool)> = schedule.bytes.iter()
+            .map(|step| (step.packet, step.byte, step.end_of_packet))
+            .collect();
+        assert_eq!(steps, vec![(0, 0x01, false), (0, 0x02, true), (1, 0x03, true)]);
+
+        let at: Vec<f64> = schedule.bytes.iter().map(|step| step.at).collect();
+        let expected = [0.0, 0.025, 0.3];
+        assert!(at.iter().zip(expected).all(|(at, expected)| (at - expected).abs() < 1e-6));
+        assert!((schedule.total_seconds - 0.575).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_led_schedule_matches_estimate() {
+        let packets = vec![vec![0x55; 10], vec![0x20, 0x00, 0x00, 0x03], vec![0x21]];
+        let timing = AdapterTiming::led(0.025, 0.25);
+        let schedule = TransmissionSchedule::new(&packets, &timing);
+
+        // The first byte waits for the adapter to start up
+        assert!((schedule.bytes[0].at - 0.5).abs() < 1e-6);
+        assert!((schedule.bytes[1].at - (0.5 + 0.05)).abs() < 1e-6);
+        assert!((schedule.total_seconds - estimate_transfer(&packets, &timing).total_seconds).abs() < 1e-6);
+    }
+}
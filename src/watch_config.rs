@@ -0,0 +1,652 @@
+//! Watch Config module
+//!
+//! A `WatchConfig` describes the desired state of the watch using the
+//! protocol-neutral models and assembles the full sync session for a
+//! protocol:
+//!
+//! ```text
+//! Sync, Start, [packets after start], Time (zone 1, 2), Alarm (1-5),
+//! Eeprom, SoundTheme, SoundOptions, WristApp, End
+//! ```
+//!
+//! Sessions can leave out sections (see `packets_with`), which the
+//! incremental sync in `sync_snapshot` uses to skip unchanged data.
+//!
+//! The builder validates singleton constraints (at most one time zone per
+//! zone number, at most one alarm per alarm number) and the size of the sound
+//! theme and wrist app, so every frontend emits the same well-formed packet
+//! stream.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::PacketGenerator;
+use crate::helpers::appointment_time::{self, QuantizationWarning};
+use crate::helpers::load_section::MAX_PAYLOAD_LENGTH;
+use crate::models::{
+    Alarm, Anniversary, Appointment, InvalidAlarmTime, ListEntry, NotificationMinutes, PhoneEntry, SoundOptions,
+    TimeZone,
+};
+use crate::{protocol_3, protocol_4, Protocol3, Protocol4};
+use crate::spc::{Spc, SpcError};
+
+/// Default number of sync bytes sent before the session
+pub const DEFAULT_SYNC_LENGTH: usize = 300;
+
+/// Number of alarms the watch holds
+pub const ALARM_COUNT: u8 = 5;
+
+/// Number of time zones the watch holds
+pub const TIME_ZONE_COUNT: u8 = 2;
+
+/// Protocol used to transmit a watch config
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProtocolVersion {
+    /// Protocol 3 (Timex Datalink 150)
+    Protocol3,
+    /// Protocol 4 (Timex Datalink 150s)
+    Protocol4,
+}
+
+impl ProtocolVersion {
+    /// Every supported protocol
+    pub const ALL: [ProtocolVersion; 2] = [ProtocolVersion::Protocol3, ProtocolVersion::Protocol4];
+
+    /// Look up a protocol by number
+    pub fn from_number(number: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|protocol| protocol.number() == number)
+    }
+
+    /// Protocol number (3 or 4)
+    pub fn number(self) -> u8 {
+        match self {
+            ProtocolVersion::Protocol3 => 3,
+            ProtocolVersion::Protocol4 => 4,
+        }
+    }
+}
+
+impl std::str::FromStr for ProtocolVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "3" => Ok(ProtocolVersion::Protocol3),
+            "4" => Ok(ProtocolVersion::Protocol4),
+            _ => Err(format!("unsupported protocol: {} (expected 3 or 4)", s)),
+        }
+    }
+}
+
+/// A section of the sync session between the start and end packets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Section {
+    /// Time zones
+    Time,
+    /// Alarms
+    Alarms,
+    /// Appointments, anniversaries, phone numbers and lists
+    Eeprom,
+    /// Sound theme
+    SoundTheme,
+    /// Sound options
+    SoundOptions,
+    /// Wrist app
+    WristApp,
+}
+
+impl Section {
+    /// Every section, in session order
+    pub const ALL: [Section; 6] = [
+        Section::Time,
+        Section::Alarms,
+        Section::Eeprom,
+        Section::SoundTheme,
+        Section::SoundOptions,
+        Section::WristApp,
+    ];
+
+    /// Human-readable section name
+    pub fn name(self) -> &'static str {
+        match self {
+            Section::Time => "time",
+            Section::Alarms => "alarms",
+            Section::Eeprom => "EEPROM",
+            Section::SoundTheme => "sound theme",
+            Section::SoundOptions => "sound options",
+            Section::WristApp => "wrist app",
+        }
+    }
+}
+
+/// Errors raised while building a watch config
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchConfigError {
+    /// A time zone number is not 1 or 2
+    InvalidTimeZone { zone: u8 },
+    /// A time zone number is used more than once
+    DuplicateTimeZone { zone: u8 },
+    /// An alarm number is not between 1 and 5
+    InvalidAlarmNumber { number: u8 },
+    /// An alarm number is used more than once
+    DuplicateAlarm { number: u8 },
+    /// An alarm time is not a valid time of day
+    InvalidAlarmTime { number: u8, hour: u8, minute: u8 },
+    /// The sound theme data is empty or does not fit the sound area
+    InvalidSoundTheme { error: SpcError },
+    /// The wrist app data does not fit a single load section
+    WristAppTooLarge { length: usize },
+}
+
+impl fmt::Display for WatchConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WatchConfigError::InvalidTimeZone { zone } => {
+                write!(f, "time zone {} is invalid (expected 1 to {})", zone, TIME_ZONE_COUNT)
+            }
+            WatchConfigError::DuplicateTimeZone { zone } => write!(f, "time zone {} is set more than once", zone),
+            WatchConfigError::InvalidAlarmNumber { number } => {
+                write!(f, "alarm {} is invalid (expected 1 to {})", number, ALARM_COUNT)
+            }
+            WatchConfigError::DuplicateAlarm { number } => write!(f, "alarm {} is set more than once", number),
+            WatchConfigError::InvalidAlarmTime { number, hour, minute } => {
+                write!(f, "alarm {} has an invalid time {:02}:{:02}", number, hour, minute)
+            }
+            WatchConfigError::InvalidSoundTheme { error } => write!(f, "invalid sound theme: {}", error),
+            WatchConfigError::WristAppTooLarge { length } => write!(
+                f,
+                "wrist app is {} bytes, a load section holds at most {} bytes",
+                length, MAX_PAYLOAD_LENGTH
+            ),
+        }
+    }
+}
+
+impl std::error::Error for WatchConfigError {}
+
+/// Desired state of the watch
+///
+/// Create one with `WatchConfig::builder()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatchConfig {
+    sync_length: usize,
+    after_start: Vec<Vec<u8>>,
+    time_zones: Vec<TimeZone>,
+    alarms: Vec<Alarm>,
+    appointments: Vec<Appointment>,
+    anniversaries: Vec<Anniversary>,
+    phone_entries: Vec<PhoneEntry>,
+    list_entries: Vec<ListEntry>,
+    appointment_notification: Option<NotificationMinutes>,
+    sound_theme: Option<Spc>,
+    sound_options: Option<SoundOptions>,
+    wrist_app: Option<Vec<u8>>,
+}
+
+impl WatchConfig {
+    /// Start building a watch config
+    pub fn builder() -> WatchConfigBuilder {
+        WatchConfigBuilder::default()
+    }
+
+    /// Time zones, ordered by zone number
+    pub fn time_zones(&self) -> &[TimeZone] {
+        &self.time_zones
+    }
+
+    /// Alarms, ordered by alarm number
+    pub fn alarms(&self) -> &[Alarm] {
+        &self.alarms
+    }
+
+    /// Whether the config has any EEPROM data to send
+    pub fn has_eeprom(&self) -> bool {
+        !self.appointments.is_empty()
+            || !self.anniversaries.is_empty()
+            || !self.phone_entries.is_empty()
+            || !self.list_entries.is_empty()
+            || self.appointment_notification.is_some()
+    }
+
+    /// Report every appointment whose displayed time differs from its requested time
+    pub fn appointment_quantization_report(&self) -> Vec<QuantizationWarning> {
+        self.appointments.iter()
+            .enumerate()
+            .filter_map(|(index, appointment)| {
+                appointment_time::quantization_warning(
+                    index,
+                    &appointment.message,
+                    appointment.time,
+                    appointment.rounding,
+                )
+            })
+            .collect()
+    }
+
+    /// Protocol 3 models of every section in the config, in session order
+    fn protocol_3_sections(&self) -> Vec<(Section, Vec<Box<dyn PacketGenerator>>)> {
+        let mut sections: Vec<(Section, Vec<Box<dyn PacketGenerator>>)> = Vec::new();
+
+        if !self.time_zones.is_empty() {
+            sections.push((Section::Time, self.time_zones.iter()
+                .map(|time_zone| Box::new(protocol_3::Time::from(time_zone)) as Box<dyn PacketGenerator>)
+                .collect()));
+        }
+        if !self.alarms.is_empty() {
+            sections.push((Section::Alarms, self.alarms.iter()
+                .map(|alarm| {
+                    let alarm = protocol_3::Alarm::try_from(alarm).expect("alarm times are validated by build");
+                    Box::new(alarm) as Box<dyn PacketGenerator>
+                })
+                .collect()));
+        }
+        if self.has_eeprom() {
+            let mut eeprom = protocol_3::Eeprom::new();
+            eeprom.appointments = self.appointments.iter().map(Into::into).collect();
+            eeprom.anniversaries = self.anniversaries.iter().map(Into::into).collect();
+            eeprom.phone_numbers = self.phone_entries.iter().map(Into::into).collect();
+            eeprom.lists = self.list_entries.iter().map(Into::into).collect();
+            eeprom.appointment_notification_minutes = self.appointment_notification;
+            sections.push((Section::Eeprom, vec![Box::new(eeprom)]));
+        }
+        if let Some(sound_theme) = &self.sound_theme {
+            sections.push((Section::SoundTheme, vec![Box::new(protocol_3::SoundTheme::from_spc(sound_theme))]));
+        }
+        if let Some(sound_options) = &self.sound_options {
+            sections.push((Section::SoundOptions, vec![Box::new(protocol_3::SoundOptions::from(sound_options))]));
+        }
+        if let Some(wrist_app) = &self.wrist_app {
+            sections.push((Section::WristApp, vec![Box::new(protocol_3::WristApp::new(wrist_app.clone()))]));
+        }
+
+        sections
+    }
+
+    /// Protocol 4 models of every section in the config, in session order
+    fn protocol_4_sections(&self) -> Vec<(Section, Vec<Box<dyn PacketGenerator>>)> {
+        let mut sections: Vec<(Section, Vec<Box<dyn PacketGenerator>>)> = Vec::new();
+
+        if !self.time_zones.is_empty() {
+            sections.push((Section::Time, self.time_zones.iter()
+                .map(|time_zone| Box::new(protocol_4::Time::from(time_zone)) as Box<dyn PacketGenerator>)
+                .collect()));
+        }
+        if !self.alarms.is_empty() {
+            sections.push((Section::Alarms, self.alarms.iter()
+                .map(|alarm| {
+                    let alarm = protocol_4::Alarm::try_from(alarm).expect("alarm times are validated by build");
+                    Box::new(alarm) as Box<dyn PacketGenerator>
+                })
+                .collect()));
+        }
+        if self.has_eeprom() {
+            sections.push((Section::Eeprom, vec![Box::new(protocol_4::Eeprom {
+                appointments: self.appointments.iter().map(Into::into).collect(),
+                anniversaries: self.anniversaries.iter().map(Into::into).collect(),
+                phone_numbers: self.phone_entries.iter().map(Into::into).collect(),
+                lists: self.list_entries.iter().map(Into::into).collect(),
+                appointment_notification_minutes: self.appointment_notification,
+            })]));
+        }
+        if let Some(sound_theme) = &self.sound_theme {
+            sections.push((Section::SoundTheme, vec![Box::new(protocol_4::SoundTheme::from_spc(sound_theme))]));
+        }
+        if let Some(sound_options) = &self.sound_options {
+            sections.push((Section::SoundOptions, vec![Box::new(protocol_4::SoundOptions::from(sound_options))]));
+        }
+        if let Some(wrist_app) = &self.wrist_app {
+            sections.push((Section::WristApp, vec![Box::new(protocol_4::WristApp {
+                wrist_app_data: wrist_app.clone(),
+            })]));
+        }
+
+        sections
+    }
+
+    /// Assemble the sync session as Protocol 3 models
+    pub fn protocol_3(&self) -> Protocol3 {
+        self.protocol_3_with(|_| true)
+    }
+
+    /// Assemble a sync session with only some sections as Protocol 3 models
+    ///
+    /// # Arguments
+    ///
+    /// * `include` - Returns whether a section is sent
+    pub fn protocol_3_with<F: Fn(Section) -> bool>(&self, include: F) -> Protocol3 {
+        let mut protocol = Protocol3::new();
+
+        protocol.add(protocol_3::Sync { length: self.sync_length });
+        protocol.add(protocol_3::Start);
+        protocol.add(RawPackets(self.after_start.clone()));
+
+        for (section, models) in self.protocol_3_sections() {
+            if include(section) {
+                protocol.add(Models(models));
+            }
+        }
+
+        protocol.add(protocol_3::End);
+        protocol
+    }
+
+    /// Assemble the sync session as Protocol 4 models
+    pub fn protocol_4(&self) -> Protocol4 {
+        self.protocol_4_with(|_| true)
+    }
+
+    /// Assemble a sync session with only some sections as Protocol 4 models
+    ///
+    /// # Arguments
+    ///
+    /// * `include` - Returns whether a section is sent
+    pub fn protocol_4_with<F: Fn(Section) -> bool>(&self, include: F) -> Protocol4 {
+        let mut protocol = Protocol4::new();
+
+        protocol.add(protocol_4::Sync { length: self.sync_length });
+        protocol.add(protocol_4::Start {});
+        protocol.add(RawPackets(self.after_start.clone()));
+
+        for (section, models) in self.protocol_4_sections() {
+            if include(section) {
+                protocol.add(Models(models));
+            }
+        }
+
+        protocol.add(protocol_4::End {});
+        protocol
+    }
+
+    /// Generate the packet stream of the sync session for a protocol
+    pub fn packets(&self, protocol: ProtocolVersion) -> Vec<Vec<u8>> {
+        self.packets_with(protocol, |_| true)
+    }
+
+    /// Generate the packet stream of a sync session with only some sections
+    ///
+    /// # Arguments
+    ///
+    /// * `protocol` - The protocol to generate packets for
+    /// * `include` - Returns whether a section is sent
+    pub fn packets_with<F: Fn(Section) -> bool>(&self, protocol: ProtocolVersion, include: F) -> Vec<Vec<u8>> {
+        match protocol {
+            ProtocolVersion::Protocol3 => self.protocol_3_with(include).packets(),
+            ProtocolVersion::Protocol4 => self.protocol_4_with(include).packets(),
+        }
+    }
+
+    /// Generate the packets of each section in the config
+    ///
+    /// # Returns
+    ///
+    /// The sections present in the config with their packets, in session order
+    pub fn section_packets(&self, protocol: ProtocolVersion) -> Vec<(Section, Vec<Vec<u8>>)> {
+        let sections = match protocol {
+            ProtocolVersion::Protocol3 => self.protocol_3_sections(),
+            ProtocolVersion::Protocol4 => self.protocol_4_sections(),
+        };
+
+        sections.into_iter()
+            .map(|(section, models)| (section, Models(models).packets()))
+            .collect()
+    }
+}
+
+/// Builder for `WatchConfig`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatchConfigBuilder {
+    config: WatchConfig,
+    sound_theme_data: Option<Vec<u8>>,
+}
+
+impl Default for WatchConfigBuilder {
+    fn default() -> Self {
+        WatchConfigBuilder {
+            config: WatchConfig {
+                sync_length: DEFAULT_SYNC_LENGTH,
+                after_start: Vec::new(),
+                time_zones: Vec::new(),
+                alarms: Vec::new(),
+                appointments: Vec::new(),
+                anniversaries: Vec::new(),
+                phone_entries: Vec::new(),
+                list_entries: Vec::new(),
+                appointment_notification: None,
+                sound_theme: None,
+                sound_options: None,
+                wrist_app: None,
+            },
+            sound_theme_data: None,
+        }
+    }
+}
+
+impl WatchConfigBuilder {
+    /// Set the number of sync bytes sent before the session
+    pub fn sync_length(mut self, length: usize) -> Self {
+        self.config.sync_length = length;
+        self
+    }
+
+    /// Send CRC-wrapped packets right after the start packet (e.g. a start beep)
+    pub fn after_start(mut self, packets: Vec<Vec<u8>>) -> Self {
+        self.config.after_start.extend(packets);
+        self
+    }
+
+    /// Set one of the two time zones
+    pub fn time_zone(mut self, time_zone: TimeZone) -> Self {
+        self.config.time_zones.push(time_zone);
+        self
+    }
+
+    /// Set one of the five alarms
+    pub fn alarm(mut self, alarm: Alarm) -> Self {
+        self.config.alarms.push(alarm);
+        self
+    }
+
+    /// Add an appointment
+    pub fn appointment(mut self, appointment: Appointment) -> Self {
+        self.config.appointments.push(appointment);
+        self
+    }
+
+    /// Add an anniversary
+    pub fn anniversary(mut self, anniversary: Anniversary) -> Self {
+        self.config.anniversaries.push(anniversary);
+        self
+    }
+
+    /// Add a phone book entry
+    pub fn phone_entry(mut self, phone_entry: PhoneEntry) -> Self {
+        self.config.phone_entries.push(phone_entry);
+        self
+    }
+
+    /// Add a to-do list entry
+    pub fn list_entry(mut self, list_entry: ListEntry) -> Self {
+        self.config.list_entries.push(list_entry);
+        self
+    }
+
+    /// Set how long before an appointment the watch notifies
+    pub fn appointment_notification(mut self, notification: NotificationMinutes) -> Self {
+        self.config.appointment_notification = Some(notification);
+        self
+    }
+
+    /// Set the sound theme data (the SPC file contents, the header is optional)
+    pub fn sound_theme(mut self, sound_theme_data: Vec<u8>) -> Self {
+        self.sound_theme_data = Some(sound_theme_data);
+        self
+    }
+
+    /// Set the sound options
+    pub fn sound_options(mut self, sound_options: SoundOptions) -> Self {
+        self.config.sound_options = Some(sound_options);
+        self
+    }
+
+    /// Set the wrist app data
+    pub fn wrist_app(mut self, wrist_app_data: Vec<u8>) -> Self {
+        self.config.wrist_app = Some(wrist_app_data);
+        self
+    }
+
+    /// Validate the config and order time zones and alarms by number
+    ///
+    /// # Returns
+    ///
+    /// The watch config, or an error describing the first invalid setting
+    pub fn build(self) -> Result<WatchConfig, WatchConfigError> {
+        let mut config = self.config;
+
+        let mut time_zones = BTreeMap::new();
+        for time_zone in config.time_zones {
+            let zone = time_zone.zone;
+            if !(1..=TIME_ZONE_COUNT).contains(&zone) {
+                return Err(WatchConfigError::InvalidTimeZone { zone });
+            }
+            if time_zones.insert(zone, time_zone).is_some() {
+                return Err(WatchConfigError::DuplicateTimeZone { zone });
+            }
+        }
+        config.time_zones = time_zones.into_values().collect();
+
+        let mut alarms = BTreeMap::new();
+        for alarm in config.alarms {
+            let number = alarm.number;
+            if !(1..=ALARM_COUNT).contains(&number) {
+                return Err(WatchConfigError::InvalidAlarmNumber { number });
+            }
+            if let Err(InvalidAlarmTime { hour, minute }) = alarm.time() {
+                return Err(WatchConfigError::InvalidAlarmTime { number, hour, minute });
+            }
+            if alarms.insert(number, alarm).is_some() {
+                return Err(WatchConfigError::DuplicateAlarm { number });
+            }
+        }
+        config.alarms = alarms.into_values().collect();
+
+        if let Some(sound_theme_data) = self.sound_theme_data {
+            let spc = Spc::from_bytes(&sound_theme_data)
+                .map_err(|error| WatchConfigError::InvalidSoundTheme { error })?;
+            config.sound_theme = Some(spc);
+        }
+
+        if let Some(wrist_app) = &config.wrist_app {
+            if wrist_app.len() > MAX_PAYLOAD_LENGTH {
+                return Err(WatchConfigError::WristAppTooLarge { length: wrist_app.len() });
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+/// Packets sent as-is
+struct RawPackets(Vec<Vec<u8>>);
+
+impl PacketGenerator for RawPackets {
+    fn packets(&self) -> Vec<Vec<u8>> {
+        self.0.clone()
+    }
+}
+
+/// The models of one section, sent in order
+struct Models(Vec<Box<dyn PacketGenerator>>);
+
+impl PacketGenerator for Models {
+    fn packets(&self) -> Vec<Vec<u8>> {
+        self.0.iter()
+            .flat_map(|model| model.packets())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::DateFormat;
+    use chrono::{TimeZone as _, Utc};
+
+    fn time_zone(zone: u8, name: &str) -> TimeZone {
+        TimeZone {
+            zone,
+            is_24h: true,
+            date_format: DateFormat::YearDotMonthDotDay,
+            time: Utc.with_ymd_and_hms(2022, 9, 5, 3, 39, 44).unwrap().into(),
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_session_order() {
+        let config = WatchConfig::builder()
+            .sync_length(10)
+            .alarm(Alarm::new(2, 12, 30, "Lunch".to_string()))
+            .time_zone(time_zone(2, "UTC"))
+            .alarm(Alarm::new(1, 9, 0, "Wake up".to_string()))
+            .time_zone(time_zone(1, "PDT"))
+            .sound_options(SoundOptions { hourly_chime: true, button_beep: false })
+            .build()
+            .unwrap();
+
+        let mut expected = Protocol3::new();
+        expected.add(protocol_3::Sync { length: 10 });
+        expected.add(protocol_3::Start);
+        expected.add(protocol_3::Time::from(&time_zone(1, "PDT")));
+        expected.add(protocol_3::Time::from(&time_zone(2, "UTC")));
+        expected.add(protocol_3::Alarm::try_from(&Alarm::new(1, 9, 0, "Wake up".to_string())).unwrap());
+        expected.add(protocol_3::Alarm::try_from(&Alarm::new(2, 12, 30, "Lunch".to_string())).unwrap());
+        expected.add(protocol_3::SoundOptions { hourly_chime: true, button_beep: false });
+        expected.add(protocol_3::End);
+
+        assert_eq!(config.packets(ProtocolVersion::Protocol3), expected.packets());
+        assert!(!config.has_eeprom());
+
+        // Protocol 4 only differs in the start packet
+        let protocol_4_packets = config.packets(ProtocolVersion::Protocol4);
+        assert_eq!(protocol_4_packets[1], protocol_4::Start {}.packets()[0]);
+        assert_eq!(protocol_4_packets[2..], expected.packets()[2..]);
+    }
+
+    #[test]
+    fn test_build_errors() {
+        let build = |builder: WatchConfigBuilder| builder.build().err();
+
+        assert_eq!(
+            build(WatchConfig::builder().time_zone(time_zone(3, "CET"))),
+            Some(WatchConfigError::InvalidTimeZone { zone: 3 })
+        );
+        assert_eq!(
+            build(WatchConfig::builder().time_zone(time_zone(1, "PDT")).time_zone(time_zone(1, "UTC"))),
+            Some(WatchConfigError::DuplicateTimeZone { zone: 1 })
+        );
+        assert_eq!(
+            build(WatchConfig::builder().alarm(Alarm::new(6, 9, 0, "Nope".to_string()))),
+            Some(WatchConfigError::InvalidAlarmNumber { number: 6 })
+        );
+        assert_eq!(
+            build(WatchConfig::builder().alarm(Alarm::new(1, 9, 0, "A".to_string())).alarm(Alarm::new(1, 10, 0, "B".to_string()))),
+            Some(WatchConfigError::DuplicateAlarm { number: 1 })
+        );
+        assert_eq!(
+            build(WatchConfig::builder().alarm(Alarm::new(1, 24, 0, "Nope".to_string()))),
+            Some(WatchConfigError::InvalidAlarmTime { number: 1, hour: 24, minute: 0 })
+        );
+        assert_eq!(
+            build(WatchConfig::builder().sound_theme(vec![0x11; 0x101])),
+            Some(WatchConfigError::InvalidSoundTheme { error: SpcError::TooLarge { length: 0x101 } })
+        );
+        assert_eq!(
+            build(WatchConfig::builder().wrist_app(vec![0x9d; MAX_PAYLOAD_LENGTH + 1])),
+            Some(WatchConfigError::WristAppTooLarge { length: MAX_PAYLOAD_LENGTH + 1 })
+        );
+        assert!(build(WatchConfig::builder().wrist_app(vec![0x9d; MAX_PAYLOAD_LENGTH])).is_none());
+    }
+}
@@ -0,0 +1,606 @@
+//! Web Config module
+//!
+//! This module defines the typed configuration the web app hands to the
+//! WebAssembly API. It is deserialized with serde, so any JavaScript object
+//! (or JSON document) of the documented shape is accepted, and every problem
+//! is reported as a `ConfigError` carrying the path of the offending field,
+//! e.g. `appointments[2].date`, instead of being logged and defaulted.
+//!
+//! Field names are camelCase to match the JavaScript side. The TypeScript
+//! definitions shipped with the wasm package describe the same shape.
+
+use std::fmt;
+use std::time::{Duration, UNIX_EPOCH};
+
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::helpers::appointment_time::RoundingPolicy;
+use crate::models::{self, DateFormat, NotificationMinutes, PhoneType, Priority};
+use crate::watch_config::{ProtocolVersion, WatchConfig, WatchConfigError};
+use crate::zap::Zap;
+
+/// Sync length used when a web config does not set one
+///
+/// Shorter than the `watch_config::DEFAULT_SYNC_LENGTH` used elsewhere.
+pub const WEB_DEFAULT_SYNC_LENGTH: usize = 150;
+
+/// A problem with one field of a web config
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConfigError {
+    /// Path of the field, e.g. `alarms[0].hour` (empty for the whole config)
+    pub path: String,
+
+    /// What is wrong with the field
+    pub message: String,
+}
+
+impl ConfigError {
+    fn new(path: impl Into<String>, message: impl Into<String>) -> Self {
+        ConfigError { path: path.into(), message: message.into() }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Settings of one time zone
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebTimeZone {
+    /// Name shown on the watch (max 3 characters)
+    pub name: String,
+
+    /// Whether to use 24-hour time format
+    #[serde(default = "default_true")]
+    pub is24h: bool,
+
+    /// Date format name, e.g. "DayDashMonthDashYear"
+    #[serde(default)]
+    pub date_format: Option<String>,
+
+    /// Time to set, as seconds since the Unix epoch
+    pub timestamp: u64,
+}
+
+/// An alarm
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebAlarm {
+    /// Alarm number (1-5)
+    pub number: u8,
+
+    /// Whether the alarm beeps
+    #[serde(default = "default_true")]
+    pub audible: bool,
+
+    /// Hour (0-23)
+    pub hour: u8,
+
+    /// Minute (0-59)
+    pub minute: u8,
+
+    /// Message shown when the alarm goes off
+    #[serde(default)]
+    pub message: String,
+}
+
+/// An appointment
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebAppointment {
+    /// Wall-clock time as `YYYY-MM-DDTHH:MM` (or just `YYYY-MM-DD`)
+    pub date: String,
+
+    /// Appointment text
+    #[serde(default)]
+    pub message: String,
+}
+
+/// An anniversary
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebAnniversary {
+    /// Date as `YYYY-MM-DD`, only the month and day are stored
+    pub date: String,
+
+    /// Anniversary text
+    #[serde(default)]
+    pub message: String,
+}
+
+/// A phone number
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebPhoneNumber {
+    /// Contact name
+    pub name: String,
+
+    /// Phone number
+    pub number: String,
+
+    /// Type code, e.g. "H" for home (defaults to other)
+    #[serde(rename = "type", default)]
+    pub type_code: String,
+}
+
+impl From<&models::PhoneEntry> for WebPhoneNumber {
+    fn from(entry: &models::PhoneEntry) -> Self {
+        let type_code = match entry.phone_type {
+            PhoneType::Other => "O".to_string(),
+            phone_type => phone_type.code().to_ascii_uppercase().to_string(),
+        };
+
+        WebPhoneNumber { name: entry.name.clone(), number: entry.number.clone(), type_code }
+    }
+}
+
+/// A list entry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebListEntry {
+    /// Entry text
+    pub entry: String,
+
+    /// Priority (1-5, 0 for none)
+    #[serde(default)]
+    pub priority: u8,
+}
+
+/// Sound settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebSoundOptions {
+    /// Whether the watch chimes every hour
+    #[serde(default = "default_true")]
+    pub hourly_chime: bool,
+
+    /// Whether buttons beep
+    #[serde(default = "default_true")]
+    pub button_beep: bool,
+}
+
+impl Default for WebSoundOptions {
+    fn default() -> Self {
+        WebSoundOptions { hourly_chime: true, button_beep: true }
+    }
+}
+
+/// Configuration sent by the web app
+///
+/// Sections are only sent when their `include*` flag is set.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WebConfig {
+    /// Length of the sync signal
+    pub sync_length: usize,
+
+    /// Whether to send the time zones
+    pub include_time: bool,
+    /// Time zone 1
+    pub time1: Option<WebTimeZone>,
+    /// Time zone 2
+    pub time2: Option<WebTimeZone>,
+
+    /// Whether to send the alarms
+    pub include_alarms: bool,
+    /// Alarms
+    pub alarms: Vec<WebAlarm>,
+
+    /// Whether to send the EEPROM data (appointments, anniversaries, phone numbers and lists)
+    pub include_eeprom: bool,
+    /// Minutes before an appointment to notify (0, 5, 10, 15, 20 or 30)
+    pub appointment_notification: Option<u8>,
+    /// How appointment times are rounded ("floor", "nearest" or "ceil")
+    pub appointment_rounding: Option<String>,
+    /// Appointments
+    pub appointments: Vec<WebAppointment>,
+    /// Anniversaries
+    pub anniversaries: Vec<WebAnniversary>,
+    /// Phone numbers
+    pub phone_numbers: Vec<WebPhoneNumber>,
+    /// List entries
+    pub lists: Vec<WebListEntry>,
+
+    /// Whether to send the sound options
+    pub include_sound_options: bool,
+    /// Sound options
+    pub sound_options: WebSoundOptions,
+
+    /// Whether to send the sound theme
+    pub include_sound_theme: bool,
+    /// Sound theme (SPC file contents)
+    pub sound_theme_data: Option<Vec<u8>>,
+
+    /// Whether to send the wrist app
+    pub include_wrist_app: bool,
+    /// Wrist app (ZAP file contents or raw code)
+    pub wrist_app_data: Option<Vec<u8>>,
+}
+
+impl Default for WebConfig {
+    fn default() -> Self {
+        WebConfig {
+            sync_length: WEB_DEFAULT_SYNC_LENGTH,
+            include_time: false,
+            time1: None,
+            time2: None,
+            include_alarms: false,
+            alarms: Vec::new(),
+            include_eeprom: false,
+            appointment_notification: None,
+            appointment_rounding: None,
+            appointments: Vec::new(),
+            anniversaries: Vec::new(),
+            phone_numbers: Vec::new(),
+            lists: Vec::new(),
+            include_sound_options: true,
+            sound_options: WebSoundOptions::default(),
+            include_sound_theme: false,
+            sound_theme_data: None,
+            include_wrist_app: false,
+            wrist_app_data: None,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl WebConfig {
+    /// Deserialize a config, reporting the path of the field that failed
+    ///
+    /// # Arguments
+    ///
+    /// * `deserializer` - Any serde deserializer, e.g. `serde_wasm_bindgen::Deserializer`
+    pub fn deserialize_with_path<'de, D>(deserializer: D) -> Result<Self, ConfigError>
+    where
+        D: Deserializer<'de>,
+        D::Error: fmt::Display,
+    {
+        serde_path_to_error::deserialize(deserializer).map_err(|e| {
+            let path = e.path().to_string();
+            let path = if path == "." { String::new() } else { path };
+            ConfigError::new(path, e.into_inner().to_string())
+        })
+    }
+
+    /// Parse a config from JSON
+    pub fn from_json(json: &str) -> Result<Self, ConfigError> {
+        Self::deserialize_with_path(&mut serde_json::Deserializer::from_str(json))
+    }
+
+    /// Validate the config and describe the watch state it sets
+    ///
+    /// # Arguments
+    ///
+    /// * `protocol` - Protocol of the target watch, which picks the wrist app variant of ZAP files
+    ///
+    /// # Returns
+    ///
+    /// The watch config, or every problem found
+    pub fn to_watch_config(&self, protocol: ProtocolVersion) -> Result<WatchConfig, Vec<ConfigError>> {
+        let mut errors = Vec::new();
+        let mut config = WatchConfig::builder().sync_length(self.sync_length);
+
+        if self.include_time {
+            let zones = [
+                ("time1", &self.time1, DateFormat::DayDashMonthDashYear),
+                ("time2", &self.time2, DateFormat::YearDotMonthDotDay),
+            ];
+            for (zone, (path, time_zone, default_format)) in (1..).zip(zones) {
+                let Some(time_zone) = time_zone else {
+                    errors.push(ConfigError::new(path, "required when includeTime is set"));
+                    continue;
+                };
+
+                let date_format = match &time_zone.date_format {
+                    None => default_format,
+                    Some(format) => match format.parse() {
+                        Ok(format) => format,
+                        Err(_) => {
+                            errors.push(ConfigError::new(
+                                format!("{}.dateFormat", path),
+                                format!("unknown date format \"{}\"", format),
+                            ));
+                            continue;
+                        }
+                    },
+                };
+
+                config = config.time_zone(models::TimeZone {
+                    zone,
+                    is_24h: time_zone.is24h,
+                    date_format,
+                    time: UNIX_EPOCH + Duration::from_secs(time_zone.timestamp),
+                    name: time_zone.name.clone(),
+                });
+            }
+        }
+
+        if self.include_alarms {
+            for alarm in &self.alarms {
+                config = config.alarm(models::Alarm {
+                    number: alarm.number,
+                    audible: alarm.audible,
+                    hour: alarm.hour,
+                    minute: alarm.minute,
+                    message: alarm.message.clone(),
+                });
+            }
+        }
+
+        if self.include_eeprom {
+            if let Some(minutes) = self.appointment_notification {
+                match NotificationMinutes::from_minutes(minutes) {
+                    Some(notification) => config = config.appointment_notification(notification),
+                    None => errors.push(ConfigError::new(
+                        "appointmentNotification",
+                        format!("{} minutes is not supported (expected 0, 5, 10, 15, 20 or 30)", minutes),
+                    )),
+                }
+            }
+
+            let rounding = match &self.appointment_rounding {
+                None => RoundingPolicy::default(),
+                Some(policy) => policy.parse().unwrap_or_else(|_| {
+                    errors.push(ConfigError::new(
+                        "appointmentRounding",
+                        format!("unknown rounding \"{}\" (expected floor, nearest or ceil)", policy),
+                    ));
+                    RoundingPolicy::default()
+                }),
+            };
+
+            for (index, appointment) in self.appointments.iter().enumerate() {
+                match parse_appointment_time(&appointment.date) {
+                    Some(time) => config = config.appointment(models::Appointment {
+                        time,
+                        message: appointment.message.clone(),
+                        rounding,
+                    }),
+                    None => errors.push(ConfigError::new(
+                        format!("appointments[{}].date", index),
+                        format!("invalid date \"{}\" (expected YYYY-MM-DDTHH:MM)", appointment.date),
+                    )),
+                }
+            }
+
+            for (index, anniversary) in self.anniversaries.iter().enumerate() {
+                match parse_anniversary_date(&anniversary.date) {
+                    Some(date) => {
+                        config = config.anniversary(models::Anniversary::from_date(date, anniversary.message.clone()))
+                    }
+                    None => errors.push(ConfigError::new(
+                        format!("anniversaries[{}].date", index),
+                        format!("invalid date \"{}\" (expected YYYY-MM-DD)", anniversary.date),
+                    )),
+                }
+            }
+
+            for phone in &self.phone_numbers {
+                config = config.phone_entry(models::PhoneEntry::new(
+                    phone.name.clone(),
+                    phone.number.clone(),
+                    PhoneType::from_code(&phone.type_code),
+                ));
+            }
+
+            for (index, list) in self.lists.iter().enumerate() {
+                let priority = match list.priority {
+                    0 => None,
+                    value => match Priority::from_value(value) {
+                        Some(priority) => Some(priority),
+                        None => {
+                            errors.push(ConfigError::new(
+                                format!("lists[{}].priority", index),
+                                format!("priority {} is invalid (expected 0 to 5)", value),
+                            ));
+                            continue;
+                        }
+                    },
+                };
+                config = config.list_entry(models::ListEntry::new(list.entry.clone(), priority));
+            }
+        }
+
+        if self.include_sound_options {
+            config = config.sound_options(models::SoundOptions {
+                hourly_chime: self.sound_options.hourly_chime,
+                button_beep: self.sound_options.button_beep,
+            });
+        }
+
+        if self.include_sound_theme {
+            match &self.sound_theme_data {
+                Some(data) => config = config.sound_theme(data.clone()),
+                None => errors.push(ConfigError::new("soundThemeData", "required when includeSoundTheme is set")),
+            }
+        }
+
+        if self.include_wrist_app {
+            match self.wrist_app_data.as_deref().map(|data| wrist_app_code(data, protocol)) {
+                Some(Ok(code)) => config = config.wrist_app(code),
+                Some(Err(e)) => errors.push(e),
+                None => errors.push(ConfigError::new("wristAppData", "required when includeWristApp is set")),
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        config.build().map_err(|e| vec![self.watch_config_error(e)])
+    }
+
+    // Attach the path of the field that caused a builder error
+    fn watch_config_error(&self, error: WatchConfigError) -> ConfigError {
+        let path = match &error {
+            WatchConfigError::InvalidTimeZone { zone } | WatchConfigError::DuplicateTimeZone { zone } => {
+                format!("time{}", zone)
+            }
+            WatchConfigError::InvalidAlarmNumber { number }
+            | WatchConfigError::DuplicateAlarm { number }
+            | WatchConfigError::InvalidAlarmTime { number, .. } => {
+                // Duplicates are reported at the last alarm using the number
+                match self.alarms.iter().rposition(|alarm| alarm.number == *number) {
+                    Some(index) => format!("alarms[{}]", index),
+                    None => "alarms".to_string(),
+                }
+            }
+            WatchConfigError::InvalidSoundTheme { .. } => "soundThemeData".to_string(),
+            WatchConfigError::WristAppTooLarge { .. } => "wristAppData".to_string(),
+        };
+
+        ConfigError::new(path, error.to_string())
+    }
+}
+
+// Extract the code of a wrist app, which is either a ZAP file or raw code
+fn wrist_app_code(data: &[u8], protocol: ProtocolVersion) -> Result<Vec<u8>, ConfigError> {
+    if !Zap::is_zap(data) {
+        return Ok(data.to_vec());
+    }
+
+    Zap::parse(data)
+        .and_then(|zap| zap.code(protocol).map(<[u8]>::to_vec))
+        .map_err(|e| ConfigError::new("wristAppData", format!("invalid ZAP file: {}", e)))
+}
+
+// Parse a datetime-local string (YYYY-MM-DDTHH:MM) into the watch's wall-clock time
+fn parse_appointment_time(date_str: &str) -> Option<NaiveDateTime> {
+    for format in ["%Y-%m-%dT%H:%M", "%Y-%m-%dT%H:%M:%S"] {
+        if let Ok(datetime) = NaiveDateTime::parse_from_str(date_str, format) {
+            return Some(datetime);
+        }
+    }
+
+    // Try to parse just a date (YYYY-MM-DD)
+    NaiveDate::parse_from_str(date_str, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+}
+
+// Parse the month and day of an anniversary date string (YYYY-MM-DD)
+fn parse_anniversary_date(date_str: &str) -> Option<NaiveDate> {
+    // Ignore any time part (YYYY-MM-DDTHH:MM)
+    let date_part = date_str.get(..10).unwrap_or(date_str);
+    NaiveDate::parse_from_str(date_part, "%Y-%m-%d").ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_json() {
+        let config = WebConfig::from_json(r#"{
+            "includeTime": true,
+            "time1": { "zone": 1, "name": "HOM", "is24h": false, "dateFormat": "MonthDashDayDashYear", "timestamp": 0, "offsetHours": 2 },
+            "time2": { "name": "UTC", "timestamp": 0 },
+            "includeAlarms": true,
+            "alarms": [{ "number": 1, "hour": 7, "minute": 30, "message": "WAKE" }],
+            "phoneNumbers": [{ "name": "HOME", "number": "5551234", "type": "H" }]
+        }"#).unwrap();
+
+        assert_eq!(config.sync_length, WEB_DEFAULT_SYNC_LENGTH);
+        assert!(config.include_sound_options);
+        assert!(config.alarms[0].audible);
+        assert_eq!(config.phone_numbers[0].type_code, "H");
+
+        let watch_config = config.to_watch_config(ProtocolVersion::Protocol3).unwrap();
+        assert_eq!(watch_config.time_zones().len(), 2);
+        assert_eq!(watch_config.time_zones()[0].date_format, DateFormat::MonthDashDayDashYear);
+        assert_eq!(watch_config.time_zones()[1].date_format, DateFormat::YearDotMonthDotDay);
+        assert!(!watch_config.packets(ProtocolVersion::Protocol3).is_empty());
+    }
+
+    #[test]
+    fn test_from_json_errors() {
+        assert_eq!(
+            WebConfig::from_json(r#"{ "alarms": [{ "number": 1, "hour": "7", "minute": 0 }] }"#).unwrap_err().path,
+            "alarms[0].hour"
+        );
+        assert_eq!(WebConfig::from_json(r#"{ "time1": { "name": "HOM" } }"#).unwrap_err().path, "time1");
+        assert_eq!(WebConfig::from_json("3").unwrap_err().path, "");
+    }
+
+    #[test]
+    fn test_to_watch_config_errors() {
+        let config = WebConfig::from_json(r#"{
+            "includeTime": true,
+            "time1": { "name": "HOM", "dateFormat": "Sideways", "timestamp": 0 },
+            "includeEeprom": true,
+            "appointmentNotification": 7,
+            "appointments": [{ "date": "2025-01-01T09:00" }, { "date": "soon" }],
+            "lists": [{ "entry": "MILK", "priority": 9 }],
+            "includeWristApp": true
+        }"#).unwrap();
+
+        let paths: Vec<String> = config.to_watch_config(ProtocolVersion::Protocol3).unwrap_err().into_iter().map(|e| e.path).collect();
+        assert_eq!(paths, [
+            "time1.dateFormat",
+            "time2",
+            "appointmentNotification",
+            "appointments[1].date",
+            "lists[0].priority",
+            "wristAppData",
+        ]);
+
+        let config = WebConfig::from_json(r#"{
+            "includeAlarms": true,
+            "alarms": [{ "number": 1, "hour": 7, "minute": 0 }, { "number": 2, "hour": 25, "minute": 0 }]
+        }"#).unwrap();
+        let errors = config.to_watch_config(ProtocolVersion::Protocol3).unwrap_err();
+        assert_eq!(errors, vec![ConfigError::new("alarms[1]", "alarm 2 has an invalid time 25:00")]);
+        assert_eq!(errors[0].to_string(), "alarms[1]: alarm 2 has an invalid time 25:00");
+    }
+
+    #[test]
+    fn test_wrist_app_variant() {
+        let zap = crate::zap::builder::ZapBuilder::new("App")
+            .code(ProtocolVersion::Protocol4, vec![0x01, 0x02])
+            .build()
+            .unwrap();
+        let config = WebConfig {
+            include_wrist_app: true,
+            wrist_app_data: Some(zap.to_bytes()),
+            ..WebConfig::default()
+        };
+
+        assert!(config.to_watch_config(ProtocolVersion::Protocol4).is_ok());
+
+        let errors = config.to_watch_config(ProtocolVersion::Protocol3).unwrap_err();
+        assert_eq!(errors[0].path, "wristAppData");
+        assert!(matches!(ProtocolVersion::from_number(4), Some(ProtocolVersion::Protocol4)));
+        assert_eq!(ProtocolVersion::from_number(5), None);
+    }
+
+    #[test]
+    fn test_wrist_app_raw_code() {
+        // 0xAC is an ordinary byte in 6805 code (ADD with an immediate operand)
+        let code = vec![0xa6, 0x01, 0xac, 0x02, 0x81];
+        assert_eq!(wrist_app_code(&code, ProtocolVersion::Protocol3), Ok(code.clone()));
+
+        let config = WebConfig {
+            include_wrist_app: true,
+            wrist_app_data: Some(code),
+            ..WebConfig::default()
+        };
+        assert!(config.to_watch_config(ProtocolVersion::Protocol3).is_ok());
+
+        // Uploads that do not fit a load section are rejected, not truncated
+        let config = WebConfig {
+            include_wrist_app: true,
+            wrist_app_data: Some(vec![0x9d; 0x2000]),
+            ..WebConfig::default()
+        };
+        let errors = config.to_watch_config(ProtocolVersion::Protocol3).unwrap_err();
+        assert_eq!(errors[0].path, "wristAppData");
+    }
+}
@@ -0,0 +1,435 @@
+//! ZAP wrist app module
+//!
+//! This module parses ZAP wrist app files. A ZAP file is a list of text
+//! fields, each ended by a `0xAC` byte followed by a comment up to the end
+//! of the line:
+//!
+//! ```text
+//! TDL0724962¬      Applet file header
+//! Example ZAP app¬     Applet friendly name
+//! ...
+//! ```
+//!
+//! The file header is followed by one group of fields per supported watch:
+//! the app name, version, description, help file name, an undocumented
+//! field, the parent app, the target watch, the code as hex, a CRC16-ARC
+//! checksum of the code and the sound data as hex (`0` if there is none).
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crc16::{State, ARC};
+
+use crate::watch_config::ProtocolVersion;
+
+pub mod builder;
+
+/// Byte that ends every field
+pub const FIELD_END: u8 = 0xac;
+
+/// Number of fields of each watch variant
+pub const VARIANT_FIELD_COUNT: usize = 10;
+
+/// Value of the parent app field when there is no parent
+const NO_PARENT: &str = "none";
+
+/// Value of the sound data field when there is no sound data
+const NO_SOUND_DATA: &str = "0";
+
+/// Header written to new ZAP files
+pub const DEFAULT_HEADER: &str = "TDL0724962";
+
+/// Name of the watch a protocol's wrist apps are built for
+pub fn watch_name(protocol: ProtocolVersion) -> &'static str {
+    match protocol {
+        ProtocolVersion::Protocol3 => "Timex Data Link 150 Watch",
+        ProtocolVersion::Protocol4 => "Timex Data Link 150s Watch",
+    }
+}
+
+/// Errors raised while parsing a ZAP file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ZapError {
+    /// The file is empty or has no header field
+    MissingHeader,
+    /// The fields after the header do not form whole variants
+    FieldCount { count: usize },
+    /// A hex field holds an invalid character or an odd number of digits
+    InvalidHex { field: usize, position: usize },
+    /// The checksum field is not a number
+    InvalidChecksum { field: usize, value: String },
+    /// The checksum does not match the code
+    ChecksumMismatch { variant: usize, expected: u16, actual: u16 },
+    /// The file has no variant for a protocol
+    UnsupportedProtocol { protocol: u8, supported: Vec<u8> },
+}
+
+impl fmt::Display for ZapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ZapError::MissingHeader => write!(f, "ZAP file has no header"),
+            ZapError::FieldCount { count } => write!(
+                f,
+                "ZAP file has {} fields after the header, expected a multiple of {}",
+                count, VARIANT_FIELD_COUNT
+            ),
+            ZapError::InvalidHex { field, position } => {
+                write!(f, "field {} has invalid hex at position {}", field, position)
+            }
+            ZapError::InvalidChecksum { field, value } => {
+                write!(f, "field {} holds invalid checksum \"{}\"", field, value)
+            }
+            ZapError::ChecksumMismatch { variant, expected, actual } => write!(
+                f,
+                "code of variant {} has checksum {}, the file declares {}",
+                variant, actual, expected
+            ),
+            ZapError::UnsupportedProtocol { protocol, supported } => write!(
+                f,
+                "ZAP file does not support protocol {} (supported: {:?})",
+                protocol, supported
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ZapError {}
+
+impl From<ZapError> for io::Error {
+    fn from(e: ZapError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, e)
+    }
+}
+
+/// The wrist app for one kind of watch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZapVariant {
+    /// Friendly app name
+    pub name: String,
+
+    /// App version
+    pub version: String,
+
+    /// App description (may span several lines)
+    pub description: String,
+
+    /// Help file name
+    pub help_file: String,
+
+    /// Undocumented field, preserved as-is
+    pub reserved: String,
+
+    /// Name of the parent app, if any
+    pub parent: Option<String>,
+
+    /// Target watch (e.g. "Timex Data Link 150 Watch")
+    pub watch: String,
+
+    /// App code loaded onto the watch
+    pub code: Vec<u8>,
+
+    /// Sound data shipped with the app, if any
+    pub sound_data: Option<Vec<u8>>,
+}
+
+impl ZapVariant {
+    /// Protocol of the target watch, if it is known
+    pub fn protocol(&self) -> Option<ProtocolVersion> {
+        ProtocolVersion::ALL
+            .into_iter()
+            .find(|&protocol| self.watch.trim() == watch_name(protocol))
+    }
+
+    /// CRC16-ARC checksum of the code, as stored in ZAP files
+    pub fn checksum(&self) -> u16 {
+        State::<ARC>::calculate(&self.code)
+    }
+}
+
+/// A parsed ZAP file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Zap {
+    /// File header (e.g. "TDL0724962")
+    pub header: String,
+
+    /// One wrist app per supported watch, in file order
+    pub variants: Vec<ZapVariant>,
+}
+
+impl Zap {
+    /// Whether data starts with a ZAP file header (`TDL…` followed by `0xAC`)
+    ///
+    /// Raw wrist app code may hold `0xAC` bytes anywhere, so the header is
+    /// what tells a ZAP file apart.
+    pub fn is_zap(data: &[u8]) -> bool {
+        match data.iter().position(|&byte| byte == FIELD_END) {
+            Some(end) => data[..end].starts_with(b"TDL") && data[..end].iter().all(u8::is_ascii_alphanumeric),
+            None => false,
+        }
+    }
+
+    /// Parse ZAP file contents
+    ///
+    /// Hex fields are validated strictly and every checksum is verified.
+    pub fn parse(zap_data: &[u8]) -> Result<Self, ZapError> {
+        let fields = split_fields(zap_data);
+        let (header, fields) = fields.split_first().ok_or(ZapError::MissingHeader)?;
+        if header.is_empty() {
+            return Err(ZapError::MissingHeader);
+        }
+        if fields.is_empty() || !fields.len().is_multiple_of(VARIANT_FIELD_COUNT) {
+            return Err(ZapError::FieldCount { count: fields.len() });
+        }
+
+        let variants = fields.chunks(VARIANT_FIELD_COUNT)
+            .enumerate()
+            .map(|(variant, group)| {
+                let first_field = 1 + variant * VARIANT_FIELD_COUNT;
+                let text = |index: usize| decode_text(group[index]);
+
+                let code = decode_hex(group[7], first_field + 7)?;
+                let checksum_text = text(8);
+                let expected: u16 = checksum_text.trim().parse()
+                    .map_err(|_| ZapError::InvalidChecksum { field: first_field + 8, value: checksum_text.clone() })?;
+                let sound_data = match text(9).trim() {
+                    NO_SOUND_DATA => None,
+                    _ => Some(decode_hex(group[9], first_field + 9)?),
+                };
+
+                let variant_data = ZapVariant {
+                    name: text(0),
+                    version: text(1),
+                    description: text(2),
+                    help_file: text(3),
+                    reserved: text(4),
+                    parent: Some(text(5)).filter(|parent| parent.trim() != NO_PARENT),
+                    watch: text(6),
+                    code,
+                    sound_data,
+                };
+
+                let actual = variant_data.checksum();
+                if actual != expected {
+                    return Err(ZapError::ChecksumMismatch { variant, expected, actual });
+                }
+
+                Ok(variant_data)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Zap { header: decode_text(header), variants })
+    }
+
+    /// Read and parse a ZAP file
+    pub fn read<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Self::parse(&fs::read(path)?)?)
+    }
+
+    /// Write a ZAP file
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_bytes())
+    }
+
+    /// ZAP file contents, with the comments written by the Timex software
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut fields: Vec<(String, &str)> = vec![(self.header.clone(), "      Applet file header")];
+
+        for variant in &self.variants {
+            fields.extend([
+                (variant.name.clone(), "     Applet friendly name"),
+                (variant.version.clone(), "           Applet version #"),
+                (variant.description.clone(), ""),
+                (variant.help_file.clone(), "   Applet help filename"),
+                (variant.reserved.clone(), " "),
+                (
+                    variant.parent.clone().unwrap_or_else(|| NO_PARENT.to_string()),
+                    "   Applet's parent's app name (if it exists - 'none' if it doesn't)",
+                ),
+                (variant.watch.clone(), ""),
+                (encode_hex(&variant.code), ""),
+                (variant.checksum().to_string(), ""),
+            ]);
+            fields.push(match &variant.sound_data {
+                Some(sound_data) => (encode_hex(sound_data), ""),
+                None => (NO_SOUND_DATA.to_string(), " No data"),
+            });
+        }
+
+        let lines: Vec<Vec<u8>> = fields.iter()
+            .map(|(value, comment)| {
+                // Characters outside the single-byte range cannot be stored
+                value.chars().map(|c| u8::try_from(c).unwrap_or(b'?'))
+                    .chain([FIELD_END])
+                    .chain(comment.bytes())
+                    .collect()
+            })
+            .collect();
+
+        lines.join(&b"\r\n"[..])
+    }
+
+    /// Protocols the file has a wrist app for
+    pub fn protocols(&self) -> Vec<ProtocolVersion> {
+        self.variants.iter().filter_map(ZapVariant::protocol).collect()
+    }
+
+    /// The wrist app for a protocol, if the file has one
+    pub fn variant(&self, protocol: ProtocolVersion) -> Option<&ZapVariant> {
+        self.variants.iter().find(|variant| variant.protocol() == Some(protocol))
+    }
+
+    /// The wrist app code for a protocol
+    pub fn code(&self, protocol: ProtocolVersion) -> Result<&[u8], ZapError> {
+        self.variant(protocol)
+            .map(|variant| variant.code.as_slice())
+            .ok_or_else(|| ZapError::UnsupportedProtocol {
+                protocol: protocol.number(),
+                supported: self.protocols().iter().map(|protocol| protocol.number()).collect(),
+            })
+    }
+}
+
+/// Split ZAP file contents into fields, dropping the comment after each field end
+///
+/// Comments end at the end of the line, so files whose CRLF line endings were
+/// converted to LF are split the same way.
+fn split_fields(zap_data: &[u8]) -> Vec<&[u8]> {
+    let mut fields = Vec::new();
+    let mut rest = zap_data;
+
+    while let Some(end) = rest.iter().position(|&byte| byte == FIELD_END) {
+        fields.push(&rest[..end]);
+
+        // An optional '\r' before the '\n' is part of the dropped comment
+        let comment = &rest[end + 1..];
+        rest = match comment.iter().position(|&byte| byte == b'\n') {
+            Some(line_end) => &comment[line_end + 1..],
+            None => &[],
+        };
+    }
+
+    // Trailing text without a field end is not a field
+    fields
+}
+
+/// Decode a text field (ZAP files use single-byte characters)
+fn decode_text(field: &[u8]) -> String {
+    field.iter().map(|&byte| byte as char).collect()
+}
+
+/// Encode bytes as an upper case hex field
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02X}", byte)).collect()
+}
+
+/// Decode a hex field strictly
+fn decode_hex(field: &[u8], index: usize) -> Result<Vec<u8>, ZapError> {
+    if !field.len().is_multiple_of(2) {
+        return Err(ZapError::InvalidHex { field: index, position: field.len() });
+    }
+
+    field.chunks(2)
+        .enumerate()
+        .map(|(pair, digits)| {
+            let digit = |offset: usize| {
+                (digits[offset] as char).to_digit(16)
+                    .ok_or(ZapError::InvalidHex { field: index, position: pair * 2 + offset })
+            };
+            Ok((digit(0)? << 4 | digit(1)?) as u8)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE_ZAP: &[u8] = include_bytes!("../fixtures/EXAMPLE.ZAP");
+
+    #[test]
+    fn test_parse_example() {
+        let zap = Zap::parse(EXAMPLE_ZAP).unwrap();
+
+        assert_eq!(zap.header, "TDL0724962");
+        assert_eq!(zap.protocols(), vec![ProtocolVersion::Protocol3, ProtocolVersion::Protocol4]);
+
+        let variant = zap.variant(ProtocolVersion::Protocol3).unwrap();
+        assert_eq!(variant.name, "Example ZAP app");
+        assert_eq!(variant.version, "EXAMPLE18");
+        assert_eq!(variant.description, "Example ZAP app description\r\nExample of multiple lines in description");
+        assert_eq!(variant.help_file, "EXAMPLE.HLP");
+        assert_eq!(variant.parent, None);
+        assert_eq!(variant.sound_data, None);
+        assert!(variant.code.starts_with(b"150 data: Lorem ipsum"));
+        assert_eq!(variant.checksum(), 64458);
+
+        assert!(zap.code(ProtocolVersion::Protocol4).unwrap().starts_with(b"150s data: Lorem ipsum"));
+    }
+
+    #[test]
+    fn test_parse_lf_line_endings() {
+        // e.g. a checkout with core.autocrlf converting CRLF to LF
+        let lf_zap: Vec<u8> = EXAMPLE_ZAP.iter()
+            .enumerate()
+            .filter(|&(index, &byte)| !(byte == b'\r' && EXAMPLE_ZAP.get(index + 1) == Some(&b'\n')))
+            .map(|(_, &byte)| byte)
+            .collect();
+        assert!(!lf_zap.contains(&b'\r'));
+
+        let zap = Zap::parse(&lf_zap).unwrap();
+        let example = Zap::parse(EXAMPLE_ZAP).unwrap();
+
+        assert_eq!(zap.header, example.header);
+        assert_eq!(zap.protocols(), example.protocols());
+        for protocol in zap.protocols() {
+            let (variant, expected) = (zap.variant(protocol).unwrap(), example.variant(protocol).unwrap());
+            assert_eq!(variant.name, expected.name);
+            assert_eq!(variant.version, expected.version);
+            assert_eq!(variant.code, expected.code);
+            assert_eq!(variant.checksum(), expected.checksum());
+        }
+        assert_eq!(
+            zap.variant(ProtocolVersion::Protocol3).unwrap().description,
+            "Example ZAP app description\nExample of multiple lines in description"
+        );
+    }
+
+    #[test]
+    fn test_is_zap() {
+        assert!(Zap::is_zap(EXAMPLE_ZAP));
+        assert!(!Zap::is_zap(&[0xa6, 0x01, 0xac, 0x02, 0x81]));
+        assert!(!Zap::is_zap(b"TDL0724962"));
+    }
+
+    #[test]
+    fn test_write_round_trip() {
+        let zap = Zap::parse(EXAMPLE_ZAP).unwrap();
+        assert_eq!(zap.to_bytes(), EXAMPLE_ZAP);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(Zap::parse(b""), Err(ZapError::MissingHeader));
+        assert_eq!(Zap::parse(b"TDL\xac\r\nname\xac\r\n"), Err(ZapError::FieldCount { count: 1 }));
+
+        let fields = ["TDL", "App", "1", "", "APP.HLP", "", "none", "Timex Data Link 150 Watch"];
+        let zap = |code: &str, checksum: &str| {
+            let mut data: Vec<u8> = fields.iter().chain(&[code, checksum, "0"]).flat_map(|field| {
+                field.bytes().chain([FIELD_END, b'\r', b'\n'])
+            }).collect();
+            data.truncate(data.len() - 2);
+            Zap::parse(&data)
+        };
+
+        let valid = zap("A0B1", "29880").unwrap();
+        assert_eq!(valid.variants[0].code, vec![0xa0, 0xb1]);
+        assert_eq!(valid.code(ProtocolVersion::Protocol4), Err(ZapError::UnsupportedProtocol { protocol: 4, supported: vec![3] }));
+
+        assert_eq!(zap("A0G1", "0"), Err(ZapError::InvalidHex { field: 8, position: 2 }));
+        assert_eq!(zap("A0B", "0"), Err(ZapError::InvalidHex { field: 8, position: 3 }));
+        assert_eq!(zap("A0B1", "x"), Err(ZapError::InvalidChecksum { field: 9, value: "x".to_string() }));
+        assert_eq!(zap("A0B1", "1"), Err(ZapError::ChecksumMismatch { variant: 0, expected: 1, actual: 29880 }));
+    }
+}
@@ -0,0 +1,462 @@
+//! ZAP builder
+//!
+//! This module builds ZAP files from the output of a 6805 assembler: raw
+//! binaries, Intel HEX or Motorola S-record files. Hex images are flattened
+//! into a single block starting at their lowest address, with gaps filled
+//! with zeros.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::helpers::load_section::MAX_PAYLOAD_LENGTH;
+use crate::watch_config::ProtocolVersion;
+use crate::zap::{watch_name, Zap, ZapVariant, DEFAULT_HEADER, FIELD_END};
+
+/// Largest span of addresses a hex image may cover (the 6805's 64 KB address space)
+pub const MAX_IMAGE_SPAN: usize = 0x10000;
+
+/// Format of an assembled image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    /// Raw binary
+    Binary,
+    /// Intel HEX records
+    IntelHex,
+    /// Motorola S-records
+    SRecord,
+}
+
+impl ImageFormat {
+    /// Guess the format of an image from its contents
+    pub fn detect(image: &[u8]) -> Self {
+        let text = image.iter().position(|byte| !byte.is_ascii_whitespace()).map(|start| &image[start..]);
+
+        match text {
+            Some([b':', ..]) => ImageFormat::IntelHex,
+            Some([b'S', digit, ..]) if digit.is_ascii_digit() => ImageFormat::SRecord,
+            _ => ImageFormat::Binary,
+        }
+    }
+}
+
+/// Errors raised while building a ZAP file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ZapBuildError {
+    /// A record of a hex image is malformed
+    InvalidRecord { line: usize },
+    /// A record of a hex image has a wrong checksum
+    RecordChecksum { line: usize },
+    /// The image holds no code
+    EmptyImage,
+    /// The records of a hex image span more addresses than a wrist app can use
+    ImageSpan { start: u32, end: u32, max: usize },
+    /// The code is larger than the size limit of the builder
+    TooLarge { protocol: u8, size: usize, max: usize },
+    /// No code was added for any protocol
+    MissingCode,
+    /// A text field holds the field end character
+    InvalidText { field: &'static str },
+}
+
+impl fmt::Display for ZapBuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ZapBuildError::InvalidRecord { line } => write!(f, "line {} is not a valid record", line),
+            ZapBuildError::RecordChecksum { line } => write!(f, "record on line {} has a wrong checksum", line),
+            ZapBuildError::EmptyImage => write!(f, "image holds no code"),
+            ZapBuildError::ImageSpan { start, end, max } => write!(
+                f,
+                "image spans addresses {:#x} to {:#x}, more than {} bytes",
+                start, end, max
+            ),
+            ZapBuildError::TooLarge { protocol, size, max } => write!(
+                f,
+                "protocol {} wrist app is {} bytes, the limit is {} bytes",
+                protocol, size, max
+            ),
+            ZapBuildError::MissingCode => write!(f, "no wrist app code was added"),
+            ZapBuildError::InvalidText { field } => {
+                write!(f, "{} must not contain the field end character", field)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ZapBuildError {}
+
+/// Load an assembled image
+///
+/// # Arguments
+///
+/// * `image` - File contents, as a raw binary, Intel HEX or S-record file
+///
+/// # Returns
+///
+/// The code, starting at the lowest address of the image
+pub fn load_image(image: &[u8]) -> Result<Vec<u8>, ZapBuildError> {
+    let code = match ImageFormat::detect(image) {
+        ImageFormat::Binary => image.to_vec(),
+        ImageFormat::IntelHex => flatten(parse_intel_hex(image)?)?,
+        ImageFormat::SRecord => flatten(parse_s_records(image)?)?,
+    };
+
+    if code.is_empty() {
+        return Err(ZapBuildError::EmptyImage);
+    }
+
+    Ok(code)
+}
+
+/// Parse Intel HEX records into bytes by address
+fn parse_intel_hex(image: &[u8]) -> Result<BTreeMap<u32, u8>, ZapBuildError> {
+    let mut memory = BTreeMap::new();
+    let mut base: u32 = 0;
+
+    for (line, bytes) in record_lines(image)? {
+        let invalid = ZapBuildError::InvalidRecord { line };
+        let record = bytes.strip_prefix(":").ok_or(invalid.clone())?;
+        let record = decode_record(record, line)?;
+
+        // Length, address (2), type, data, checksum
+        if record.len() < 5 || record.len() != record[0] as usize + 5 {
+            return Err(invalid);
+        }
+        if record.iter().fold(0u8, |sum, &byte| sum.wrapping_add(byte)) != 0 {
+            return Err(ZapBuildError::RecordChecksum { line });
+        }
+
+        let address = u16::from_be_bytes([record[1], record[2]]) as u32;
+        let data = &record[4..record.len() - 1];
+        match record[3] {
+            0x00 => {
+                for (offset, &byte) in data.iter().enumerate() {
+                    let address = base.checked_add(address)
+                        .and_then(|address| address.checked_add(offset as u32))
+                        .ok_or(invalid.clone())?;
+                    memory.insert(address, byte);
+                }
+            }
+            0x01 => break,
+            0x02 if data.len() == 2 => base = (u16::from_be_bytes([data[0], data[1]]) as u32) << 4,
+            0x04 if data.len() == 2 => base = (u16::from_be_bytes([data[0], data[1]]) as u32) << 16,
+            0x03 | 0x05 => {}
+            _ => return Err(invalid),
+        }
+    }
+
+    Ok(memory)
+}
+
+/// Parse Motorola S-records into bytes by address
+fn parse_s_records(image: &[u8]) -> Result<BTreeMap<u32, u8>, ZapBuildError> {
+    let mut memory = BTreeMap::new();
+
+    for (line, bytes) in record_lines(image)? {
+        let invalid = ZapBuildError::InvalidRecord { line };
+        let (kind, record) = match bytes.as_bytes() {
+            [b'S', kind, ..] => (*kind, decode_record(&bytes[2..], line)?),
+            _ => return Err(invalid),
+        };
+
+        // Count, address, data, checksum
+        if record.is_empty() || record.len() != record[0] as usize + 1 {
+            return Err(invalid);
+        }
+        if record.iter().fold(0u8, |sum, &byte| sum.wrapping_add(byte)) != 0xff {
+            return Err(ZapBuildError::RecordChecksum { line });
+        }
+
+        let address_length = match kind {
+            b'1' => 2,
+            b'2' => 3,
+            b'3' => 4,
+            b'0' | b'5' | b'6' => continue,
+            b'7'..=b'9' => break,
+            _ => return Err(invalid),
+        };
+        if record.len() < address_length + 2 {
+            return Err(invalid);
+        }
+
+        let address = record[1..=address_length].iter().fold(0u32, |address, &byte| address << 8 | byte as u32);
+        for (offset, &byte) in record[address_length + 1..record.len() - 1].iter().enumerate() {
+            memory.insert(address.checked_add(offset as u32).ok_or(invalid.clone())?, byte);
+        }
+    }
+
+    Ok(memory)
+}
+
+/// Non-empty lines of a text image with their line numbers (records are ASCII)
+fn record_lines(image: &[u8]) -> Result<Vec<(usize, String)>, ZapBuildError> {
+    let text = std::str::from_utf8(image).map_err(|_| ZapBuildError::InvalidRecord { line: 1 })?;
+
+    text.lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim().to_string()))
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(line, record)| match record.is_ascii() {
+            true => Ok((line, record)),
+            false => Err(ZapBuildError::InvalidRecord { line }),
+        })
+        .collect()
+}
+
+/// Decode the hex digits of a record
+fn decode_record(digits: &str, line: usize) -> Result<Vec<u8>, ZapBuildError> {
+    if !digits.len().is_multiple_of(2) {
+        return Err(ZapBuildError::InvalidRecord { line });
+    }
+
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).map_err(|_| ZapBuildError::InvalidRecord { line }))
+        .collect()
+}
+
+/// Flatten bytes by address into a block starting at the lowest address
+///
+/// The span is checked before the block is allocated, so records far
+/// apart cannot make it huge.
+fn flatten(memory: BTreeMap<u32, u8>) -> Result<Vec<u8>, ZapBuildError> {
+    let (start, end) = match (memory.keys().next(), memory.keys().next_back()) {
+        (Some(&start), Some(&end)) => (start, end),
+        _ => return Ok(Vec::new()),
+    };
+    if (end - start) as u64 + 1 > MAX_IMAGE_SPAN as u64 {
+        return Err(ZapBuildError::ImageSpan { start, end, max: MAX_IMAGE_SPAN });
+    }
+
+    let mut code = vec![0; (end - start) as usize + 1];
+    for (address, byte) in memory {
+        code[(address - start) as usize] = byte;
+    }
+
+    Ok(code)
+}
+
+/// Builder for ZAP files
+#[derive(Debug, Clone)]
+pub struct ZapBuilder {
+    name: String,
+    version: String,
+    description: String,
+    help_file: String,
+    parent: Option<String>,
+    max_size: usize,
+    code: Vec<(ProtocolVersion, Vec<u8>)>,
+}
+
+impl ZapBuilder {
+    /// Start a ZAP file for an app
+    pub fn new(name: &str) -> Self {
+        ZapBuilder {
+            name: name.to_string(),
+            version: String::new(),
+            description: String::new(),
+            help_file: String::new(),
+            parent: None,
+            max_size: MAX_PAYLOAD_LENGTH,
+            code: Vec::new(),
+        }
+    }
+
+    /// Set the app version
+    pub fn version(mut self, version: &str) -> Self {
+        self.version = version.to_string();
+        self
+    }
+
+    /// Set the app description
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = description.to_string();
+        self
+    }
+
+    /// Set the help file name
+    pub fn help_file(mut self, help_file: &str) -> Self {
+        self.help_file = help_file.to_string();
+        self
+    }
+
+    /// Set the parent app name
+    pub fn parent(mut self, parent: &str) -> Self {
+        self.parent = Some(parent.to_string());
+        self
+    }
+
+    /// Set the largest code size accepted for each protocol
+    ///
+    /// The size of the wrist app memory of the 150 and 150s is not known
+    /// here, so the default is `load_section::MAX_PAYLOAD_LENGTH`, the most
+    /// a single load section can carry. Set this to the memory size of the
+    /// target watch to check against it.
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Add the code for a protocol, replacing any code added before
+    pub fn code(mut self, protocol: ProtocolVersion, code: Vec<u8>) -> Self {
+        self.code.retain(|(existing, _)| *existing != protocol);
+        self.code.push((protocol, code));
+        self
+    }
+
+    /// Add the code for a protocol from an assembled image
+    pub fn image(self, protocol: ProtocolVersion, image: &[u8]) -> Result<Self, ZapBuildError> {
+        Ok(self.code(protocol, load_image(image)?))
+    }
+
+    /// Validate the app and build the ZAP file
+    ///
+    /// Variants are ordered by protocol, as the Timex software does.
+    pub fn build(mut self) -> Result<Zap, ZapBuildError> {
+        let texts = [
+            ("name", &self.name),
+            ("version", &self.version),
+            ("description", &self.description),
+            ("help file", &self.help_file),
+        ];
+        for (field, text) in texts {
+            if text.contains(FIELD_END as char) {
+                return Err(ZapBuildError::InvalidText { field });
+            }
+        }
+        if self.parent.as_ref().is_some_and(|parent| parent.contains(FIELD_END as char)) {
+            return Err(ZapBuildError::InvalidText { field: "parent" });
+        }
+
+        if self.code.is_empty() {
+            return Err(ZapBuildError::MissingCode);
+        }
+        self.code.sort_by_key(|(protocol, _)| protocol.number());
+
+        let variants = self.code.into_iter()
+            .map(|(protocol, code)| {
+                if code.is_empty() {
+                    return Err(ZapBuildError::EmptyImage);
+                }
+                if code.len() > self.max_size {
+                    return Err(ZapBuildError::TooLarge {
+                        protocol: protocol.number(),
+                        size: code.len(),
+                        max: self.max_size,
+                    });
+                }
+
+                Ok(ZapVariant {
+                    name: self.name.clone(),
+                    version: self.version.clone(),
+                    description: self.description.clone(),
+                    help_file: self.help_file.clone(),
+                    reserved: String::new(),
+                    parent: self.parent.clone(),
+                    watch: watch_name(protocol).to_string(),
+                    code,
+                    sound_data: None,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Zap { header: DEFAULT_HEADER.to_string(), variants })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{protocol_3, protocol_4, PacketGenerator};
+
+    const INTEL_HEX: &str = "\
+:0401100001020304E1
+:02011600AABB82
+:00000001FF
+";
+
+    const S_RECORDS: &str = "\
+S00600004844521B
+S107011001020304DD
+S1050116AABB7E
+S9030000FC
+";
+
+    #[test]
+    fn test_load_image() {
+        assert_eq!(ImageFormat::detect(INTEL_HEX.as_bytes()), ImageFormat::IntelHex);
+        assert_eq!(ImageFormat::detect(S_RECORDS.as_bytes()), ImageFormat::SRecord);
+        assert_eq!(ImageFormat::detect(&[0xa6, 0x01]), ImageFormat::Binary);
+
+        let code = vec![1, 2, 3, 4, 0, 0, 0xaa, 0xbb];
+        assert_eq!(load_image(INTEL_HEX.as_bytes()), Ok(code.clone()));
+        assert_eq!(load_image(S_RECORDS.as_bytes()), Ok(code));
+        assert_eq!(load_image(&[0xa6, 0x01]), Ok(vec![0xa6, 0x01]));
+    }
+
+    #[test]
+    fn test_load_image_errors() {
+        assert_eq!(load_image(b":0401100001020304E3\n"), Err(ZapBuildError::RecordChecksum { line: 1 }));
+        assert_eq!(load_image(b":0401100001\n"), Err(ZapBuildError::InvalidRecord { line: 1 }));
+        assert_eq!(load_image(b"S1070110010203\n"), Err(ZapBuildError::InvalidRecord { line: 1 }));
+        assert_eq!(load_image(b":00000001FF\n"), Err(ZapBuildError::EmptyImage));
+        assert_eq!(load_image(":04é\n".as_bytes()), Err(ZapBuildError::InvalidRecord { line: 1 }));
+    }
+
+    #[test]
+    fn test_load_image_addresses() {
+        // Extended addresses are fine as long as the records stay close together
+        assert_eq!(load_image(b":020000040001F9\n:01000000AA55\n"), Ok(vec![0xaa]));
+
+        assert_eq!(
+            load_image(b":01000000AA55\n:020000040001F9\n:01000000AA55\n"),
+            Err(ZapBuildError::ImageSpan { start: 0, end: 0x10000, max: MAX_IMAGE_SPAN })
+        );
+        assert_eq!(
+            load_image(b"S3060000000001F8\nS3061000000002E7\n"),
+            Err(ZapBuildError::ImageSpan { start: 0, end: 0x10000000, max: MAX_IMAGE_SPAN })
+        );
+        assert_eq!(
+            load_image(b":02000004FFFFFC\n:02FFFF000102FD\n"),
+            Err(ZapBuildError::InvalidRecord { line: 2 })
+        );
+        assert_eq!(load_image(b"S307FFFFFFFF0102F9\n"), Err(ZapBuildError::InvalidRecord { line: 1 }));
+    }
+
+    #[test]
+    fn test_build() {
+        let zap = ZapBuilder::new("Hello")
+            .version("HELLO1")
+            .description("Says hello")
+            .image(ProtocolVersion::Protocol4, INTEL_HEX.as_bytes())
+            .unwrap()
+            .code(ProtocolVersion::Protocol3, vec![0x9d, 0x81])
+            .build()
+            .unwrap();
+
+        assert_eq!(zap.protocols(), vec![ProtocolVersion::Protocol3, ProtocolVersion::Protocol4]);
+        assert_eq!(Zap::parse(&zap.to_bytes()), Ok(zap.clone()));
+
+        let protocol_3_app = protocol_3::WristApp::from_zap(&zap).unwrap();
+        assert_eq!(protocol_3_app.wrist_app_data, vec![0x9d, 0x81]);
+        let protocol_4_app = protocol_4::WristApp::from_zap(&zap).unwrap();
+        assert_eq!(protocol_4_app.packets()[2][4..12], [1, 2, 3, 4, 0, 0, 0xaa, 0xbb]);
+    }
+
+    #[test]
+    fn test_build_errors() {
+        assert_eq!(ZapBuilder::new("Empty").build(), Err(ZapBuildError::MissingCode));
+        assert_eq!(
+            ZapBuilder::new("Big").max_size(4).code(ProtocolVersion::Protocol3, vec![0; 5]).build(),
+            Err(ZapBuildError::TooLarge { protocol: 3, size: 5, max: 4 })
+        );
+
+        // Without a known memory size, code must fit a single load section
+        let error = ZapBuilder::new("Big").code(ProtocolVersion::Protocol3, vec![0; MAX_PAYLOAD_LENGTH + 1]).build();
+        assert_eq!(error, Err(ZapBuildError::TooLarge { protocol: 3, size: 8161, max: 8160 }));
+        assert_eq!(error.unwrap_err().to_string(), "protocol 3 wrist app is 8161 bytes, the limit is 8160 bytes");
+        assert_eq!(
+            ZapBuilder::new("A¬B").code(ProtocolVersion::Protocol3, vec![0x81]).build(),
+            Err(ZapBuildError::InvalidText { field: "name" })
+        );
+    }
+}
@@ -222,6 +222,7 @@ function collectFormData() {
     // EEPROM Data (combined section)
     const includeEeprom = document.getElementById('includeEeprom').checked;
     const appointmentNotification = parseInt(document.getElementById('appointmentNotification').value);
+    const appointmentRounding = document.getElementById('appointmentRounding').value;
     
     // Appointments
     const appointments = [];
@@ -284,6 +285,7 @@ function collectFormData() {
         alarms,
         includeEeprom,
         appointmentNotification: appointmentNotification >= 0 ? appointmentNotification : null,
+        appointmentRounding,
         appointments,
         anniversaries,
         phoneNumbers,
@@ -321,32 +323,51 @@ async function sendDataToWatch() {
             log(`Time Zone 2 (${formData.time2.name}): ${time2Date.toISOString()} (UTC${formData.time2.offsetHours >= 0 ? '+' : ''}${formData.time2.offsetHours})`);
         }
         
-        log('Generating packets...');
+        const protocol = parseInt(document.getElementById('protocol').value);
+        log(`Generating Protocol ${protocol} packets...`);
         
-        // Pass the form data to the WebAssembly module
-        const packets = wasmModule.generate_protocol3_packets(formData);
+        // Pass the form data to the WebAssembly module, invalid fields are thrown as an array of {path, message}
+        let packets;
+        try {
+            packets = wasmModule.generate_packets(formData, protocol);
+        } catch (errors) {
+            if (!Array.isArray(errors)) {
+                throw errors;
+            }
+            for (const error of errors) {
+                log(`Invalid ${error.path || 'watch data'}: ${error.message}`, true);
+            }
+            updateStatus('Invalid watch data, see the log for details', true);
+            return;
+        }
         
         log(`Generated ${packets.length} packets`);
         
+        // Pace the transfer with the same timing as the native adapters
+        const timing = wasmModule.default_adapter_timing();
+        const schedule = wasmModule.transmission_schedule(packets, timing);
+        
+        const estimate = wasmModule.estimate_transfer_time(packets, timing);
+        log(`Estimated transfer time: ${estimate.total_seconds.toFixed(1)}s`);
+        for (const section of estimate.sections) {
+            log(`  ${section.section}: ${section.seconds.toFixed(1)}s (${section.packets} packets, ${section.bytes} bytes)`);
+        }
+        
         updateStatus('Sending data to watch...');
         
         // Get a writer for the serial port
         const writer = port.writable.getWriter();
         
-        // Loop through each packet
-        for (let i = 0; i < packets.length; i++) {
-            const packet = packets[i];
-            log(`Sending packet ${i+1} of ${packets.length} (${packet.length} bytes)`);
-            
-            // Send each byte with a delay
-            for (let j = 0; j < packet.length; j++) {
-                await writer.write(new Uint8Array([packet[j]]));
-                // Sleep between bytes (14ms - as in the example)
-                await new Promise(resolve => setTimeout(resolve, 14));
+        // Send each byte, then wait as long as the schedule says
+        let currentPacket = -1;
+        for (const step of schedule.bytes) {
+            if (step.packet !== currentPacket) {
+                currentPacket = step.packet;
+                log(`Sending packet ${currentPacket + 1} of ${packets.length} (${packets[currentPacket].length} bytes)`);
             }
             
-            // Sleep between packets (80ms - as in the example)
-            await new Promise(resolve => setTimeout(resolve, 80));
+            await writer.write(new Uint8Array([step.byte]));
+            await new Promise(resolve => setTimeout(resolve, step.delay * 1000));
         }
         
         // Release the writer
@@ -708,7 +729,9 @@ function saveFormData() {
             buttonBeep: document.getElementById('buttonBeep').checked
         }));
         localStorage.setItem('timexDatalink_appointmentNotification', document.getElementById('appointmentNotification').value);
+        localStorage.setItem('timexDatalink_appointmentRounding', document.getElementById('appointmentRounding').value);
         localStorage.setItem('timexDatalink_syncLength', document.getElementById('syncLength').value);
+        localStorage.setItem('timexDatalink_protocol', document.getElementById('protocol').value);
         
         // Save toggle states
         localStorage.setItem('timexDatalink_toggles', JSON.stringify({
@@ -836,12 +859,23 @@ function loadFormData() {
             document.getElementById('appointmentNotification').value = savedAppointmentNotification;
         }
         
+        const savedAppointmentRounding = localStorage.getItem('timexDatalink_appointmentRounding');
+        if (savedAppointmentRounding) {
+            document.getElementById('appointmentRounding').value = savedAppointmentRounding;
+        }
+        
         // Load sync length
         const savedSyncLength = localStorage.getItem('timexDatalink_syncLength');
         if (savedSyncLength) {
             document.getElementById('syncLength').value = savedSyncLength;
         }
         
+        // Load watch model
+        const savedProtocol = localStorage.getItem('timexDatalink_protocol');
+        if (savedProtocol) {
+            document.getElementById('protocol').value = savedProtocol;
+        }
+        
         // Load toggles
         const savedToggles = localStorage.getItem('timexDatalink_toggles');
         if (savedToggles) {
@@ -927,7 +961,9 @@ function loadSampleData() {
         localStorage.removeItem('timexDatalink_time2');
         localStorage.removeItem('timexDatalink_soundOptions');
         localStorage.removeItem('timexDatalink_appointmentNotification');
+        localStorage.removeItem('timexDatalink_appointmentRounding');
         localStorage.removeItem('timexDatalink_syncLength');
+        localStorage.removeItem('timexDatalink_protocol');
         localStorage.removeItem('timexDatalink_toggles');
         
         log('Loading sample data...');
@@ -1041,12 +1077,113 @@ function handleWristAppFileUpload(event) {
     reader.readAsArrayBuffer(file);
 }
 
+// Format bytes as hex for the capture view
+function hexBytes(bytes) {
+    return Array.from(bytes, byte => byte.toString(16).padStart(2, '0')).join(' ');
+}
+
+// Describe the CRC check of a decoded packet
+function crcDescription(crc) {
+    switch (crc.status) {
+        case 'valid': return 'crc ok';
+        case 'invalid': return `crc BAD (expected ${crc.expected.toString(16)}, got ${crc.actual.toString(16)})`;
+        case 'bad_length': return `length BAD (header says ${crc.declared}, packet has ${crc.actual})`;
+        default: return 'no crc';
+    }
+}
+
+// Append a table of decoded fields, one heading row per group
+function appendCaptureTable(container, groups) {
+    const table = document.createElement('table');
+    table.className = 'capture-table';
+    
+    for (const group of groups) {
+        const heading = table.insertRow();
+        const cell = document.createElement('th');
+        cell.colSpan = 3;
+        cell.textContent = group.title;
+        if (group.error) {
+            cell.className = 'error';
+        }
+        heading.appendChild(cell);
+        
+        for (const field of group.fields) {
+            const row = table.insertRow();
+            row.insertCell().textContent = field.name;
+            row.insertCell().textContent = hexBytes(field.raw);
+            row.insertCell().textContent = field.meaning;
+        }
+    }
+    
+    container.appendChild(table);
+}
+
+// Show a decoded capture
+function showCapture(capture) {
+    const output = document.getElementById('captureOutput');
+    output.innerHTML = '';
+    
+    appendCaptureTable(output, capture.packets.map(packet => ({
+        title: `#${packet.index} ${packet.name} [${packet.bytes.length} bytes, ${crcDescription(packet.crc)}]`,
+        error: packet.crc.status === 'invalid' || packet.crc.status === 'bad_length',
+        fields: packet.fields,
+    })));
+    
+    if (capture.trailing.length > 0) {
+        log(`Capture ends with ${capture.trailing.length} bytes that are not a whole packet: ${hexBytes(capture.trailing)}`, true);
+    }
+    if (capture.section_error) {
+        log(`Could not reassemble load sections: ${capture.section_error}`, true);
+    }
+    
+    for (const section of capture.sections) {
+        const groups = [{ title: `${section.name} section, ${section.payload_bytes} payload bytes`, fields: [] }];
+        for (const entry of section.entries) {
+            groups.push({ title: entry.kind.replace('_', ' '), fields: entry.fields });
+        }
+        appendCaptureTable(output, groups);
+    }
+    
+    log(`Decoded ${capture.packets.length} packets and ${capture.sections.length} load sections`);
+}
+
+// Decode the pasted hex capture, or the uploaded dump if no hex was pasted
+async function decodeCapture() {
+    try {
+        const text = document.getElementById('captureHex').value;
+        const file = document.getElementById('captureFile').files[0];
+        
+        if (text.trim()) {
+            showCapture(wasmModule.decode_hex_capture(text));
+        } else if (file) {
+            showCapture(wasmModule.decode_capture(new Uint8Array(await file.arrayBuffer())));
+        } else {
+            log('Paste a hex capture or choose a dump file to decode', true);
+        }
+    } catch (error) {
+        log(`Error decoding capture: ${error.message || error}`, true);
+    }
+}
+
+// Set up the capture inspector, which does not need a serial port
+function initializeCaptureInspector() {
+    document.getElementById('decodeCaptureButton').addEventListener('click', decodeCapture);
+}
+
 // Initialize the page
 async function initializePage() {
     // Check if Web Serial API is supported
     if (!navigator.serial) {
         updateStatus('Web Serial API is not supported in this browser', true);
         log('Your browser does not support the Web Serial API. Try using Chrome or Edge.', true);
+        
+        // Captures can still be inspected without a serial port
+        try {
+            await initWasm();
+            initializeCaptureInspector();
+        } catch (error) {
+            log(`Error loading WebAssembly: ${error.message}`, true);
+        }
         return;
     }
     
@@ -1074,6 +1211,7 @@ async function initializePage() {
         // Add file upload event listeners
         document.getElementById('soundThemeFile').addEventListener('change', handleSoundThemeFileUpload);
         document.getElementById('wristAppFile').addEventListener('change', handleWristAppFileUpload);
+        initializeCaptureInspector();
         
         // Function to clear all items from a section
         function clearSection(sectionId) {
@@ -1108,6 +1246,7 @@ async function initializePage() {
             // Other settings
             document.getElementById('appointmentNotification').addEventListener('change', saveFormData);
             document.getElementById('syncLength').addEventListener('change', saveFormData);
+            document.getElementById('protocol').addEventListener('change', saveFormData);
             
             // Toggle switches with section visibility
             document.getElementById('includeTime').addEventListener('change', function() {
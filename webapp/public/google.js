@@ -188,25 +188,6 @@ function formatDateTime(dateStr, timeStr) {
     return formattedDate;
 }
 
-// Determine phone type code
-function determinePhoneType(type) {
-    if (!type) return 'O'; // Other
-    
-    const typeStr = type.toLowerCase();
-    if (typeStr.includes('home')) return 'H';
-    if (typeStr.includes('work')) return 'W';
-    if (typeStr.includes('mobile') || typeStr.includes('cell')) return 'C';
-    if (typeStr.includes('fax')) return 'F';
-    return 'O'; // Other
-}
-
-// Extract clean phone number
-function cleanPhoneNumber(number) {
-    if (!number) return '';
-    // Remove everything except digits
-    return number.replace(/[^\d]/g, '');
-}
-
 // Get priority based on position (1-5)
 function getTaskPriority(index, total) {
     // Distribute tasks evenly across priorities 1-5
@@ -637,19 +618,28 @@ async function loadContacts() {
                 return person.names && person.names.length > 0 && 
                        person.phoneNumbers && person.phoneNumbers.length > 0;
             })
-            .map(person => {
+            .flatMap(person => {
                 const name = person.names[0].displayName || 'Unknown';
                 const primaryPhone = person.phoneNumbers[0];
                 
-                return {
+                // The wasm module maps the contact the same way as the vCard importer
+                let watchEntry;
+                try {
+                    watchEntry = wasmModule.contact_phone_entry(name, primaryPhone.value, primaryPhone.type || '');
+                } catch (error) {
+                    log(`Skipping contact ${error.message || error}`, true);
+                    return [];
+                }
+                
+                return [{
                     resourceName: person.resourceName,
                     name: name,
                     phoneNumber: primaryPhone.value,
                     phoneType: primaryPhone.type || 'other',
-                    watchName: name.substring(0, 12), // Limit to 12 chars
-                    watchNumber: cleanPhoneNumber(primaryPhone.value),
-                    watchType: determinePhoneType(primaryPhone.type)
-                };
+                    watchName: watchEntry.name,
+                    watchNumber: watchEntry.number,
+                    watchType: watchEntry.type
+                }];
             });
         
         // Display contacts